@@ -0,0 +1,44 @@
+//! The fuzzed code paths, shared between the `cargo fuzz` targets and the corpus regression test.
+//! None of these may panic, whatever the input.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use torn_api::{
+    user::{self, Company, Icon, Profile},
+    ApiCategoryResponse, ApiResponse,
+};
+
+// custom deserializers get fed both by serde_json directly and through `serde_json::Value`
+fn decode<'a, T>(data: &'a [u8], value: &'a serde_json::Value)
+where
+    T: Deserialize<'a>,
+{
+    _ = serde_json::from_slice::<T>(data);
+    _ = T::deserialize(value);
+}
+
+/// `Profile` covers the faction, competition and company visitors.
+pub fn profile(data: &[u8]) {
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(data) else {
+        _ = serde_json::from_slice::<Profile>(data);
+        return;
+    };
+    decode::<Profile>(data, &value);
+
+    if let Ok(response) = ApiResponse::from_value(value) {
+        let response = user::Response::from_response(response);
+        _ = response.profile();
+        _ = response.into_owned();
+    }
+}
+
+pub fn icons(data: &[u8]) {
+    let value = serde_json::from_slice(data).unwrap_or_default();
+    decode::<HashMap<Icon, &str>>(data, &value);
+}
+
+pub fn company(data: &[u8]) {
+    let value = serde_json::from_slice(data).unwrap_or_default();
+    decode::<Company>(data, &value);
+}
@@ -0,0 +1,43 @@
+//! Runs the fuzz corpora, including inputs that crashed once and were added after fixing the
+//! crash, on stable without `cargo fuzz`.
+
+use std::path::Path;
+
+fn run(target: &str, fun: fn(&[u8])) {
+    run_dir(
+        &Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("corpus")
+            .join(target),
+        fun,
+    );
+}
+
+fn run_dir(dir: &Path, fun: fn(&[u8])) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        if std::panic::catch_unwind(|| fun(&data)).is_err() {
+            panic!("{} panicked", path.display());
+        }
+    }
+}
+
+#[test]
+fn profile() {
+    run("profile", torn_api_fuzz::profile);
+    // the user corpus of torn-api doubles as the seed corpus of this target
+    run_dir(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("../torn-api/fixtures/corpus/user"),
+        torn_api_fuzz::profile,
+    );
+}
+
+#[test]
+fn icons() {
+    run("icons", torn_api_fuzz::icons);
+}
+
+#[test]
+fn company() {
+    run("company", torn_api_fuzz::company);
+}
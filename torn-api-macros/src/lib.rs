@@ -22,7 +22,10 @@ struct ApiAttribute {
     raw_value: String,
     variant: syn::Ident,
     type_name: proc_macro2::TokenStream,
+    type_str: String,
     with: Option<syn::Ident>,
+    requires_id: bool,
+    parallel: bool,
 }
 
 fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
@@ -58,6 +61,8 @@ fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
             let mut r#type: Option<String> = None;
             let mut field: Option<ApiField> = None;
             let mut with: Option<proc_macro2::Ident> = None;
+            let mut requires_id = false;
+            let mut parallel = false;
             for attr in &variant.attrs {
                 if attr.path().is_ident("api") {
                     attr.parse_nested_meta(|meta| {
@@ -76,6 +81,12 @@ fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
                         } else if meta.path.is_ident("flatten") {
                             field = Some(ApiField::Flattened);
                             Ok(())
+                        } else if meta.path.is_ident("requires_id") {
+                            requires_id = true;
+                            Ok(())
+                        } else if meta.path.is_ident("parallel") {
+                            parallel = true;
+                            Ok(())
                         } else {
                             Err(meta.error("unsupported attribute"))
                         }
@@ -83,13 +94,17 @@ fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
                     .unwrap();
                     let name = format_ident!("{}", variant.ident.to_string().to_case(Case::Snake));
                     let raw_value = variant.ident.to_string().to_lowercase();
+                    let type_str = r#type.expect("type must be specified");
                     return Some(ApiAttribute {
                         field: field.expect("field or flatten attribute must be specified"),
                         raw_value,
                         variant: variant.ident.clone(),
-                        type_name: r#type.expect("type must be specified").parse().unwrap(),
+                        type_name: type_str.parse().unwrap(),
+                        type_str,
                         name,
                         with,
+                        requires_id,
+                        parallel,
                     });
                 }
             }
@@ -101,34 +116,139 @@ fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
         |ApiAttribute {
              field,
              name,
+             raw_value,
              type_name,
+             type_str,
              with,
+             parallel,
              ..
-         }| match (field, with) {
-            (ApiField::Property(prop), None) => {
-                let prop_str = prop.to_string();
-                quote! {
-                    pub fn #name(&self) -> serde_json::Result<#type_name> {
-                        self.0.decode_field(#prop_str)
+         }| {
+            let mut static_ty: syn::Type = syn::parse_str(type_str).unwrap();
+            let (generics, receiver) = if to_static_lt(&mut static_ty) {
+                (quote! { <'a> }, quote! { &'a self })
+            } else {
+                (quote! {}, quote! { &self })
+            };
+            let parallel_accessor = match field {
+                ApiField::Property(prop) if *parallel => {
+                    let prop_str = prop.to_string();
+                    let par_name = format_ident!("{}_par", name);
+                    let (key_ty, mut value_ty) =
+                        map_args(&static_ty).expect("parallel selections must be maps");
+                    // the entries borrow from the decoding thread, so only owned values are
+                    // handed back
+                    let (owned_ty, value) = if elide_lt(&mut value_ty) {
+                        (
+                            quote! { <#static_ty as crate::into_owned::IntoOwned>::Owned },
+                            quote! {
+                                crate::into_owned::IntoOwned::into_owned(
+                                    <#value_ty as serde::Deserialize>::deserialize(value)?
+                                )
+                            },
+                        )
+                    } else {
+                        (
+                            quote! { #static_ty },
+                            quote! { <#value_ty as serde::Deserialize>::deserialize(value)? },
+                        )
+                    };
+                    quote! {
+                        #[cfg(feature = "rayon")]
+                        pub async fn #par_name(&self) -> Result<#owned_ty, crate::SelectionError> {
+                            self.0
+                                .decode_field_par(#raw_value, #type_str, #prop_str, |key, value| {
+                                    let key = <#key_ty as serde::Deserialize>::deserialize(
+                                        crate::de_util::MapKeyDeserializer(key),
+                                    )?;
+                                    Ok((key, #value))
+                                })
+                                .await
+                        }
+                    }
+                }
+                ApiField::Property(_) | ApiField::Flattened => quote! {},
+            };
+            let accessor = match (field, with) {
+                (ApiField::Property(prop), None) => {
+                    let prop_str = prop.to_string();
+                    quote! {
+                        pub fn #name #generics(#receiver) -> Result<#type_name, crate::SelectionError> {
+                            self.0.decode_field(#raw_value, #type_str, #prop_str)
+                        }
                     }
                 }
+                (ApiField::Property(prop), Some(f)) => {
+                    let prop_str = prop.to_string();
+                    quote! {
+                        pub fn #name #generics(#receiver) -> Result<#type_name, crate::SelectionError> {
+                            self.0.decode_field_with(#raw_value, #type_str, #prop_str, |de| #f(de))
+                        }
+                    }
+                }
+                // there's no property to look for, so an absent flattened selection can only
+                // surface as a malformed one
+                (ApiField::Flattened, None) => quote! {
+                    pub fn #name #generics(#receiver) -> Result<#type_name, crate::SelectionError> {
+                        self.0.decode(#raw_value, #type_str)
+                    }
+                },
+                (ApiField::Flattened, Some(_)) => todo!(),
+            };
+            quote! {
+                #accessor
+                #parallel_accessor
             }
-            (ApiField::Property(prop), Some(f)) => {
+        },
+    );
+
+    let properties: Vec<_> = fields
+        .iter()
+        .filter_map(|f| match &f.field {
+            ApiField::Property(prop) => Some(prop),
+            ApiField::Flattened => None,
+        })
+        .collect();
+
+    let mut owned_fields = Vec::with_capacity(fields.len());
+    let mut owned_values = Vec::with_capacity(fields.len());
+    for ApiAttribute {
+        field,
+        name: accessor,
+        type_str,
+        variant,
+        ..
+    } in &fields
+    {
+        let mut ty: syn::Type = syn::parse_str(type_str).unwrap();
+        let (owned_ty, convert) = if to_static_lt(&mut ty) {
+            (
+                quote! { <#ty as crate::into_owned::IntoOwned>::Owned },
+                quote! { .map(crate::into_owned::IntoOwned::into_owned) },
+            )
+        } else {
+            (quote! { #ty }, quote! {})
+        };
+        owned_fields.push(quote! { pub #accessor: Option<#owned_ty> });
+
+        // flattened selections can't be told apart from absent ones, and selections sharing a
+        // property with another one may hold the other's payload, so those rely on the request
+        let value = match field {
+            ApiField::Property(prop) if properties.iter().filter(|p| *p == &prop).count() == 1 => {
                 let prop_str = prop.to_string();
                 quote! {
-                    pub fn #name(&self) -> serde_json::Result<#type_name> {
-                        self.0.decode_field_with(#prop_str, #f)
-                    }
+                    (if self.0.has_field(#prop_str) { Some(self.#accessor()?) } else { None })#convert
                 }
             }
-            (ApiField::Flattened, None) => quote! {
-                pub fn #name(&self) -> serde_json::Result<#type_name> {
-                    self.0.decode()
-                }
+            _ => quote! {
+                (match self.0.was_requested(#name::#variant) {
+                    Some(true) => Some(self.#accessor()?),
+                    Some(false) => None,
+                    None => self.#accessor().ok(),
+                })#convert
             },
-            (ApiField::Flattened, Some(_)) => todo!(),
-        },
-    );
+        };
+        owned_values.push(quote! { #accessor: #value });
+    }
 
     let raw_values = fields.iter().map(
         |ApiAttribute {
@@ -140,11 +260,90 @@ fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
         },
     );
 
+    assert!(
+        fields.len() <= 64,
+        "selection sets only support up to 64 selections per category"
+    );
+
+    let indices = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, ApiAttribute { variant, .. })| quote! { #name::#variant => #idx });
+
+    let variants = fields
+        .iter()
+        .map(|ApiAttribute { variant, .. }| quote! { #name::#variant });
+
+    let requires_id: Vec<_> = fields
+        .iter()
+        .filter(|f| f.requires_id)
+        .map(|ApiAttribute { variant, .. }| quote! { #name::#variant })
+        .collect();
+
+    let requires_id = if requires_id.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn requires_id(self) -> bool {
+                matches!(self, #(#requires_id)|*)
+            }
+        }
+    };
+
+    // selections which are returned under the same key can't be requested together
+    let mut conflicts = Vec::new();
+    for (idx, first) in fields.iter().enumerate() {
+        let ApiField::Property(first_prop) = &first.field else {
+            continue;
+        };
+        for second in &fields[idx + 1..] {
+            if let ApiField::Property(second_prop) = &second.field {
+                if first_prop == second_prop {
+                    let (a, b) = (&first.variant, &second.variant);
+                    conflicts.push(quote! { (#name::#a, #name::#b) | (#name::#b, #name::#a) });
+                }
+            }
+        }
+    }
+
+    let conflicts_with = if conflicts.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn conflicts_with(self, other: Self) -> bool {
+                matches!((self, other), #(#conflicts)|*)
+            }
+        }
+    };
+
     let gen = quote! {
         pub struct Response(crate::ApiResponse);
 
         impl Response {
             #(#accessors)*
+
+            pub fn server_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+                self.0.server_time()
+            }
+
+            pub fn age(&self) -> Option<chrono::Duration> {
+                self.0.age()
+            }
+
+            /// Decodes all selections of the response. Flattened selections and ones sharing a
+            /// field with another selection are only decoded if they were requested, see
+            /// [`ApiResponse::with_selections`](crate::ApiResponse::with_selections); responses
+            /// which don't know their request skip those selections if they fail to decode.
+            pub fn into_owned(self) -> Result<OwnedResponse, crate::SelectionError> {
+                Ok(OwnedResponse {
+                    #(#owned_values,)*
+                })
+            }
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct OwnedResponse {
+            #(#owned_fields,)*
         }
 
         impl crate::ApiCategoryResponse for Response {
@@ -165,6 +364,20 @@ fn impl_api_category(ast: &syn::DeriveInput) -> TokenStream {
             fn category() -> &'static str {
                 #category
             }
+
+            fn index(self) -> usize {
+                match self {
+                    #(#indices,)*
+                }
+            }
+
+            fn all() -> &'static [Self] {
+                &[#(#variants),*]
+            }
+
+            #requires_id
+
+            #conflicts_with
         }
     };
 
@@ -191,11 +404,7 @@ fn to_static_lt(ty: &mut syn::Type) -> bool {
             {
                 for mut arg in &mut ab.args {
                     match &mut arg {
-                        syn::GenericArgument::Type(ty) => {
-                            if to_static_lt(ty) {
-                                res = true;
-                            }
-                        }
+                        syn::GenericArgument::Type(ty) => res |= to_static_lt(ty),
                         syn::GenericArgument::Lifetime(lt) => {
                             lt.ident = syn::Ident::new("static", proc_macro2::Span::call_site());
                             res = true;
@@ -217,17 +426,72 @@ fn to_static_lt(ty: &mut syn::Type) -> bool {
     res
 }
 
+// the key and value types of a map type like `BTreeMap<K, V>`
+fn map_args(ty: &syn::Type) -> Option<(syn::Type, syn::Type)> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(ab) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    let mut types = ab.args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    });
+    Some((types.next()?, types.next()?))
+}
+
+// like `to_static_lt`, but leaving the lifetimes to inference
+fn elide_lt(ty: &mut syn::Type) -> bool {
+    let mut res = false;
+    match ty {
+        syn::Type::Path(path) => {
+            if let Some(syn::PathArguments::AngleBracketed(ab)) = path
+                .path
+                .segments
+                .last_mut()
+                .map(|s| &mut s.arguments)
+                .as_mut()
+            {
+                for arg in &mut ab.args {
+                    match arg {
+                        syn::GenericArgument::Type(ty) => res |= elide_lt(ty),
+                        syn::GenericArgument::Lifetime(lt) => {
+                            *lt = syn::Lifetime::new("'_", proc_macro2::Span::call_site());
+                            res = true;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        syn::Type::Reference(r) => {
+            if r.lifetime.is_some() {
+                r.lifetime = Some(syn::Lifetime::new("'_", proc_macro2::Span::call_site()));
+                res = true;
+            }
+            elide_lt(&mut r.elem);
+        }
+        _ => (),
+    };
+    res
+}
+
 fn impl_into_owned(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
     let mut identity = false;
+    let mut archive = false;
     for attr in &ast.attrs {
         if attr.path().is_ident("into_owned") {
             attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("identity") {
                     identity = true;
                     Ok(())
+                } else if meta.path.is_ident("archive") {
+                    archive = true;
+                    Ok(())
                 } else {
                     Err(meta.error("unknown attribute"))
                 }
@@ -289,11 +553,33 @@ fn impl_into_owned(ast: &syn::DeriveInput) -> TokenStream {
         proc_macro2::Span::call_site(),
     );
 
+    let archive_attr = if archive {
+        quote! {
+            #[cfg_attr(
+                feature = "rkyv",
+                derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+                archive(check_bytes)
+            )]
+        }
+    } else {
+        quote! {}
+    };
+
+    let alias = syn::Ident::new(
+        &format!("Owned{}", ast.ident),
+        proc_macro2::Span::call_site(),
+    );
+    let alias_doc = format!("The owned form of [`{name}`].");
+
     let gen = quote! {
         #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+        #archive_attr
         #vis struct #owned_name {
             #(#owned_fields,)*
         }
+        #[doc = #alias_doc]
+        #vis type #alias = #owned_name;
         impl #impl_generics crate::into_owned::IntoOwned for #name #ty_generics #where_clause {
             type Owned = #owned_name;
             fn into_owned(self) -> Self::Owned {
@@ -1,5 +1,7 @@
+use std::sync::Arc;
+
 use criterion::{criterion_group, criterion_main, Criterion};
-use torn_api::{faction, send::ApiClient, user};
+use torn_api::{bulk::BufferPool, faction, send::ApiClient, user, ApiResponse, RawResponse};
 
 pub fn user_benchmark(c: &mut Criterion) {
     dotenv::dotenv().unwrap();
@@ -15,7 +17,7 @@ pub fn user_benchmark(c: &mut Criterion) {
         client
             .torn_api(key)
             .user(|b| {
-                b.selections(&[
+                b.selections([
                     user::Selection::Basic,
                     user::Selection::Discord,
                     user::Selection::Profile,
@@ -49,7 +51,7 @@ pub fn faction_benchmark(c: &mut Criterion) {
 
         client
             .torn_api(key)
-            .faction(|b| b.selections(&[faction::Selection::Basic]))
+            .faction(|b| b.selections([faction::Selection::Basic]))
             .await
             .unwrap()
     });
@@ -74,7 +76,7 @@ pub fn attacks_full(c: &mut Criterion) {
 
         client
             .torn_api(key)
-            .faction(|b| b.selections(&[faction::Selection::AttacksFull]))
+            .faction(|b| b.selections([faction::Selection::AttacksFull]))
             .await
             .unwrap()
     });
@@ -86,5 +88,42 @@ pub fn attacks_full(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, user_benchmark, faction_benchmark, attacks_full);
+// reads a body in chunks like the clients do, into a fresh or a reused buffer
+pub fn buffer_pool(c: &mut Criterion) {
+    let body = include_bytes!("../fixtures/corpus/user/1000-faction-member.json");
+    let raw = |body: Vec<u8>| RawResponse {
+        status: 200,
+        content_type: Some("application/json".to_owned()),
+        body,
+    };
+
+    c.bench_function("response fresh buffer", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::new();
+            for chunk in body.chunks(256) {
+                buffer.extend_from_slice(chunk);
+            }
+            ApiResponse::from_raw(raw(buffer)).unwrap()
+        })
+    });
+
+    let buffers = Arc::new(BufferPool::default());
+    c.bench_function("response pooled buffer", |b| {
+        b.iter(|| {
+            let mut buffer = buffers.take();
+            for chunk in body.chunks(256) {
+                buffer.extend_from_slice(chunk);
+            }
+            ApiResponse::from_raw_pooled(raw(buffer), &buffers).unwrap()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    buffer_pool,
+    user_benchmark,
+    faction_benchmark,
+    attacks_full
+);
 criterion_main!(benches);
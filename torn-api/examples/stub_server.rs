@@ -0,0 +1,54 @@
+//! Serves the response corpus in `fixtures/corpus` on localhost, so applications can be run
+//! against edge cases without using a key:
+//!
+//! `cargo run --example stub_server -- 8080` and then e.g.
+//! `curl 'http://localhost:8080/user/1002?selections=profile&key=any'`
+//!
+//! Each corpus file is served for the id it is prefixed with, regardless of the selections.
+
+use std::{convert::Infallible, net::SocketAddr, path::Path};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+
+fn lookup(category: &str, id: &str) -> Option<Vec<u8>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures/corpus")
+        .join(category);
+
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| entry.file_name().to_string_lossy().split('-').next() == Some(id))
+        .and_then(|entry| std::fs::read(entry.path()).ok())
+}
+
+async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let mut segments = request.uri().path().trim_matches('/').split('/');
+    let category = segments.next().unwrap_or_default();
+    let id = segments.next().unwrap_or_default();
+
+    let body = lookup(category, id)
+        .unwrap_or_else(|| br#"{"error":{"code":6,"error":"Incorrect ID"}}"#.to_vec());
+
+    Ok(Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let port = std::env::args()
+        .nth(1)
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(8080);
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(handle)) });
+
+    println!("serving the fixture corpus on http://{addr}");
+    Server::bind(&addr).serve(make_service).await.unwrap();
+}
@@ -0,0 +1,285 @@
+use std::marker::PhantomData;
+
+use bumpalo::{collections::Vec as BumpVec, Bump};
+use serde::{
+    de::{DeserializeSeed, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::value::RawValue;
+
+use crate::{
+    send::ApiClient, ApiClientError, ApiRequestBuilder, ApiResponse, ApiSelection,
+    DeserializeError, RawResponse, ResponseError, SelectionError,
+};
+
+/// A response whose body and top level fields live in a caller provided arena. Selections are
+/// deserialized straight from the body, borrowing all strings from it, instead of going through
+/// an intermediate [`serde_json::Value`].
+pub struct ArenaResponse<'bump> {
+    bump: &'bump Bump,
+    body: &'bump [u8],
+    fields: BumpVec<'bump, (&'bump str, &'bump RawValue)>,
+}
+
+struct FieldsSeed<'bump>(&'bump Bump);
+
+impl<'de> DeserializeSeed<'de> for FieldsSeed<'de> {
+    type Value = BumpVec<'de, (&'de str, &'de RawValue)>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldsSeed<'de> {
+    type Value = BumpVec<'de, (&'de str, &'de RawValue)>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("response object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = BumpVec::new_in(self.0);
+        while let Some(entry) = map.next_entry()? {
+            fields.push(entry);
+        }
+        Ok(fields)
+    }
+}
+
+struct PairsSeed<'bump, K, V> {
+    bump: &'bump Bump,
+    phantom: PhantomData<(K, V)>,
+}
+
+impl<'de, K, V> DeserializeSeed<'de> for PairsSeed<'de, K, V>
+where
+    K: Deserialize<'de> + 'de,
+    V: Deserialize<'de> + 'de,
+{
+    type Value = BumpVec<'de, (K, V)>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for PairsSeed<'de, K, V>
+where
+    K: Deserialize<'de> + 'de,
+    V: Deserialize<'de> + 'de,
+{
+    type Value = BumpVec<'de, (K, V)>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut pairs = BumpVec::with_capacity_in(map.size_hint().unwrap_or_default(), self.bump);
+        while let Some(entry) = map.next_entry()? {
+            pairs.push(entry);
+        }
+        Ok(pairs)
+    }
+}
+
+impl<'bump> ArenaResponse<'bump> {
+    pub fn from_raw(bump: &'bump Bump, raw: &RawResponse) -> Result<Self, ResponseError> {
+        #[derive(Deserialize)]
+        struct ApiErrorDto {
+            code: u8,
+            #[serde(rename = "error")]
+            reason: String,
+        }
+
+        ApiResponse::check_json(raw)?;
+
+        let body = bump.alloc_slice_copy(&raw.body);
+        let fields =
+            FieldsSeed(bump).deserialize(&mut serde_json::Deserializer::from_slice(body))?;
+
+        if let Some((_, error)) = fields.iter().find(|(name, _)| *name == "error") {
+            let dto: ApiErrorDto = serde_json::from_str(error.get())?;
+            return Err(ResponseError::from_code(dto.code.into(), dto.reason));
+        }
+
+        Ok(Self { bump, body, fields })
+    }
+
+    pub fn bump(&self) -> &'bump Bump {
+        self.bump
+    }
+
+    pub fn has_field(&self, field: &str) -> bool {
+        self.raw_field(field).is_some()
+    }
+
+    pub fn raw_field(&self, field: &str) -> Option<&'bump RawValue> {
+        self.fields
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, value)| *value)
+    }
+
+    /// Deserialize a selection which is spread over the top level of the response, like
+    /// `user::Selection::Profile`.
+    pub fn decode<D>(&self, selection: &'static str) -> Result<D, SelectionError>
+    where
+        D: Deserialize<'bump>,
+    {
+        serde_json::from_slice(self.body).map_err(|source| {
+            let raw = String::from_utf8_lossy(self.body).into_owned();
+            DeserializeError::with_raw(selection, std::any::type_name::<D>(), Some(raw), source)
+                .into()
+        })
+    }
+
+    /// Deserialize the selection stored in `field`, like `"attacks"`.
+    pub fn decode_field<D>(&self, field: &'static str) -> Result<D, SelectionError>
+    where
+        D: Deserialize<'bump>,
+    {
+        self.decode_field_with(field, |raw| serde_json::from_str(raw.get()))
+    }
+
+    /// Deserialize an object like `"attacks"` into key value pairs stored in the arena, so large
+    /// selections don't need an allocation per entry.
+    pub fn decode_pairs<K, V>(
+        &self,
+        field: &'static str,
+    ) -> Result<BumpVec<'bump, (K, V)>, SelectionError>
+    where
+        K: Deserialize<'bump>,
+        V: Deserialize<'bump>,
+    {
+        let bump = self.bump;
+        self.decode_field_with(field, |raw| {
+            PairsSeed {
+                bump,
+                phantom: PhantomData,
+            }
+            .deserialize(&mut serde_json::Deserializer::from_str(raw.get()))
+        })
+    }
+
+    /// Move `value` into the arena, e.g. to keep a decoded selection around for as long as the
+    /// response.
+    pub fn alloc<T>(&self, value: T) -> &'bump mut T {
+        self.bump.alloc(value)
+    }
+
+    fn decode_field_with<V, F>(&self, field: &'static str, fun: F) -> Result<V, SelectionError>
+    where
+        F: FnOnce(&'bump RawValue) -> serde_json::Result<V>,
+    {
+        match self.raw_field(field) {
+            Some(raw) => fun(raw).map_err(|source| {
+                DeserializeError::with_raw(
+                    field,
+                    std::any::type_name::<V>(),
+                    Some(raw.get().to_owned()),
+                    source,
+                )
+                .into()
+            }),
+            None => Err(SelectionError::Missing { selection: field }),
+        }
+    }
+}
+
+/// Send the request described by `builder` and keep the response in `bump`.
+pub async fn request_in<'bump, C, A>(
+    bump: &'bump Bump,
+    client: &C,
+    key: &str,
+    builder: ApiRequestBuilder<A>,
+) -> Result<ArenaResponse<'bump>, ApiClientError<C::Error>>
+where
+    C: ApiClient,
+    A: ApiSelection,
+{
+    builder.request.validate(builder.id.is_some())?;
+
+    let url = builder.request.url(key, builder.id.as_deref());
+    let raw = client.request(url).await.map_err(ApiClientError::Client)?;
+
+    Ok(ArenaResponse::from_raw(bump, &raw)?)
+}
+
+#[cfg(all(test, feature = "__common"))]
+mod tests {
+    use super::*;
+    use crate::{common::AttackFull, UserId};
+
+    #[test]
+    fn attacks_in_arena() {
+        let attack = serde_json::json!({
+            "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+            "timestamp_started": 1_650_000_000,
+            "timestamp_ended": 1_650_000_100,
+            "attacker_id": "",
+            "attacker_name": "",
+            "attacker_faction": "",
+            "attacker_factionname": "",
+            "defender_id": 28,
+            "defender_name": "Chedburn",
+            "defender_faction": "",
+            "defender_factionname": "",
+            "result": "Lost",
+            "stealthed": 1,
+            "raid": 0,
+            "ranked_war": 0,
+            "respect": 0.0,
+            "respect_loss": 0.0,
+            "modifiers": {
+                "fair_fight": 1.0,
+                "war": 1,
+                "retaliation": 1,
+                "group_attack": 1,
+                "overseas": 1,
+                "chain_bonus": 1,
+            },
+        });
+        let body = serde_json::json!({ "attacks": { "1": attack, "2": attack } });
+        let raw = RawResponse {
+            status: 200,
+            content_type: Some("application/json".to_owned()),
+            body: serde_json::to_vec(&body).unwrap(),
+        };
+
+        let bump = Bump::new();
+        let response = ArenaResponse::from_raw(&bump, &raw).unwrap();
+        let attacks = response.decode_pairs::<i64, AttackFull>("attacks").unwrap();
+        assert_eq!(attacks.len(), 2);
+        assert_eq!(attacks[1].0, 2);
+        assert_eq!(attacks[1].1.defender_id, UserId(28));
+        assert_eq!(attacks[0].1.defender_name, "Chedburn");
+
+        assert!(response
+            .decode_field::<AttackFull>("chain")
+            .unwrap_err()
+            .is_missing());
+
+        let error = RawResponse {
+            body: br#"{"error":{"code":2,"error":"Incorrect key"}}"#.to_vec(),
+            ..raw
+        };
+        assert!(matches!(
+            ArenaResponse::from_raw(&bump, &error),
+            Err(ResponseError::Key { .. })
+        ));
+    }
+}
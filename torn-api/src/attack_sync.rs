@@ -0,0 +1,243 @@
+use std::{collections::BTreeMap, future::Future};
+
+use thiserror::Error;
+
+use crate::{
+    common::AttackFullOwned,
+    send::{ApiClient, ApiProvider, RequestExecutor},
+    IntoOwned, SelectionError,
+};
+
+/// Maximum number of entries returned by the `attacks` selection.
+pub const PAGE_SIZE: usize = 100;
+
+#[derive(Error, Debug)]
+pub enum SyncError<E, S>
+where
+    E: std::error::Error,
+    S: std::error::Error,
+{
+    #[error(transparent)]
+    Api(E),
+
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+
+    #[error("Failed to store attacks: {0}")]
+    Storage(#[source] S),
+}
+
+/// The newest attack which has been stored so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttackCursor {
+    pub id: i64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AttackLog {
+    /// Attacks of the key owner or, if an id is given, of that user.
+    #[cfg(feature = "user")]
+    User(Option<crate::UserId>),
+    /// Attacks of the key owner's faction or, if an id is given, of that faction.
+    #[cfg(feature = "faction")]
+    Faction(Option<crate::FactionId>),
+}
+
+/// Keeps a local copy of an attack log up to date.
+#[derive(Debug, Clone)]
+pub struct AttackSync {
+    log: AttackLog,
+    cursor: AttackCursor,
+}
+
+impl AttackSync {
+    pub fn new(log: AttackLog, cursor: AttackCursor) -> Self {
+        Self { log, cursor }
+    }
+
+    pub fn cursor(&self) -> AttackCursor {
+        self.cursor
+    }
+
+    /// Request the attacks started since the cursor, oldest first, until fewer than a full page
+    /// is returned. Attacks which were already seen are skipped and every page of new attacks
+    /// is passed to `store` before the cursor moves past it, so a failed sync can simply be
+    /// run again. Returns the number of new attacks.
+    pub async fn run<C, E, F, Fut, S>(
+        &mut self,
+        provider: &ApiProvider<'_, C, E>,
+        mut store: F,
+    ) -> Result<usize, SyncError<E::Error, S>>
+    where
+        C: ApiClient,
+        E: RequestExecutor<C>,
+        F: FnMut(BTreeMap<i64, AttackFullOwned>) -> Fut,
+        Fut: Future<Output = Result<(), S>>,
+        S: std::error::Error,
+    {
+        let mut total = 0;
+        loop {
+            let mut attacks = self.fetch::<_, _, S>(provider).await?;
+            let full = attacks.len() >= PAGE_SIZE;
+            attacks.retain(|id, _| *id > self.cursor.id);
+
+            let next = match attacks.last_key_value() {
+                Some((id, _)) => AttackCursor {
+                    id: *id,
+                    timestamp: attacks
+                        .values()
+                        .map(|a| a.timestamp_started.timestamp())
+                        .max()
+                        .unwrap_or(self.cursor.timestamp),
+                },
+                // a full page of attacks started in the same second; skip ahead
+                None if full => AttackCursor {
+                    timestamp: self.cursor.timestamp + 1,
+                    ..self.cursor
+                },
+                None => return Ok(total),
+            };
+
+            if !attacks.is_empty() {
+                total += attacks.len();
+                store(attacks).await.map_err(SyncError::Storage)?;
+            }
+            self.cursor = next;
+
+            if !full {
+                return Ok(total);
+            }
+        }
+    }
+
+    async fn fetch<C, E, S>(
+        &self,
+        provider: &ApiProvider<'_, C, E>,
+    ) -> Result<BTreeMap<i64, AttackFullOwned>, SyncError<E::Error, S>>
+    where
+        C: ApiClient,
+        E: RequestExecutor<C>,
+        S: std::error::Error,
+    {
+        let from = self.cursor.timestamp;
+        match self.log {
+            #[cfg(feature = "user")]
+            AttackLog::User(id) => {
+                let response = provider
+                    .user(|b| {
+                        let b = b
+                            .selections([crate::user::Selection::Attacks])
+                            .from_timestamp(from);
+                        match id {
+                            Some(id) => b.id(id),
+                            None => b,
+                        }
+                    })
+                    .await
+                    .map_err(SyncError::Api)?;
+                Ok(response.attacks()?.into_owned())
+            }
+            #[cfg(feature = "faction")]
+            AttackLog::Faction(id) => {
+                let response = provider
+                    .faction(|b| {
+                        let b = b
+                            .selections([crate::faction::Selection::Attacks])
+                            .from_timestamp(from);
+                        match id {
+                            Some(id) => b.id(id),
+                            None => b,
+                        }
+                    })
+                    .await
+                    .map_err(SyncError::Api)?;
+                Ok(response.attacks()?.into_owned())
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock", feature = "user"))]
+mod tests {
+    use super::*;
+    use crate::{mock::MockClient, user, UserId};
+
+    #[tokio::test]
+    async fn sync_new_attacks() {
+        let attack = |defender: i32, started: i64| {
+            serde_json::json!({
+                "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                "timestamp_started": started,
+                "timestamp_ended": started + 100,
+                "attacker_id": 2111649,
+                "attacker_name": "Pyrit",
+                "attacker_faction": "",
+                "attacker_factionname": "",
+                "defender_id": defender,
+                "defender_name": "",
+                "defender_faction": "",
+                "defender_factionname": "",
+                "result": "Hospitalized",
+                "stealthed": 0,
+                "raid": 0,
+                "ranked_war": 0,
+                "respect": 0.0,
+                "respect_loss": 0.0,
+                "modifiers": {
+                    "fair_fight": 1.0,
+                    "war": 1,
+                    "retaliation": 1,
+                    "group_attack": 1,
+                    "overseas": 1,
+                    "chain_bonus": 1,
+                },
+            })
+        };
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [user::Selection::Attacks],
+            serde_json::json!({
+                "attacks": {
+                    "10": attack(1, 1_650_000_000),
+                    "11": attack(4, 1_650_000_050),
+                    "12": attack(28, 1_650_000_050),
+                }
+            }),
+        );
+        let provider = client.torn_api("key");
+
+        let mut sync = AttackSync::new(
+            AttackLog::User(None),
+            AttackCursor {
+                id: 10,
+                timestamp: 1_650_000_000,
+            },
+        );
+        let mut stored = Vec::new();
+        let count = sync
+            .run(&provider, |attacks| {
+                stored.extend(attacks.into_values().map(|a| a.defender_id));
+                async { Ok::<_, std::io::Error>(()) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(stored, [UserId(4), UserId(28)]);
+        assert_eq!(
+            sync.cursor(),
+            AttackCursor {
+                id: 12,
+                timestamp: 1_650_000_050
+            }
+        );
+
+        // nothing new the second time around
+        let count = sync
+            .run(&provider, |_| async { Ok::<_, std::io::Error>(()) })
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+}
@@ -1,7 +1,10 @@
 use async_trait::async_trait;
 use thiserror::Error;
 
-use crate::local::ApiClient;
+use crate::{local::ApiClient, RawResponse};
+
+// large selections like faction attacks easily exceed awc's default body limit
+const BODY_LIMIT: usize = 32 * 1024 * 1024;
 
 #[derive(Error, Debug)]
 pub enum AwcApiClientError {
@@ -9,14 +12,54 @@ pub enum AwcApiClientError {
     Client(#[from] awc::error::SendRequestError),
 
     #[error(transparent)]
-    Payload(#[from] awc::error::JsonPayloadError),
+    Payload(#[from] awc::error::PayloadError),
 }
 
 #[async_trait(?Send)]
 impl ApiClient for awc::Client {
     type Error = AwcApiClientError;
 
-    async fn request(&self, url: String) -> Result<serde_json::Value, Self::Error> {
-        self.get(url).send().await?.json().await.map_err(Into::into)
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let mut response = self.get(url).send().await?;
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(awc::http::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+        let body = response.body().limit(BODY_LIMIT).await?.to_vec();
+
+        Ok(RawResponse {
+            status,
+            content_type,
+            body,
+        })
+    }
+}
+
+#[cfg(feature = "yata")]
+#[async_trait(?Send)]
+impl crate::yata::local::PostClient for awc::Client {
+    async fn post_json(&self, url: String, body: Vec<u8>) -> Result<RawResponse, Self::Error> {
+        let mut response = self
+            .post(url)
+            .insert_header((awc::http::header::CONTENT_TYPE, "application/json"))
+            .send_body(body)
+            .await?;
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(awc::http::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+        let body = response.body().limit(BODY_LIMIT).await?.to_vec();
+
+        Ok(RawResponse {
+            status,
+            content_type,
+            body,
+        })
     }
 }
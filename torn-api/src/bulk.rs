@@ -0,0 +1,343 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiClientError, ApiErrorCode, ResponseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureCause {
+    Api(ApiErrorCode),
+    MalformedResponse,
+    NonJsonResponse,
+    Client,
+    Validation,
+    Storage,
+}
+
+impl FailureCause {
+    pub fn is_retryable(self) -> bool {
+        match self {
+            Self::Api(code) => code.is_temporary(),
+            Self::Client | Self::NonJsonResponse => true,
+            Self::MalformedResponse | Self::Validation | Self::Storage => false,
+        }
+    }
+
+    /// Whether a request which failed like this may succeed if it's sent again right away. Unlike
+    /// running out of calls or a blocked IP, these don't need any waiting.
+    pub fn is_immediately_retryable(self) -> bool {
+        match self {
+            Self::Api(code) => matches!(
+                code,
+                ApiErrorCode::TemporaryError | ApiErrorCode::BackendError
+            ),
+            Self::Client | Self::NonJsonResponse => true,
+            Self::MalformedResponse | Self::Validation | Self::Storage => false,
+        }
+    }
+}
+
+pub trait ClassifyFailure {
+    fn cause(&self) -> FailureCause;
+}
+
+impl ClassifyFailure for ResponseError {
+    fn cause(&self) -> FailureCause {
+        match self {
+            Self::Key { code, .. } | Self::Request { code, .. } | Self::Service { code, .. } => {
+                FailureCause::Api(*code)
+            }
+            Self::MalformedResponse(_) => FailureCause::MalformedResponse,
+            Self::NonJson { .. } => FailureCause::NonJsonResponse,
+        }
+    }
+}
+
+impl<C> ClassifyFailure for ApiClientError<C>
+where
+    C: std::error::Error,
+{
+    fn cause(&self) -> FailureCause {
+        match self {
+            Self::Client(_) => FailureCause::Client,
+            Self::Response(why) => why.cause(),
+            Self::Validation(_) => FailureCause::Validation,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BulkReport<I> {
+    pub succeeded: usize,
+    pub failed: HashMap<FailureCause, Vec<I>>,
+}
+
+impl<I> BulkReport<I> {
+    pub fn failed_count(&self) -> usize {
+        self.failed.values().map(Vec::len).sum()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Progress of a bulk job, meant to be persisted after every chunk so that a job interrupted by
+/// a crash or by running out of calls can be resumed with the ids which are still pending.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "I: Deserialize<'de> + std::hash::Hash + Eq"))]
+pub struct BulkCheckpoint<I> {
+    pub completed: Vec<I>,
+    pub failed: Vec<I>,
+    pub pending: Vec<I>,
+    /// Number of retryable failures of the pending ids which failed before.
+    #[serde(default)]
+    pub retries: HashMap<I, u32>,
+    /// How often an id may fail for a retryable reason before it's counted as failed.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+impl<I> BulkCheckpoint<I> {
+    pub fn new(ids: impl IntoIterator<Item = I>) -> Self {
+        Self {
+            completed: Vec::new(),
+            failed: Vec::new(),
+            pending: ids.into_iter().collect(),
+            retries: HashMap::new(),
+            max_retries: default_max_retries(),
+        }
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Sort the results of `chunk` into the checkpoint, in the order of `chunk`. Ids which failed
+    /// for a retryable reason are put back in front of the pending ids, unless they already used
+    /// up their retries; returns whether any were put back.
+    pub fn record<T, E>(&mut self, chunk: &[I], results: &HashMap<I, Result<T, E>>) -> bool
+    where
+        I: Clone + std::hash::Hash + Eq,
+        E: ClassifyFailure,
+    {
+        let mut retry = Vec::new();
+        for id in chunk {
+            match results.get(id) {
+                Some(Ok(_)) => self.completed.push(id.clone()),
+                Some(Err(why)) if !why.cause().is_retryable() => self.failed.push(id.clone()),
+                // ids missing from the results are retried as well
+                _ => {
+                    let retries = self.retries.entry(id.clone()).or_default();
+                    *retries += 1;
+                    if *retries <= self.max_retries {
+                        retry.push(id.clone());
+                        continue;
+                    }
+                    self.failed.push(id.clone());
+                }
+            }
+            self.retries.remove(id);
+        }
+
+        let interrupted = !retry.is_empty();
+        retry.append(&mut self.pending);
+        self.pending = retry;
+        interrupted
+    }
+}
+
+/// Response body buffers shared by the requests of an executor, so bulk jobs don't allocate a
+/// fresh body for every id. At most `limit` idle buffers are kept around.
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    limit: usize,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::with_limit(64)
+    }
+}
+
+impl BufferPool {
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::with_capacity(limit)),
+            limit,
+        }
+    }
+
+    /// An empty buffer, reusing the capacity of a previously returned one if possible.
+    pub fn take(&self) -> Vec<u8> {
+        self.buffers.lock().unwrap().pop().unwrap_or_default()
+    }
+
+    pub fn put(&self, mut buffer: Vec<u8>) {
+        buffer.clear();
+        let mut buffers = self.buffers.lock().unwrap();
+        if buffers.len() < self.limit && buffer.capacity() > 0 {
+            buffers.push(buffer);
+        }
+    }
+
+    pub fn idle(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+}
+
+pub trait BulkResults<I, T, E> {
+    fn into_ok_and_errors(self) -> (HashMap<I, T>, HashMap<I, E>);
+
+    fn report(&self) -> BulkReport<I>
+    where
+        I: Clone;
+}
+
+impl<I, T, E> BulkResults<I, T, E> for HashMap<I, Result<T, E>>
+where
+    I: std::hash::Hash + std::cmp::Eq,
+    E: ClassifyFailure,
+{
+    fn into_ok_and_errors(self) -> (HashMap<I, T>, HashMap<I, E>) {
+        let mut ok = HashMap::with_capacity(self.len());
+        let mut errors = HashMap::new();
+
+        for (id, result) in self {
+            match result {
+                Ok(value) => {
+                    ok.insert(id, value);
+                }
+                Err(why) => {
+                    errors.insert(id, why);
+                }
+            }
+        }
+
+        (ok, errors)
+    }
+
+    fn report(&self) -> BulkReport<I>
+    where
+        I: Clone,
+    {
+        let mut succeeded = 0;
+        let mut failed: HashMap<FailureCause, Vec<I>> = HashMap::new();
+
+        for (id, result) in self {
+            match result {
+                Ok(_) => succeeded += 1,
+                Err(why) => failed.entry(why.cause()).or_default().push(id.clone()),
+            }
+        }
+
+        BulkReport { succeeded, failed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error = ApiClientError<std::io::Error>;
+
+    fn api_error(code: u8) -> Error {
+        ApiClientError::Response(ResponseError::from_code(code.into(), String::new()))
+    }
+
+    #[test]
+    fn split_and_report() {
+        let results: HashMap<i32, Result<&str, Error>> = HashMap::from([
+            (1, Ok("one")),
+            (2, Err(api_error(6))),
+            (3, Err(api_error(6))),
+            (4, Err(api_error(5))),
+        ]);
+
+        let report = results.report();
+        assert_eq!(report.succeeded, 1);
+        assert_eq!(report.failed_count(), 3);
+        assert_eq!(
+            report.failed[&FailureCause::Api(ApiErrorCode::IncorrectId)].len(),
+            2
+        );
+        assert!(FailureCause::Api(ApiErrorCode::TooManyRequests).is_retryable());
+        assert!(!FailureCause::Api(ApiErrorCode::TooManyRequests).is_immediately_retryable());
+        assert!(FailureCause::Api(ApiErrorCode::BackendError).is_immediately_retryable());
+
+        let (ok, errors) = results.into_ok_and_errors();
+        assert_eq!(ok.len(), 1);
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn checkpoint() {
+        let mut checkpoint = BulkCheckpoint::new([4, 3, 2, 1, 5]);
+        let chunk: Vec<_> = checkpoint.pending.drain(..4).collect();
+
+        let results: HashMap<i32, Result<(), Error>> = HashMap::from([
+            (1, Ok(())),
+            (2, Err(api_error(6))),
+            (3, Err(api_error(5))),
+            (4, Ok(())),
+        ]);
+        assert!(checkpoint.record(&chunk, &results));
+
+        assert_eq!(checkpoint.completed, [4, 1]);
+        assert_eq!(checkpoint.failed, [2]);
+        assert_eq!(checkpoint.pending, [3, 5]);
+        assert_eq!(checkpoint.retries[&3], 1);
+        assert!(!checkpoint.is_done());
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let resumed: BulkCheckpoint<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(resumed.pending, [3, 5]);
+        assert_eq!(resumed.retries[&3], 1);
+    }
+
+    #[test]
+    fn checkpoint_retries() {
+        let mut checkpoint = BulkCheckpoint::new([1, 2]).max_retries(1);
+
+        let chunk: Vec<_> = checkpoint.pending.drain(..).collect();
+        let results: HashMap<i32, Result<(), Error>> =
+            HashMap::from([(1, Err(api_error(5))), (2, Err(api_error(5)))]);
+        assert!(checkpoint.record(&chunk, &results));
+        assert_eq!(checkpoint.pending, [1, 2]);
+
+        let chunk: Vec<_> = checkpoint.pending.drain(..).collect();
+        let results = HashMap::from([(1, Err(api_error(5))), (2, Ok(()))]);
+        assert!(!checkpoint.record(&chunk, &results));
+        assert!(checkpoint.is_done());
+        assert_eq!(checkpoint.completed, [2]);
+        assert_eq!(checkpoint.failed, [1]);
+        assert!(checkpoint.retries.is_empty());
+    }
+
+    #[test]
+    fn buffer_reuse() {
+        let pool = BufferPool::with_limit(1);
+
+        let mut buffer = pool.take();
+        buffer.extend_from_slice(b"{\"player_id\":1}");
+        let capacity = buffer.capacity();
+        pool.put(buffer);
+        pool.put(Vec::with_capacity(16));
+        assert_eq!(pool.idle(), 1);
+
+        let buffer = pool.take();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.capacity(), capacity);
+        assert_eq!(pool.idle(), 0);
+    }
+}
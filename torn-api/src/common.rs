@@ -1,10 +1,12 @@
+use std::{collections::BTreeMap, time::Duration};
+
 use chrono::{serde::ts_seconds, DateTime, Utc};
-use serde::Deserialize;
-use torn_api_macros::IntoOwned;
+use serde::{Deserialize, Deserializer};
 
-use crate::de_util;
+use crate::{de_util, into_owned::IntoOwned, CourseId, FactionId, UserId};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum OnlineStatus {
     Online,
     Offline,
@@ -12,13 +14,43 @@ pub enum OnlineStatus {
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LastAction {
     #[serde(with = "ts_seconds")]
+    #[cfg_attr(feature = "schemars", schemars(with = "i64"))]
     pub timestamp: DateTime<Utc>,
     pub status: OnlineStatus,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+impl LastAction {
+    pub fn is_online(&self) -> bool {
+        self.status == OnlineStatus::Online
+    }
+
+    pub fn seconds_since(&self) -> i64 {
+        self.seconds_since_at(Utc::now())
+    }
+
+    pub fn seconds_since_at(&self, now: DateTime<Utc>) -> i64 {
+        (now - self.timestamp).num_seconds().max(0)
+    }
+
+    pub fn is_active_within(&self, window: Duration) -> bool {
+        self.is_active_within_at(window, Utc::now())
+    }
+
+    /// Whether the last action happened within `window` of `now`. An idle user may not have
+    /// done anything for a while, so the status isn't taken into account.
+    pub fn is_active_within_at(&self, window: Duration, now: DateTime<Utc>) -> bool {
+        self.seconds_since_at(now) as u64 <= window.as_secs()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum State {
     Okay,
     Traveling,
@@ -29,7 +61,23 @@ pub enum State {
     Fallen,
 }
 
+impl State {
+    /// The state as sent by the API.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Okay => "Okay",
+            Self::Traveling => "Traveling",
+            Self::Hospital => "Hospital",
+            Self::Abroad => "Abroad",
+            Self::Jail => "Jail",
+            Self::Federal => "Federal",
+            Self::Fallen => "Fallen",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum StateColour {
     Green,
@@ -37,16 +85,508 @@ pub enum StateColour {
     Blue,
 }
 
-#[derive(Debug, IntoOwned, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TravelMethod {
+    Standard,
+    Airstrip,
+    Private,
+    Business,
+}
+
+impl TravelMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "standard" => Some(Self::Standard),
+            "airstrip" => Some(Self::Airstrip),
+            "private" => Some(Self::Private),
+            "business" => Some(Self::Business),
+            _ => None,
+        }
+    }
+
+    fn time_factor(self) -> f64 {
+        match self {
+            Self::Standard => 1.0,
+            Self::Airstrip => 0.7,
+            Self::Private => 0.5,
+            Self::Business => 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum Country {
+    Torn,
+    Mexico,
+    CaymanIslands,
+    Canada,
+    Hawaii,
+    UnitedKingdom,
+    Argentina,
+    Switzerland,
+    Japan,
+    China,
+    Uae,
+    SouthAfrica,
+    /// A destination which isn't known to this version of the crate.
+    Other,
+}
+
+impl Country {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Torn => "Torn",
+            Self::Mexico => "Mexico",
+            Self::CaymanIslands => "Cayman Islands",
+            Self::Canada => "Canada",
+            Self::Hawaii => "Hawaii",
+            Self::UnitedKingdom => "United Kingdom",
+            Self::Argentina => "Argentina",
+            Self::Switzerland => "Switzerland",
+            Self::Japan => "Japan",
+            Self::China => "China",
+            Self::Uae => "UAE",
+            Self::SouthAfrica => "South Africa",
+            Self::Other => "Other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Torn" => Some(Self::Torn),
+            "Mexico" => Some(Self::Mexico),
+            "Cayman Islands" => Some(Self::CaymanIslands),
+            "Canada" => Some(Self::Canada),
+            "Hawaii" => Some(Self::Hawaii),
+            "United Kingdom" => Some(Self::UnitedKingdom),
+            "Argentina" => Some(Self::Argentina),
+            "Switzerland" => Some(Self::Switzerland),
+            "Japan" => Some(Self::Japan),
+            "China" => Some(Self::China),
+            "UAE" => Some(Self::Uae),
+            "South Africa" => Some(Self::SouthAfrica),
+            _ => None,
+        }
+    }
+
+    /// The three letter code used by YATA and the travel pages, `None` for Torn itself.
+    pub fn code(self) -> Option<&'static str> {
+        match self {
+            Self::Torn => None,
+            Self::Mexico => Some("mex"),
+            Self::CaymanIslands => Some("cay"),
+            Self::Canada => Some("can"),
+            Self::Hawaii => Some("haw"),
+            Self::UnitedKingdom => Some("uni"),
+            Self::Argentina => Some("arg"),
+            Self::Switzerland => Some("swi"),
+            Self::Japan => Some("jap"),
+            Self::China => Some("chi"),
+            Self::Uae => Some("uae"),
+            Self::SouthAfrica => Some("sou"),
+            Self::Other => None,
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::all().find(|country| country.code() == Some(code))
+    }
+
+    fn standard_minutes(self) -> Option<u64> {
+        Some(match self {
+            Self::Torn => 0,
+            Self::Mexico => 26,
+            Self::CaymanIslands => 35,
+            Self::Canada => 41,
+            Self::Hawaii => 134,
+            Self::UnitedKingdom => 159,
+            Self::Argentina => 167,
+            Self::Switzerland => 175,
+            Self::Japan => 225,
+            Self::China => 242,
+            Self::Uae => 271,
+            Self::SouthAfrica => 297,
+            Self::Other => return None,
+        })
+    }
+
+    /// One-way flight time between Torn and this country, `None` for [`Country::Other`].
+    pub fn flight_time(self, method: TravelMethod) -> Option<Duration> {
+        let secs = (self.standard_minutes()? * 60) as f64 * method.time_factor();
+        Some(Duration::from_secs(secs.round() as u64))
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::Torn,
+            Self::Mexico,
+            Self::CaymanIslands,
+            Self::Canada,
+            Self::Hawaii,
+            Self::UnitedKingdom,
+            Self::Argentina,
+            Self::Switzerland,
+            Self::Japan,
+            Self::China,
+            Self::Uae,
+            Self::SouthAfrica,
+        ]
+        .into_iter()
+    }
+}
+
+impl std::fmt::Display for Country {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Country {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        match Self::from_name(&name) {
+            Some(country) => Ok(country),
+            None => {
+                de_util::fallback::<D::Error>(&name, "country name")?;
+                Ok(Self::Other)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum StatusKind<S> {
+    Okay,
+    Hospital {
+        until: DateTime<Utc>,
+    },
+    Jail {
+        until: DateTime<Utc>,
+    },
+    Federal {
+        until: Option<DateTime<Utc>>,
+        reason: Option<S>,
+    },
+    Traveling {
+        from: Country,
+        to: Country,
+    },
+    Abroad {
+        location: Country,
+    },
+    Fallen,
+    /// The state is known but its description couldn't be interpreted.
+    Other,
+}
+
+impl<'a> StatusKind<&'a str> {
+    fn parse(
+        state: State,
+        description: &'a str,
+        details: Option<&'a str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Self {
+        match (state, until) {
+            (State::Okay, _) => Self::Okay,
+            (State::Hospital, Some(until)) => Self::Hospital { until },
+            (State::Jail, Some(until)) => Self::Jail { until },
+            (State::Federal, until) => Self::Federal {
+                until,
+                reason: details,
+            },
+            (State::Traveling, _) => {
+                let route = if let Some(rest) = description.strip_prefix("Returning to ") {
+                    rest.split_once(" from ").and_then(|(to, from)| {
+                        Some((Country::from_name(from)?, Country::from_name(to)?))
+                    })
+                } else {
+                    description
+                        .strip_prefix("Traveling to ")
+                        .and_then(Country::from_name)
+                        .map(|to| (Country::Torn, to))
+                };
+                match route {
+                    Some((from, to)) => Self::Traveling { from, to },
+                    None => Self::Other,
+                }
+            }
+            (State::Abroad, _) => {
+                match description.strip_prefix("In ").and_then(Country::from_name) {
+                    Some(location) => Self::Abroad { location },
+                    None => Self::Other,
+                }
+            }
+            (State::Fallen, _) => Self::Fallen,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl IntoOwned for StatusKind<&str> {
+    type Owned = StatusKind<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Okay => StatusKind::Okay,
+            Self::Hospital { until } => StatusKind::Hospital { until },
+            Self::Jail { until } => StatusKind::Jail { until },
+            Self::Federal { until, reason } => StatusKind::Federal {
+                until,
+                reason: reason.map(ToOwned::to_owned),
+            },
+            Self::Traveling { from, to } => StatusKind::Traveling { from, to },
+            Self::Abroad { location } => StatusKind::Abroad { location },
+            Self::Fallen => StatusKind::Fallen,
+            Self::Other => StatusKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, IntoOwned)]
 pub struct Status<'a> {
     pub description: &'a str,
-    #[serde(deserialize_with = "de_util::empty_string_is_none")]
     pub details: Option<&'a str>,
-    #[serde(rename = "color")]
     pub colour: StateColour,
     pub state: State,
-    #[serde(deserialize_with = "de_util::zero_date_is_none")]
     pub until: Option<DateTime<Utc>>,
+    pub kind: StatusKind<&'a str>,
+    #[cfg(feature = "unknown-fields")]
+    pub unknown_fields: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Status<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct StatusDto<'a> {
+            description: &'a str,
+            #[serde(deserialize_with = "de_util::empty_string_is_none")]
+            details: Option<&'a str>,
+            #[serde(rename = "color")]
+            colour: StateColour,
+            state: State,
+            #[serde(deserialize_with = "de_util::zero_date_is_none")]
+            until: Option<DateTime<Utc>>,
+            #[cfg(feature = "unknown-fields")]
+            #[serde(flatten)]
+            unknown_fields: std::collections::BTreeMap<String, serde_json::Value>,
+        }
+
+        let dto = StatusDto::deserialize(deserializer)?;
+        let kind = StatusKind::parse(dto.state, dto.description, dto.details, dto.until);
+        if matches!(kind, StatusKind::Other) {
+            de_util::fallback::<D::Error>(dto.description, "status description")?;
+        }
+
+        Ok(Self {
+            kind,
+            description: dto.description,
+            details: dto.details,
+            colour: dto.colour,
+            state: dto.state,
+            until: dto.until,
+            #[cfg(feature = "unknown-fields")]
+            unknown_fields: dto.unknown_fields,
+        })
+    }
+}
+
+impl Status<'_> {
+    pub fn is_okay(&self) -> bool {
+        self.state == State::Okay
+    }
+
+    pub fn is_in_hospital(&self) -> bool {
+        self.state == State::Hospital
+    }
+
+    pub fn is_in_jail(&self) -> bool {
+        matches!(self.state, State::Jail | State::Federal)
+    }
+
+    pub fn is_travelling(&self) -> bool {
+        self.state == State::Traveling
+    }
+
+    pub fn is_abroad(&self) -> bool {
+        self.state == State::Abroad
+    }
+
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.time_remaining_at(Utc::now())
+    }
+
+    pub fn time_remaining_at(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.until
+            .map(|until| (until - now).to_std().unwrap_or_default())
+    }
+
+    pub fn hospital_time_remaining(&self) -> Option<Duration> {
+        self.hospital_time_remaining_at(Utc::now())
+    }
+
+    pub fn hospital_time_remaining_at(&self, now: DateTime<Utc>) -> Option<Duration> {
+        if self.is_in_hospital() {
+            self.time_remaining_at(now)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Position<S> {
+    Leader,
+    CoLeader,
+    Recruit,
+    Custom(S),
+}
+
+impl<S> Position<S>
+where
+    S: AsRef<str>,
+{
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Leader => "Leader",
+            Self::CoLeader => "Co-leader",
+            Self::Recruit => "Recruit",
+            Self::Custom(name) => name.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Position<&'a str> {
+    fn from(name: &'a str) -> Self {
+        match name {
+            "Leader" => Self::Leader,
+            "Co-leader" => Self::CoLeader,
+            "Recruit" => Self::Recruit,
+            other => Self::Custom(other),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Position<&'a str> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <&'a str>::deserialize(deserializer).map(Into::into)
+    }
+}
+
+impl IntoOwned for Position<&str> {
+    type Owned = Position<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Leader => Position::Leader,
+            Self::CoLeader => Position::CoLeader,
+            Self::Recruit => Position::Recruit,
+            Self::Custom(name) => Position::Custom(name.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Permissions {
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub default: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_use_medical_item: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_use_booster_item: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_use_drug_item: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_use_energy_refill: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_use_nerve_refill: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_loan_temporary_item: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_loan_weapon_and_armory: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_adjust_member_balance: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_manage_wars: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_manage_applications: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_kick_members: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_change_announcement: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_change_description: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_manage_forum: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_manage_upgrades: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_give_item: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_give_money: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_give_points: bool,
+    #[serde(deserialize_with = "de_util::string_is_bool")]
+    pub can_access_faction_api: bool,
+}
+
+impl Permissions {
+    /// The names of the granted permissions, as the fields are called.
+    pub fn granted(&self) -> impl Iterator<Item = &'static str> {
+        [
+            ("default", self.default),
+            ("can_use_medical_item", self.can_use_medical_item),
+            ("can_use_booster_item", self.can_use_booster_item),
+            ("can_use_drug_item", self.can_use_drug_item),
+            ("can_use_energy_refill", self.can_use_energy_refill),
+            ("can_use_nerve_refill", self.can_use_nerve_refill),
+            ("can_loan_temporary_item", self.can_loan_temporary_item),
+            (
+                "can_loan_weapon_and_armory",
+                self.can_loan_weapon_and_armory,
+            ),
+            ("can_adjust_member_balance", self.can_adjust_member_balance),
+            ("can_manage_wars", self.can_manage_wars),
+            ("can_manage_applications", self.can_manage_applications),
+            ("can_kick_members", self.can_kick_members),
+            ("can_change_announcement", self.can_change_announcement),
+            ("can_change_description", self.can_change_description),
+            ("can_manage_forum", self.can_manage_forum),
+            ("can_manage_upgrades", self.can_manage_upgrades),
+            ("can_give_item", self.can_give_item),
+            ("can_give_money", self.can_give_money),
+            ("can_give_points", self.can_give_points),
+            ("can_access_faction_api", self.can_access_faction_api),
+        ]
+        .into_iter()
+        .filter_map(|(name, granted)| granted.then_some(name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ApplicationStatus {
+    Active,
+    Accepted,
+    Declined,
+    Withdrawn,
+    Expired,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -54,19 +594,80 @@ pub struct Territory {
     pub sector: i16,
     pub size: i16,
     pub density: i16,
+    pub slots: i16,
     pub daily_respect: i16,
-    pub faction: i32,
+    /// `None` while no faction holds the territory.
+    #[serde(deserialize_with = "de_util::zero_is_none")]
+    pub faction: Option<FactionId>,
 
     #[cfg(feature = "decimal")]
-    #[serde(deserialize_with = "de_util::string_or_decimal")]
+    #[serde(deserialize_with = "de_util::string_decimal")]
     pub coordinate_x: rust_decimal::Decimal,
 
     #[cfg(feature = "decimal")]
-    #[serde(deserialize_with = "de_util::string_or_decimal")]
+    #[serde(deserialize_with = "de_util::string_decimal")]
     pub coordinate_y: rust_decimal::Decimal,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RankedWarInfo {
+    #[serde(with = "ts_seconds")]
+    #[cfg_attr(feature = "schemars", schemars(with = "i64"))]
+    pub start: DateTime<Utc>,
+    /// `None` while the war is still running.
+    #[serde(deserialize_with = "de_util::zero_date_is_none")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<i64>"))]
+    pub end: Option<DateTime<Utc>>,
+    /// The lead needed to win the war.
+    pub target: i32,
+    #[serde(deserialize_with = "de_util::zero_is_none")]
+    pub winner: Option<FactionId>,
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct RankedWarFaction<'a> {
+    pub name: &'a str,
+    pub score: i32,
+    pub chain: i32,
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct RankedWar<'a> {
+    #[serde(borrow)]
+    pub factions: BTreeMap<FactionId, RankedWarFaction<'a>>,
+    pub war: RankedWarInfo,
+}
+
+impl<'a> RankedWar<'a> {
+    pub fn is_over(&self) -> bool {
+        self.war.end.is_some()
+    }
+
+    /// The faction fighting the given one, or `None` if it isn't part of the war.
+    pub fn opponent(&self, faction_id: FactionId) -> Option<FactionId> {
+        if !self.factions.contains_key(&faction_id) {
+            return None;
+        }
+        self.factions.keys().copied().find(|id| *id != faction_id)
+    }
+
+    /// The lead of the given faction over its opponent, negative while it is behind.
+    pub fn score_differential(&self, faction_id: FactionId) -> Option<i32> {
+        let own = self.factions.get(&faction_id)?;
+        let opponent = self.factions.get(&self.opponent(faction_id)?)?;
+        Some(own.score - opponent.score)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+#[non_exhaustive]
 pub enum AttackResult {
     Attacked,
     Mugged,
@@ -80,9 +681,86 @@ pub enum AttackResult {
     Stalemate,
     Special,
     Looted,
+    Bounty,
+    /// A result which isn't known yet, as sent by the API.
+    Unknown(String),
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl AttackResult {
+    /// The result as sent by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Attacked => "Attacked",
+            Self::Mugged => "Mugged",
+            Self::Hospitalized => "Hospitalized",
+            Self::Lost => "Lost",
+            Self::Arrested => "Arrested",
+            Self::Escape => "Escape",
+            Self::Interrupted => "Interrupted",
+            Self::Assist => "Assist",
+            Self::Timeout => "Timeout",
+            Self::Stalemate => "Stalemate",
+            Self::Special => "Special",
+            Self::Looted => "Looted",
+            Self::Bounty => "Bounty",
+            Self::Unknown(raw) => raw,
+        }
+    }
+
+    pub fn is_win(&self) -> bool {
+        matches!(
+            self,
+            Self::Attacked
+                | Self::Mugged
+                | Self::Hospitalized
+                | Self::Arrested
+                | Self::Special
+                | Self::Looted
+                | Self::Bounty
+        )
+    }
+
+    pub fn is_loss(&self) -> bool {
+        matches!(self, Self::Lost | Self::Timeout)
+    }
+}
+
+impl<'de> Deserialize<'de> for AttackResult {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(match raw.as_ref() {
+            "Attacked" => Self::Attacked,
+            "Mugged" => Self::Mugged,
+            "Hospitalized" => Self::Hospitalized,
+            "Lost" => Self::Lost,
+            "Arrested" => Self::Arrested,
+            "Escape" => Self::Escape,
+            "Interrupted" => Self::Interrupted,
+            "Assist" => Self::Assist,
+            "Timeout" => Self::Timeout,
+            "Stalemate" => Self::Stalemate,
+            "Special" => Self::Special,
+            "Looted" => Self::Looted,
+            "Bounty" => Self::Bounty,
+            _ => {
+                de_util::fallback::<D::Error>(&raw, "attack result")?;
+                Self::Unknown(raw.into_owned())
+            }
+        })
+    }
+}
+
+#[cfg(feature = "decimal")]
+pub type Respect = rust_decimal::Decimal;
+
+#[cfg(not(feature = "decimal"))]
+pub type Respect = f32;
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+#[into_owned(archive)]
 pub struct Attack<'a> {
     pub code: &'a str,
     #[serde(with = "ts_seconds")]
@@ -91,35 +769,61 @@ pub struct Attack<'a> {
     pub timestamp_ended: DateTime<Utc>,
 
     #[serde(deserialize_with = "de_util::empty_string_int_option")]
-    pub attacker_id: Option<i32>,
+    pub attacker_id: Option<UserId>,
     #[serde(deserialize_with = "de_util::empty_string_int_option")]
-    pub attacker_faction: Option<i32>,
-    pub defender_id: i32,
+    pub attacker_faction: Option<FactionId>,
+    pub defender_id: UserId,
     #[serde(deserialize_with = "de_util::empty_string_int_option")]
-    pub defender_faction: Option<i32>,
+    pub defender_faction: Option<FactionId>,
     pub result: AttackResult,
 
     #[serde(deserialize_with = "de_util::int_is_bool")]
     pub stealthed: bool,
 
-    #[cfg(feature = "decimal")]
-    pub respect: rust_decimal::Decimal,
+    pub respect: Respect,
+}
 
-    #[cfg(not(feature = "decimal"))]
-    pub respect: f32,
+impl Attack<'_> {
+    pub fn duration(&self) -> chrono::Duration {
+        self.timestamp_ended - self.timestamp_started
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct RespectModifiers {
-    pub fair_fight: f32,
-    pub war: f32,
-    pub retaliation: f32,
-    pub group_attack: f32,
-    pub overseas: f32,
-    pub chain_bonus: f32,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub fair_fight: f64,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub war: f64,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub retaliation: f64,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub group_attack: f64,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub overseas: f64,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub chain_bonus: f64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl RespectModifiers {
+    pub fn total(&self) -> f64 {
+        self.fair_fight
+            * self.war
+            * self.retaliation
+            * self.group_attack
+            * self.overseas
+            * self.chain_bonus
+    }
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+#[into_owned(archive)]
 pub struct AttackFull<'a> {
     pub code: &'a str,
     #[serde(with = "ts_seconds")]
@@ -128,21 +832,21 @@ pub struct AttackFull<'a> {
     pub timestamp_ended: DateTime<Utc>,
 
     #[serde(deserialize_with = "de_util::empty_string_int_option")]
-    pub attacker_id: Option<i32>,
+    pub attacker_id: Option<UserId>,
     #[serde(deserialize_with = "de_util::empty_string_is_none")]
     pub attacker_name: Option<&'a str>,
     #[serde(deserialize_with = "de_util::empty_string_int_option")]
-    pub attacker_faction: Option<i32>,
+    pub attacker_faction: Option<FactionId>,
     #[serde(
         deserialize_with = "de_util::empty_string_is_none",
         rename = "attacker_factionname"
     )]
     pub attacker_faction_name: Option<&'a str>,
 
-    pub defender_id: i32,
+    pub defender_id: UserId,
     pub defender_name: &'a str,
     #[serde(deserialize_with = "de_util::empty_string_int_option")]
-    pub defender_faction: Option<i32>,
+    pub defender_faction: Option<FactionId>,
     #[serde(
         deserialize_with = "de_util::empty_string_is_none",
         rename = "defender_factionname"
@@ -158,15 +862,869 @@ pub struct AttackFull<'a> {
     #[serde(deserialize_with = "de_util::int_is_bool")]
     pub ranked_war: bool,
 
-    #[cfg(feature = "decimal")]
-    pub respect: rust_decimal::Decimal,
-    #[cfg(feature = "decimal")]
-    pub respect_loss: rust_decimal::Decimal,
-
-    #[cfg(not(feature = "decimal"))]
-    pub respect: f32,
-    #[cfg(not(feature = "decimal"))]
-    pub respect_loss: f32,
+    pub respect: Respect,
+    pub respect_loss: Respect,
 
     pub modifiers: RespectModifiers,
 }
+
+impl AttackFull<'_> {
+    pub fn duration(&self) -> chrono::Duration {
+        self.timestamp_ended - self.timestamp_started
+    }
+
+    pub fn net_respect(&self) -> Respect {
+        self.respect - self.respect_loss
+    }
+}
+
+impl AttackFullOwned {
+    /// Fill in the details which are hidden in this copy of the attack but known in `other`,
+    /// e.g. the attacker of a stealthed attack as seen by the attacker's faction.
+    pub fn enrich(&mut self, other: AttackFullOwned) {
+        self.attacker_id = self.attacker_id.or(other.attacker_id);
+        self.attacker_name = self.attacker_name.take().or(other.attacker_name);
+        self.attacker_faction = self.attacker_faction.or(other.attacker_faction);
+        self.attacker_faction_name = self
+            .attacker_faction_name
+            .take()
+            .or(other.attacker_faction_name);
+        self.defender_faction = self.defender_faction.or(other.defender_faction);
+        self.defender_faction_name = self
+            .defender_faction_name
+            .take()
+            .or(other.defender_faction_name);
+    }
+}
+
+/// Merge `other` into `attacks`, e.g. pages of overlapping time windows or the logs of both
+/// sides of a fight. Attacks present in both, by id or by code, are combined with
+/// [`AttackFullOwned::enrich`] under the id already in `attacks`.
+pub fn merge_attacks<I>(attacks: &mut std::collections::BTreeMap<i64, AttackFullOwned>, other: I)
+where
+    I: IntoIterator<Item = (i64, AttackFullOwned)>,
+{
+    let mut codes: std::collections::HashMap<String, i64> = attacks
+        .iter()
+        .map(|(id, attack)| (attack.code.clone(), *id))
+        .collect();
+
+    for (id, attack) in other {
+        let id = codes.get(&attack.code).copied().unwrap_or(id);
+        match attacks.entry(id) {
+            std::collections::btree_map::Entry::Occupied(mut entry) => {
+                entry.get_mut().enrich(attack)
+            }
+            std::collections::btree_map::Entry::Vacant(entry) => {
+                codes.insert(attack.code.clone(), id);
+                entry.insert(attack);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Stock {
+    Tsb,
+    Tci,
+    Sys,
+    Lag,
+    Iou,
+    Grn,
+    Ths,
+    Yaz,
+    Tct,
+    Cnc,
+    Msg,
+    Tmi,
+    Tcp,
+    Iil,
+    Fhg,
+    Sym,
+    Lsc,
+    Prn,
+    Ewm,
+    Tcm,
+    Elt,
+    Hrg,
+    Tgp,
+    Mun,
+    Wsu,
+    Ist,
+    Bag,
+    Evl,
+    Mcs,
+    Wlt,
+    Tcc,
+    Ass,
+    /// A stock which isn't known to this version of the crate, by its id.
+    Other(i16),
+}
+
+impl Stock {
+    const TABLE: &'static [(Self, i16, &'static str, &'static str)] = &[
+        (Self::Tsb, 1, "TSB", "Torn & Shanghai Banking"),
+        (Self::Tci, 2, "TCI", "Torn City Investments"),
+        (Self::Sys, 3, "SYS", "Syscore MFG"),
+        (Self::Lag, 4, "LAG", "Legal Authorities Group"),
+        (Self::Iou, 5, "IOU", "Insured On Us"),
+        (Self::Grn, 6, "GRN", "Grain"),
+        (Self::Ths, 7, "THS", "Torn City Health Service"),
+        (Self::Yaz, 8, "YAZ", "Yazoo"),
+        (Self::Tct, 9, "TCT", "The Torn City Times"),
+        (Self::Cnc, 10, "CNC", "Crude & Co"),
+        (Self::Msg, 11, "MSG", "Messaging Inc."),
+        (Self::Tmi, 12, "TMI", "TC Music Industries"),
+        (Self::Tcp, 13, "TCP", "TC Media Productions"),
+        (Self::Iil, 14, "IIL", "I Industries Ltd."),
+        (Self::Fhg, 15, "FHG", "Feathery Hotels Group"),
+        (Self::Sym, 16, "SYM", "Symbiotic Ltd."),
+        (Self::Lsc, 17, "LSC", "Lucky Shots Casino"),
+        (Self::Prn, 18, "PRN", "Performance Ribaldry"),
+        (Self::Ewm, 19, "EWM", "Eaglewood Mercenary"),
+        (Self::Tcm, 20, "TCM", "Torn City Motors"),
+        (Self::Elt, 21, "ELT", "Empty Lunchbox Traders"),
+        (Self::Hrg, 22, "HRG", "Home Retail Group"),
+        (Self::Tgp, 23, "TGP", "Tell Group Plc."),
+        (Self::Mun, 24, "MUN", "Munster Beverage Corp."),
+        (Self::Wsu, 25, "WSU", "West Side University"),
+        (Self::Ist, 26, "IST", "International School TC"),
+        (Self::Bag, 27, "BAG", "Big Al's Gun Shop"),
+        (Self::Evl, 28, "EVL", "Evil Ducks Candy Corp"),
+        (Self::Mcs, 29, "MCS", "Mc Smoogle Corp"),
+        (Self::Wlt, 30, "WLT", "Wind Lines Travel"),
+        (Self::Tcc, 31, "TCC", "Torn City Clothing"),
+        (Self::Ass, 32, "ASS", "Alcoholics Synonymous"),
+    ];
+
+    fn entry(self) -> Option<&'static (Self, i16, &'static str, &'static str)> {
+        Self::TABLE.iter().find(|(stock, ..)| *stock == self)
+    }
+
+    pub fn id(self) -> i16 {
+        match self {
+            Self::Other(id) => id,
+            stock => {
+                stock
+                    .entry()
+                    .expect("every known stock has a table entry")
+                    .1
+            }
+        }
+    }
+
+    /// The acronym, or `None` for [`Stock::Other`].
+    pub fn acronym(self) -> Option<&'static str> {
+        self.entry().map(|(_, _, acronym, _)| *acronym)
+    }
+
+    /// The company name, or `None` for [`Stock::Other`].
+    pub fn name(self) -> Option<&'static str> {
+        self.entry().map(|(.., name)| *name)
+    }
+
+    pub fn from_id(id: i16) -> Option<Self> {
+        Self::TABLE
+            .iter()
+            .find(|(_, stock_id, ..)| *stock_id == id)
+            .map(|(stock, ..)| *stock)
+    }
+
+    pub fn from_acronym(acronym: &str) -> Option<Self> {
+        Self::TABLE
+            .iter()
+            .find(|(_, _, a, _)| a.eq_ignore_ascii_case(acronym))
+            .map(|(stock, ..)| *stock)
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::TABLE.iter().map(|(stock, ..)| *stock)
+    }
+
+    fn known<E>(id: i16) -> Result<Self, E>
+    where
+        E: serde::de::Error,
+    {
+        match Self::from_id(id) {
+            Some(stock) => Ok(stock),
+            None => {
+                de_util::fallback::<E>(&id.to_string(), "stock")?;
+                Ok(Self::Other(id))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Stock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.acronym() {
+            Some(acronym) => f.write_str(acronym),
+            None => write!(f, "#{}", self.id()),
+        }
+    }
+}
+
+impl std::str::FromStr for Stock {
+    type Err = UnknownStock;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_acronym(s).ok_or_else(|| UnknownStock(s.to_owned()))
+    }
+}
+
+impl From<i16> for Stock {
+    fn from(id: i16) -> Self {
+        Self::from_id(id).unwrap_or(Self::Other(id))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown stock '{0}'")]
+pub struct UnknownStock(pub String);
+
+impl<'de> Deserialize<'de> for Stock {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StockVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for StockVisitor {
+            type Value = Stock;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("stock id or acronym")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let id = i16::try_from(v)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))?;
+                Stock::known(id)
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match v.parse::<i16>() {
+                    Ok(id) => Stock::known(id),
+                    Err(_) => Stock::from_acronym(v)
+                        .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(StockVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PropertyType {
+    Shack,
+    Trailer,
+    Apartment,
+    SemiDetachedHouse,
+    DetachedHouse,
+    BeachHouse,
+    Chalet,
+    Villa,
+    Penthouse,
+    Mansion,
+    Ranch,
+    Palace,
+    Castle,
+    PrivateIsland,
+}
+
+impl PropertyType {
+    const TABLE: &'static [(Self, i16, &'static str)] = &[
+        (Self::Shack, 1, "Shack"),
+        (Self::Trailer, 2, "Trailer"),
+        (Self::Apartment, 3, "Apartment"),
+        (Self::SemiDetachedHouse, 4, "Semi-Detached House"),
+        (Self::DetachedHouse, 5, "Detached House"),
+        (Self::BeachHouse, 6, "Beach House"),
+        (Self::Chalet, 7, "Chalet"),
+        (Self::Villa, 8, "Villa"),
+        (Self::Penthouse, 9, "Penthouse"),
+        (Self::Mansion, 10, "Mansion"),
+        (Self::Ranch, 11, "Ranch"),
+        (Self::Palace, 12, "Palace"),
+        (Self::Castle, 13, "Castle"),
+        (Self::PrivateIsland, 14, "Private Island"),
+    ];
+
+    pub fn id(self) -> i16 {
+        Self::TABLE
+            .iter()
+            .find(|(property, ..)| *property == self)
+            .map(|(_, id, _)| *id)
+            .expect("every property type has a table entry")
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::TABLE
+            .iter()
+            .find(|(property, ..)| *property == self)
+            .map(|(.., name)| *name)
+            .expect("every property type has a table entry")
+    }
+
+    pub fn from_id(id: i16) -> Option<Self> {
+        Self::TABLE
+            .iter()
+            .find(|(_, property_id, _)| *property_id == id)
+            .map(|(property, ..)| *property)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::TABLE
+            .iter()
+            .find(|(.., property_name)| property_name.eq_ignore_ascii_case(name))
+            .map(|(property, ..)| *property)
+    }
+}
+
+impl std::fmt::Display for PropertyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for PropertyType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PropertyTypeVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for PropertyTypeVisitor {
+            type Value = PropertyType;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("property type id or name")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                i16::try_from(v)
+                    .ok()
+                    .and_then(PropertyType::from_id)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match v.parse::<i16>() {
+                    Ok(id) => PropertyType::from_id(id),
+                    Err(_) => PropertyType::from_name(v),
+                }
+                .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(PropertyTypeVisitor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PropertyUpgrade {
+    Interior(String),
+    HotTub,
+    Sauna,
+    Pool(String),
+    OpenBar,
+    ShootingRange,
+    Vault(String),
+    MedicalFacility,
+    Airstrip,
+    Yacht,
+    Other(String),
+}
+
+impl From<&str> for PropertyUpgrade {
+    fn from(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "ruined interior"
+            | "dilapidated interior"
+            | "below average interior"
+            | "average interior"
+            | "above average interior"
+            | "superior interior" => Self::Interior(name.to_owned()),
+            "hot tub" => Self::HotTub,
+            "sauna" => Self::Sauna,
+            "small pool" | "medium pool" | "large pool" => Self::Pool(name.to_owned()),
+            "open bar" => Self::OpenBar,
+            "shooting range" => Self::ShootingRange,
+            "small vault" | "medium vault" | "large vault" | "extra large vault" => {
+                Self::Vault(name.to_owned())
+            }
+            "medical facility" => Self::MedicalFacility,
+            "airstrip" => Self::Airstrip,
+            "yacht" => Self::Yacht,
+            _ => Self::Other(name.to_owned()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PropertyUpgrade {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <std::borrow::Cow<'de, str>>::deserialize(deserializer).map(|name| name.as_ref().into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PropertyStaff {
+    Maid,
+    Butler,
+    Guard,
+    Doctor,
+    Pilot,
+    Other(String),
+}
+
+impl From<&str> for PropertyStaff {
+    fn from(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "maid" => Self::Maid,
+            "butler" => Self::Butler,
+            "guard" => Self::Guard,
+            "doctor" => Self::Doctor,
+            "pilot" => Self::Pilot,
+            _ => Self::Other(name.to_owned()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PropertyStaff {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <std::borrow::Cow<'de, str>>::deserialize(deserializer).map(|name| name.as_ref().into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum EducationBranch {
+    Biology,
+    Business,
+    CombatTraining,
+    Ict,
+    GeneralStudies,
+    HealthAndFitness,
+    History,
+    Law,
+    Mathematics,
+    Psychology,
+    Defense,
+    SportsScience,
+}
+
+impl EducationBranch {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Biology => "Biology",
+            Self::Business => "Business Management",
+            Self::CombatTraining => "Combat Training",
+            Self::Ict => "ICT",
+            Self::GeneralStudies => "General Studies",
+            Self::HealthAndFitness => "Health & Fitness",
+            Self::History => "History",
+            Self::Law => "Law",
+            Self::Mathematics => "Mathematics",
+            Self::Psychology => "Psychology",
+            Self::Defense => "Self Defense",
+            Self::SportsScience => "Sports Science",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Biology" => Some(Self::Biology),
+            "Business Management" => Some(Self::Business),
+            "Combat Training" => Some(Self::CombatTraining),
+            "ICT" => Some(Self::Ict),
+            "General Studies" => Some(Self::GeneralStudies),
+            "Health & Fitness" => Some(Self::HealthAndFitness),
+            "History" => Some(Self::History),
+            "Law" => Some(Self::Law),
+            "Mathematics" => Some(Self::Mathematics),
+            "Psychology" => Some(Self::Psychology),
+            "Self Defense" => Some(Self::Defense),
+            "Sports Science" => Some(Self::SportsScience),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EducationBranch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for EducationBranch {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Self::from_name(&name).ok_or_else(|| {
+            serde::de::Error::invalid_value(serde::de::Unexpected::Str(&name), &"education branch")
+        })
+    }
+}
+
+impl EducationBranch {
+    /// The branch of the course `id` in the torn `education` selection. Bachelor's degrees are
+    /// named after their branch and require all the other courses of it.
+    pub fn of(id: CourseId, courses: &BTreeMap<CourseId, Course>) -> Option<Self> {
+        courses.iter().find_map(|(degree_id, degree)| {
+            let branch = degree.degree()?;
+            (*degree_id == id || degree.prerequisites.contains(&id)).then_some(branch)
+        })
+    }
+
+    /// All courses of the branch in the torn `education` selection, including its bachelor's
+    /// degree.
+    pub fn courses(
+        self,
+        courses: &BTreeMap<CourseId, Course>,
+    ) -> impl Iterator<Item = (CourseId, &Course)> {
+        courses
+            .iter()
+            .filter(move |(id, _)| Self::of(**id, courses) == Some(self))
+            .map(|(id, course)| (*id, course))
+    }
+}
+
+/// A course of the torn `education` selection, which is keyed by [`CourseId`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Course {
+    pub name: String,
+    pub code: String,
+    pub description: String,
+    pub money: i64,
+    pub tier: i16,
+    #[serde(deserialize_with = "de_util::seconds_duration")]
+    pub duration: Duration,
+    /// The rewards for finishing the course by their kind, e.g. `perk`.
+    #[serde(default, deserialize_with = "de_util::empty_array_is_empty_btree_map")]
+    pub results: BTreeMap<String, Vec<String>>,
+    /// Courses which have to be finished first.
+    #[serde(default, deserialize_with = "de_util::null_is_empty_vec")]
+    pub prerequisites: Vec<CourseId>,
+}
+
+impl Course {
+    pub fn is_bachelor(&self) -> bool {
+        self.name.starts_with("Bachelor")
+    }
+
+    /// The branch this course is the bachelor's degree of.
+    pub fn degree(&self) -> Option<EducationBranch> {
+        EducationBranch::from_name(self.name.strip_prefix("Bachelor of ")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn attack(code: &str, attacker: Option<i64>) -> AttackFullOwned {
+        let attacker = attacker.map_or(serde_json::json!(""), Into::into);
+        AttackFull::deserialize(&serde_json::json!({
+            "code": code,
+            "timestamp_started": 1_650_000_000,
+            "timestamp_ended": 1_650_000_100,
+            "attacker_id": attacker,
+            "attacker_name": "",
+            "attacker_faction": "",
+            "attacker_factionname": "",
+            "defender_id": 28,
+            "defender_name": "Chedburn",
+            "defender_faction": "",
+            "defender_factionname": "",
+            "result": "Hospitalized",
+            "stealthed": 1,
+            "raid": 0,
+            "ranked_war": 0,
+            "respect": 0.0,
+            "respect_loss": 0.0,
+            "modifiers": {
+                "fair_fight": 1.0,
+                "war": 1,
+                "retaliation": 1,
+                "group_attack": 1,
+                "overseas": 1,
+                "chain_bonus": 1,
+            },
+        }))
+        .unwrap()
+        .into_owned()
+    }
+
+    #[test]
+    fn merge() {
+        let mut attacks =
+            std::collections::BTreeMap::from([(1, attack("a", None)), (2, attack("b", None))]);
+        merge_attacks(
+            &mut attacks,
+            [
+                (1, attack("a", Some(4))),
+                (5, attack("b", Some(4))),
+                (3, attack("c", None)),
+            ],
+        );
+
+        assert_eq!(attacks.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(attacks[&1].attacker_id, Some(UserId(4)));
+        assert_eq!(attacks[&2].attacker_id, Some(UserId(4)));
+        assert_eq!(attacks[&3].attacker_id, None);
+    }
+
+    #[test]
+    fn unknown_attack_result() {
+        let result = AttackResult::deserialize(serde_json::json!("Abandoned")).unwrap();
+        assert_eq!(result, AttackResult::Unknown("Abandoned".to_owned()));
+        assert_eq!(result.as_str(), "Abandoned");
+        assert_eq!(AttackResult::Lost.as_str(), "Lost");
+    }
+
+    fn status(value: serde_json::Value) -> StatusKind<String> {
+        Status::deserialize(&value).unwrap().kind.into_owned()
+    }
+
+    #[test]
+    fn attack_respect() {
+        let value = serde_json::json!({
+            "code": "abc",
+            "timestamp_started": 1700000000,
+            "timestamp_ended": 1700000030,
+            "attacker_id": 1,
+            "attacker_name": "Attacker",
+            "attacker_faction": "",
+            "attacker_factionname": "",
+            "defender_id": 2,
+            "defender_name": "Defender",
+            "defender_faction": 3,
+            "defender_factionname": "Faction",
+            "result": "Hospitalized",
+            "stealthed": 0,
+            "raid": 0,
+            "ranked_war": 0,
+            "respect": 3.0,
+            "respect_loss": 1.0,
+            "modifiers": {
+                "fair_fight": 3,
+                "war": 1,
+                "retaliation": 1,
+                "group_attack": 1,
+                "overseas": 1,
+                "chain_bonus": "1.5",
+            },
+        });
+        let attack = AttackFull::deserialize(&value).unwrap();
+
+        assert!(attack.result.is_win());
+        assert_eq!(attack.duration().num_seconds(), 30);
+        assert_eq!(attack.net_respect(), Respect::from(2u8));
+        assert_eq!(attack.modifiers.total(), 4.5);
+    }
+
+    #[test]
+    fn stock_conversions() {
+        assert_eq!(Stock::from_acronym("wsu"), Some(Stock::Wsu));
+        assert_eq!(Stock::from(1), Stock::Tsb);
+        assert_eq!(Stock::from(99).acronym(), None);
+        assert_eq!(Stock::from(99).to_string(), "#99");
+        assert_eq!("IOU".parse::<Stock>().map(Stock::id), Ok(5));
+
+        let stocks: std::collections::BTreeMap<Stock, i32> =
+            serde_json::from_str(r#"{"1":10,"25":3}"#).unwrap();
+        assert_eq!(stocks.get(&Stock::Wsu), Some(&3));
+
+        let stocks: std::collections::BTreeMap<Stock, i32> =
+            serde_json::from_str(r#"{"33":1}"#).unwrap();
+        assert_eq!(stocks.get(&Stock::Other(33)), Some(&1));
+
+        for stock in Stock::all() {
+            assert_eq!(Stock::from_id(stock.id()), Some(stock));
+        }
+    }
+
+    #[test]
+    fn country_flight_time() {
+        assert_eq!(
+            Country::Mexico.flight_time(TravelMethod::Standard),
+            Some(Duration::from_secs(26 * 60))
+        );
+        assert_eq!(
+            Country::Uae.flight_time(TravelMethod::Business),
+            Some(Duration::from_secs(4878))
+        );
+        assert_eq!(
+            serde_json::from_str::<Country>(r#""Atlantis""#).unwrap(),
+            Country::Other
+        );
+        assert_eq!(Country::Other.flight_time(TravelMethod::Standard), None);
+        assert_eq!(
+            serde_json::from_str::<Country>(r#""Cayman Islands""#).unwrap(),
+            Country::CaymanIslands
+        );
+        for country in Country::all().filter(|c| *c != Country::Torn) {
+            assert_eq!(Country::from_code(country.code().unwrap()), Some(country));
+        }
+    }
+
+    #[test]
+    fn property_vocabulary() {
+        assert_eq!(PropertyType::from_id(13), Some(PropertyType::Castle));
+        assert_eq!(
+            serde_json::from_str::<PropertyType>(r#""Private Island""#).unwrap(),
+            PropertyType::PrivateIsland
+        );
+        assert_eq!(
+            PropertyUpgrade::from("Large vault"),
+            PropertyUpgrade::Vault("Large vault".to_owned())
+        );
+        assert_eq!(
+            PropertyUpgrade::from("Vaulted ceiling"),
+            PropertyUpgrade::Other("Vaulted ceiling".to_owned())
+        );
+        assert_eq!(PropertyStaff::from("Butler"), PropertyStaff::Butler);
+        assert_eq!(
+            PropertyStaff::from("Head guard"),
+            PropertyStaff::Other("Head guard".to_owned())
+        );
+    }
+
+    #[test]
+    fn last_action() {
+        let now = Utc.timestamp_opt(1700000600, 0).unwrap();
+        let last_action: LastAction = serde_json::from_value(serde_json::json!({
+            "status": "Offline",
+            "timestamp": 1700000000,
+            "relative": "10 minutes ago",
+        }))
+        .unwrap();
+
+        assert_eq!(last_action.seconds_since_at(now), 600);
+        assert!(last_action.is_active_within_at(Duration::from_secs(900), now));
+        assert!(!last_action.is_active_within_at(Duration::from_secs(300), now));
+    }
+
+    #[test]
+    fn status_predicates() {
+        let now = Utc.timestamp_opt(1700000000, 0).unwrap();
+        let value = serde_json::json!({
+            "description": "In hospital for 3 mins",
+            "details": "Hospitalized by someone",
+            "state": "Hospital",
+            "color": "red",
+            "until": 1700000180,
+        });
+        let status = Status::deserialize(&value).unwrap();
+
+        assert!(status.is_in_hospital());
+        assert!(!status.is_okay() && !status.is_travelling());
+        assert_eq!(
+            status.hospital_time_remaining_at(now),
+            Some(Duration::from_secs(180))
+        );
+        assert_eq!(
+            status.time_remaining_at(now + chrono::Duration::minutes(5)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn status_kind() {
+        assert_eq!(
+            status(serde_json::json!({
+                "description": "Returning to Torn from Mexico",
+                "details": "",
+                "state": "Traveling",
+                "color": "blue",
+                "until": 0,
+            })),
+            StatusKind::Traveling {
+                from: Country::Mexico,
+                to: Country::Torn
+            }
+        );
+
+        assert_eq!(
+            status(serde_json::json!({
+                "description": "In hospital for 3 mins",
+                "details": "Hospitalized by someone",
+                "state": "Hospital",
+                "color": "red",
+                "until": 1700000000,
+            })),
+            StatusKind::Hospital {
+                until: Utc.timestamp_opt(1700000000, 0).unwrap()
+            }
+        );
+
+        assert!(matches!(
+            status(serde_json::json!({
+                "description": "In federal jail for 30 days",
+                "details": "Account closed",
+                "state": "Federal",
+                "color": "red",
+                "until": 0,
+            })),
+            StatusKind::Federal { until: None, reason: Some(reason) } if reason == "Account closed"
+        ));
+    }
+
+    #[test]
+    fn unowned_territory() {
+        let territory = |faction: i64| {
+            Territory::deserialize(serde_json::json!({
+                "sector": 1,
+                "size": 20,
+                "density": 5,
+                "slots": 3,
+                "daily_respect": 100,
+                "faction": faction,
+                "coordinate_x": "1.5",
+                "coordinate_y": "2.5",
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(territory(0).faction, None);
+        assert_eq!(territory(7049).faction, Some(FactionId(7049)));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn owned_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(AttackFullOwned)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert!(properties.contains_key("defender_name"));
+        assert!(properties.contains_key("modifiers"));
+
+        schemars::schema_for!(StatusOwned);
+    }
+}
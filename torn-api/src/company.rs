@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use torn_api_macros::{ApiCategory, IntoOwned};
+
+use crate::{
+    de_util::{self, null_is_empty_btree_map, unix_timestamp},
+    CompanyId, UserId,
+};
+
+pub use crate::common::{ApplicationStatus, LastAction, Status};
+
+#[derive(Debug, Clone, Copy, ApiCategory)]
+#[api(category = "company")]
+#[non_exhaustive]
+pub enum CompanySelection {
+    #[api(type = "Profile<'a>", field = "company")]
+    Profile,
+
+    /// Only available to the company's director.
+    #[api(type = "Detailed", field = "company_detailed")]
+    Detailed,
+
+    /// Only available to the company's director.
+    #[api(
+        type = "BTreeMap<UserId, Employee<'a>>",
+        field = "company_employees",
+        with = "null_is_empty_btree_map"
+    )]
+    Employees,
+
+    /// Only available to the company's director.
+    #[api(
+        type = "BTreeMap<String, StockItem>",
+        field = "company_stock",
+        with = "null_is_empty_btree_map"
+    )]
+    Stock,
+
+    /// Only available to the company's director.
+    #[api(
+        type = "BTreeMap<i64, Application<'a>>",
+        field = "applications",
+        with = "null_is_empty_btree_map"
+    )]
+    Applications,
+
+    #[api(
+        type = "BTreeMap<String, NewsEntry>",
+        field = "news",
+        with = "null_is_empty_btree_map"
+    )]
+    News,
+
+    /// Like [`Self::News`], but including the entries of the full history.
+    #[api(
+        type = "BTreeMap<String, NewsEntry>",
+        field = "news",
+        with = "null_is_empty_btree_map"
+    )]
+    NewsFull,
+
+    #[api(type = "Vec<&'a str>", field = "selections")]
+    Lookup,
+
+    #[api(type = "DateTime<Utc>", field = "timestamp", with = "unix_timestamp")]
+    Timestamp,
+}
+
+pub type Selection = CompanySelection;
+
+/// An employee as listed on the company's public profile.
+#[derive(Debug, IntoOwned, Deserialize)]
+pub struct ProfileEmployee<'a> {
+    pub name: &'a str,
+    pub position: &'a str,
+    pub days_in_company: i16,
+    pub last_action: LastAction,
+    pub status: Status<'a>,
+}
+
+#[derive(Debug, IntoOwned, Deserialize)]
+pub struct Profile<'a> {
+    #[serde(rename = "ID")]
+    pub id: CompanyId,
+    pub company_type: i16,
+    pub rating: i16,
+    pub name: &'a str,
+    pub director: UserId,
+    pub employees_hired: i16,
+    pub employees_capacity: i16,
+    pub daily_income: i64,
+    pub daily_customers: i32,
+    pub weekly_income: i64,
+    pub weekly_customers: i32,
+    pub days_old: i32,
+    #[serde(borrow, default, deserialize_with = "de_util::id_keyed_map")]
+    pub employees: BTreeMap<UserId, ProfileEmployee<'a>>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl<'a> Profile<'a> {
+    pub fn vacancies(&self) -> i16 {
+        (self.employees_capacity - self.employees_hired).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompanyUpgrades {
+    pub company_size: i16,
+    pub staffroom_size: String,
+    pub storage_size: String,
+    pub storage_space: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Detailed {
+    #[serde(rename = "ID")]
+    pub id: CompanyId,
+    pub company_funds: i64,
+    pub company_bank: i64,
+    pub popularity: i16,
+    pub efficiency: i16,
+    pub environment: i16,
+    pub trains_available: i16,
+    pub advertising_budget: i64,
+    pub upgrades: CompanyUpgrades,
+    pub value: i64,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+/// The parts that make up an employee's effectiveness. Parts which don't apply are left out
+/// by the API and default to zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Effectiveness {
+    pub working_stats: i16,
+    pub settled_in: i16,
+    pub merits: i16,
+    pub director_education: i16,
+    pub management: i16,
+    pub addiction: i16,
+    pub inactivity: i16,
+    pub total: i16,
+}
+
+#[derive(Debug, IntoOwned, Deserialize)]
+pub struct Employee<'a> {
+    pub name: &'a str,
+    pub position: &'a str,
+    pub days_in_company: i16,
+    pub wage: i64,
+    pub manual_labor: i32,
+    pub intelligence: i32,
+    pub endurance: i32,
+    pub effectiveness: Effectiveness,
+    pub last_action: LastAction,
+    pub status: Status<'a>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct StockItem {
+    pub cost: i64,
+    /// Recommended retail price.
+    pub rrp: i64,
+    pub price: i64,
+    pub in_stock: i64,
+    pub on_order: i64,
+    pub created_amount: i64,
+    pub sold_amount: i64,
+    pub sold_worth: i64,
+}
+
+impl StockItem {
+    /// Profit per unit sold at the current price.
+    pub fn margin(&self) -> i64 {
+        self.price - self.cost
+    }
+}
+
+/// The working stats of an applicant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ApplicationStats {
+    pub manual_labor: i32,
+    pub intelligence: i32,
+    pub endurance: i32,
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct Application<'a> {
+    #[serde(rename = "userID")]
+    pub user_id: UserId,
+    pub name: &'a str,
+    pub level: i16,
+    #[serde(default)]
+    pub stats: Option<ApplicationStats>,
+    pub message: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub expires: DateTime<Utc>,
+    pub status: ApplicationStatus,
+}
+
+impl Application<'_> {
+    pub fn is_pending(&self) -> bool {
+        self.status == ApplicationStatus::Active
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewsEntry {
+    /// The entry's text, which contains html markup.
+    pub news: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{async_test, setup, Client, ClientTrait};
+
+    #[async_test]
+    async fn company_public() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .company(|b| {
+                b.id(1)
+                    .selections([Selection::Profile, Selection::Timestamp])
+            })
+            .await
+            .unwrap();
+
+        response.profile().unwrap();
+        response.timestamp().unwrap();
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn director() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [Selection::Employees, Selection::Stock],
+            serde_json::json!({
+                "company_employees": {
+                    "1": {
+                        "name": "Chedburn",
+                        "position": "Cleaner",
+                        "days_in_company": 30,
+                        "wage": 50_000,
+                        "manual_labor": 1000,
+                        "intelligence": 2000,
+                        "endurance": 3000,
+                        "effectiveness": {
+                            "working_stats": 80, "settled_in": 10, "inactivity": -5, "total": 85,
+                        },
+                        "last_action": {
+                            "status": "Offline", "timestamp": 1_700_000_000, "relative": "",
+                        },
+                        "status": {
+                            "description": "Okay", "details": "", "state": "Okay",
+                            "color": "green", "until": 0,
+                        },
+                    },
+                },
+                "company_stock": {
+                    "Lollipop": {
+                        "cost": 10, "rrp": 30, "price": 25, "in_stock": 500, "on_order": 0,
+                        "created_amount": 0, "sold_amount": 100, "sold_worth": 2500,
+                    },
+                },
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .company(|b| b.selections([Selection::Employees, Selection::Stock]))
+            .await
+            .unwrap();
+
+        let employees = response.employees().unwrap();
+        let effectiveness = employees[&UserId(1)].effectiveness;
+        assert_eq!(effectiveness.inactivity, -5);
+        assert_eq!(effectiveness.merits, 0);
+        assert_eq!(effectiveness.total, 85);
+        assert_eq!(response.stock().unwrap()["Lollipop"].margin(), 15);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn hiring() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [Selection::Applications, Selection::News],
+            serde_json::json!({
+                "applications": {
+                    "5": {
+                        "userID": 2,
+                        "name": "Applicant",
+                        "level": 10,
+                        "stats": { "manual_labor": 100, "intelligence": 200, "endurance": 300 },
+                        "message": "",
+                        "expires": 1_700_000_000,
+                        "status": "declined",
+                    },
+                },
+                "news": {
+                    "a1b2c3": {
+                        "news": "<a href = \"profiles.php?XID=2\">Applicant</a> left the company",
+                        "timestamp": 1_700_000_000,
+                    },
+                },
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .company(|b| b.selections([Selection::Applications, Selection::News]))
+            .await
+            .unwrap();
+
+        let applications = response.applications().unwrap();
+        assert!(!applications[&5].is_pending());
+        assert_eq!(applications[&5].stats.unwrap().endurance, 300);
+        assert!(response.news().unwrap()["a1b2c3"]
+            .news
+            .ends_with("left the company"));
+    }
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Negotiate the version with the server.
+    #[default]
+    Auto,
+    Http1,
+    /// Assume the server speaks HTTP/2.
+    Http2,
+}
+
+/// Connection settings for creating the clients of the built-in backends. Pollers hitting the API
+/// every few seconds benefit from keeping idle connections around for longer than the defaults.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionOptions {
+    pub pool_idle_timeout: Option<Duration>,
+    /// Not supported by awc and ignored there.
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+    pub timeout: Option<Duration>,
+    pub http_version: HttpVersion,
+}
+
+impl ConnectionOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// A reqwest client builder with these options applied, e.g. for further configuring TLS.
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_builder(&self) -> ::reqwest::ClientBuilder {
+        let mut builder = ::reqwest::Client::builder()
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        }
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_client(&self) -> ::reqwest::Result<::reqwest::Client> {
+        self.reqwest_builder().build()
+    }
+
+    /// An awc client with these options applied. The TCP keepalive and the idle connections per
+    /// host aren't configurable with awc and are ignored.
+    #[cfg(feature = "awc")]
+    pub fn awc_client(&self) -> ::awc::Client {
+        let mut connector = ::awc::Connector::new();
+        if let Some(timeout) = self.pool_idle_timeout {
+            connector = connector.conn_keep_alive(timeout);
+        }
+
+        let mut builder = ::awc::Client::builder().connector(connector);
+        if let HttpVersion::Http1 = self.http_version {
+            builder = builder.max_http_version(::awc::http::Version::HTTP_11);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        builder.finish()
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reqwest_client() {
+        ConnectionOptions::new()
+            .pool_idle_timeout(Duration::from_secs(300))
+            .pool_max_idle_per_host(8)
+            .tcp_keepalive(Duration::from_secs(30))
+            .http_version(HttpVersion::Http1)
+            .reqwest_client()
+            .unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+use std::{collections::BTreeMap, io};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    common::{AttackFullOwned, Respect},
+    user::PersonalStats,
+    FactionId, UserId,
+};
+
+#[derive(Serialize)]
+struct PersonalStatsRow {
+    player_id: UserId,
+    attacks_won: i32,
+    attacks_lost: i32,
+    defends_won: i32,
+    defends_lost: i32,
+    stat_enhancers_used: i32,
+    refills: i32,
+    drugs_used: i32,
+    xanax_taken: i32,
+    lsd_taken: i32,
+    net_worth: i64,
+    cans_used: i32,
+    boosters_used: i32,
+    awards: i16,
+    elo: i16,
+    days_been_donator: i16,
+    best_damage: i32,
+}
+
+#[derive(Serialize)]
+struct AttackRow<'a> {
+    id: i64,
+    code: &'a str,
+    timestamp_started: DateTime<Utc>,
+    timestamp_ended: DateTime<Utc>,
+    attacker_id: Option<UserId>,
+    attacker_name: Option<&'a str>,
+    attacker_faction: Option<FactionId>,
+    defender_id: UserId,
+    defender_name: &'a str,
+    defender_faction: Option<FactionId>,
+    result: String,
+    stealthed: bool,
+    raid: bool,
+    ranked_war: bool,
+    respect: Respect,
+    respect_loss: Respect,
+}
+
+#[cfg(feature = "faction")]
+#[derive(Serialize)]
+struct ChainReportMemberRow {
+    user_id: UserId,
+    respect: f64,
+    attacks: i32,
+    leaves: i32,
+    mugs: i32,
+    hospitalizations: i32,
+    overseas: i32,
+    war_hits: i32,
+    retaliations: i32,
+    assists: i32,
+    bonus_hits: i32,
+    best_hit: f64,
+}
+
+/// Write one row per player with their personal stats, including a header.
+pub fn write_personal_stats<'a, W, I>(writer: W, stats: I) -> csv::Result<()>
+where
+    W: io::Write,
+    I: IntoIterator<Item = (UserId, &'a PersonalStats)>,
+{
+    let mut writer = csv::Writer::from_writer(writer);
+    for (player_id, stats) in stats {
+        writer.serialize(PersonalStatsRow {
+            player_id,
+            attacks_won: stats.attacks_won,
+            attacks_lost: stats.attacks_lost,
+            defends_won: stats.defends_won,
+            defends_lost: stats.defends_lost,
+            stat_enhancers_used: stats.stat_enhancers_used,
+            refills: stats.refills,
+            drugs_used: stats.drugs_used,
+            xanax_taken: stats.xanax_taken,
+            lsd_taken: stats.lsd_taken,
+            net_worth: stats.net_worth,
+            cans_used: stats.cans_used,
+            boosters_used: stats.boosters_used,
+            awards: stats.awards,
+            elo: stats.elo,
+            days_been_donator: stats.days_been_donator,
+            best_damage: stats.best_damage,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write an attack log, oldest attack first, including a header.
+pub fn write_attacks<W>(writer: W, attacks: &BTreeMap<i64, AttackFullOwned>) -> csv::Result<()>
+where
+    W: io::Write,
+{
+    let mut writer = csv::Writer::from_writer(writer);
+    for (id, attack) in attacks {
+        writer.serialize(AttackRow {
+            id: *id,
+            code: &attack.code,
+            timestamp_started: attack.timestamp_started,
+            timestamp_ended: attack.timestamp_ended,
+            attacker_id: attack.attacker_id,
+            attacker_name: attack.attacker_name.as_deref(),
+            attacker_faction: attack.attacker_faction,
+            defender_id: attack.defender_id,
+            defender_name: &attack.defender_name,
+            defender_faction: attack.defender_faction,
+            result: attack.result.as_str().to_owned(),
+            stealthed: attack.stealthed,
+            raid: attack.raid,
+            ranked_war: attack.ranked_war,
+            respect: attack.respect,
+            respect_loss: attack.respect_loss,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write the member breakdown of a chain report, one row per member, including a header.
+#[cfg(feature = "faction")]
+pub fn write_chain_report<W>(writer: W, report: &crate::faction::ChainReport) -> csv::Result<()>
+where
+    W: io::Write,
+{
+    let mut writer = csv::Writer::from_writer(writer);
+    for member in report.members.values() {
+        writer.serialize(ChainReportMemberRow {
+            user_id: member.user_id,
+            respect: member.respect,
+            attacks: member.attacks,
+            leaves: member.leaves,
+            mugs: member.mugs,
+            hospitalizations: member.hospitalizations,
+            overseas: member.overseas,
+            war_hits: member.war_hits,
+            retaliations: member.retaliations,
+            assists: member.assists,
+            bonus_hits: member.bonus_hits,
+            best_hit: member.best_hit,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{common::AttackFull, IntoOwned};
+
+    #[test]
+    fn attacks() {
+        let value = serde_json::json!({
+            "7": {
+                "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                "timestamp_started": 1_650_000_000,
+                "timestamp_ended": 1_650_000_100,
+                "attacker_id": "",
+                "attacker_name": "",
+                "attacker_faction": "",
+                "attacker_factionname": "",
+                "defender_id": 28,
+                "defender_name": "Chedburn",
+                "defender_faction": "",
+                "defender_factionname": "",
+                "result": "Lost",
+                "stealthed": 1,
+                "raid": 0,
+                "ranked_war": 0,
+                "respect": 0.0,
+                "respect_loss": 0.0,
+                "modifiers": {
+                    "fair_fight": 1.0,
+                    "war": 1,
+                    "retaliation": 1,
+                    "group_attack": 1,
+                    "overseas": 1,
+                    "chain_bonus": 1,
+                },
+            }
+        });
+        let attacks = BTreeMap::<i64, AttackFull>::deserialize(&value).unwrap();
+
+        let mut out = Vec::new();
+        write_attacks(&mut out, &attacks.into_owned()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let mut lines = out.lines();
+
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("id,code,timestamp_started"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("7,3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c,2022-04-15T05:20:00Z,"));
+        assert!(lines.next().is_none());
+    }
+    #[cfg(feature = "faction")]
+    #[test]
+    fn chain_report() {
+        let report: crate::faction::ChainReport = serde_json::from_value(serde_json::json!({
+            "factionID": 9,
+            "chain": 10,
+            "start": 1_650_000_000,
+            "end": 1_650_003_600,
+            "respect": "42.5",
+            "targets": 8,
+            "warhits": 0,
+            "besthit": "10.25",
+            "retaliations": 0,
+            "members": {
+                "28": {
+                    "userID": 28, "respect": "42.5", "attacks": 10, "leave": 10, "mug": 0,
+                    "hospitalize": 0, "overseas": 0, "war": 0, "retaliation": 0, "assist": 1,
+                    "bonus": 1, "besthit": "10.25",
+                },
+            },
+            "bonuses": null,
+        }))
+        .unwrap();
+
+        let mut out = Vec::new();
+        write_chain_report(&mut out, &report).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let mut lines = out.lines();
+
+        assert!(lines.next().unwrap().starts_with("user_id,respect,attacks"));
+        assert_eq!(lines.next().unwrap(), "28,42.5,10,10,0,0,0,0,0,1,1,10.25");
+        assert!(lines.next().is_none());
+    }
+}
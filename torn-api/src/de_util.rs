@@ -1,10 +1,46 @@
 #![allow(unused)]
 
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
 
-use chrono::{serde::ts_nanoseconds::deserialize, DateTime, NaiveDateTime, Utc};
+use chrono::{serde::ts_nanoseconds::deserialize, DateTime, Utc};
 use serde::de::{Deserialize, Deserializer, Error, Unexpected, Visitor};
 
+use crate::Strictness;
+
+thread_local! {
+    static STRICTNESS: Cell<Strictness> = const { Cell::new(Strictness::Lenient) };
+}
+
+/// Run `fun` with `strictness` applying to the [`fallback`]s it encounters on this thread.
+pub(crate) fn with_strictness<T>(strictness: Strictness, fun: impl FnOnce() -> T) -> T {
+    struct Reset(Strictness);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            STRICTNESS.with(|s| s.set(self.0));
+        }
+    }
+
+    let _reset = Reset(STRICTNESS.with(|s| s.replace(strictness)));
+    fun()
+}
+
+/// Call before falling back to a default for a value which wasn't understood. Fails in
+/// [`Strictness::Strict`] mode.
+pub(crate) fn fallback<E>(value: &str, expected: &'static str) -> Result<(), E>
+where
+    E: Error,
+{
+    match STRICTNESS.with(Cell::get) {
+        Strictness::Lenient => Ok(()),
+        Strictness::Strict => Err(E::invalid_value(Unexpected::Str(value), &expected)),
+    }
+}
+
 pub(crate) fn empty_string_is_none<'de, D>(deserializer: D) -> Result<Option<&'de str>, D::Error>
 where
     D: Deserializer<'de>,
@@ -39,9 +75,30 @@ where
     if i == 0 {
         Ok(None)
     } else {
-        let naive = NaiveDateTime::from_timestamp_opt(i, 0)
+        let date = DateTime::from_timestamp(i, 0)
             .ok_or_else(|| D::Error::invalid_value(Unexpected::Signed(i), &"Epoch timestamp"))?;
-        Ok(Some(DateTime::from_utc(naive, Utc)))
+        Ok(Some(date))
+    }
+}
+
+pub(crate) fn seconds_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let secs = i64::deserialize(deserializer)?;
+
+    // negative counters show up briefly once a timer has run out
+    Ok(Duration::from_secs(secs.max(0) as u64))
+}
+
+pub(crate) fn zero_duration_is_none<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<i64>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(secs) if secs <= 0 => Ok(None),
+        Some(secs) => Ok(Some(Duration::from_secs(secs as u64))),
     }
 }
 
@@ -58,14 +115,93 @@ where
     }
 }
 
-pub(crate) fn empty_string_int_option<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+pub(crate) fn string_is_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: Deserializer<'de>,
 {
     struct DumbVisitor;
 
     impl<'de> Visitor<'de> for DumbVisitor {
-        type Value = Option<i32>;
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "0 or 1 as integer or string")
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(v)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            match v {
+                0 => Ok(false),
+                1 => Ok(true),
+                x => Err(E::invalid_value(Unexpected::Unsigned(x), &self)),
+            }
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            match v {
+                0 => Ok(false),
+                1 => Ok(true),
+                x => Err(E::invalid_value(Unexpected::Signed(x), &self)),
+            }
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            match v {
+                "0" => Ok(false),
+                "1" => Ok(true),
+                x => Err(E::invalid_value(Unexpected::Str(x), &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(DumbVisitor)
+}
+
+pub(crate) fn yes_no_is_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: &str = Deserialize::deserialize(deserializer)?;
+
+    if s.eq_ignore_ascii_case("yes") {
+        Ok(true)
+    } else if s.eq_ignore_ascii_case("no") {
+        Ok(false)
+    } else {
+        Err(Error::invalid_value(
+            Unexpected::Str(s),
+            &"\"yes\" or \"no\"",
+        ))
+    }
+}
+
+pub(crate) fn empty_string_int_option<'de, D, I>(deserializer: D) -> Result<Option<I>, D::Error>
+where
+    D: Deserializer<'de>,
+    I: From<i32>,
+{
+    struct DumbVisitor<I>(std::marker::PhantomData<I>);
+
+    impl<'de, I> Visitor<'de> for DumbVisitor<I>
+    where
+        I: From<i32>,
+    {
+        type Value = Option<I>;
 
         fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
             write!(formatter, "Empty string or integer")
@@ -76,7 +212,7 @@ where
         where
             E: Error,
         {
-            Ok(Some(v as i32))
+            Ok(Some((v as i32).into()))
         }
 
         fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
@@ -91,24 +227,35 @@ where
         }
     }
 
-    deserializer.deserialize_any(DumbVisitor)
+    deserializer.deserialize_any(DumbVisitor(std::marker::PhantomData))
+}
+
+pub(crate) fn unix_timestamp<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    chrono::serde::ts_seconds::deserialize(deserializer)
 }
 
-pub(crate) fn datetime_map<'de, D>(
+pub(crate) fn datetime_map<'de, D, K>(
     deserializer: D,
-) -> Result<BTreeMap<i32, chrono::DateTime<chrono::Utc>>, D::Error>
+) -> Result<BTreeMap<K, chrono::DateTime<chrono::Utc>>, D::Error>
 where
     D: Deserializer<'de>,
+    K: std::str::FromStr + Ord,
 {
     #[derive(serde::Deserialize)]
     struct UnixTimestamp(
         #[serde(with = "chrono::serde::ts_seconds")] chrono::DateTime<chrono::Utc>,
     );
 
-    struct MapVisitor;
+    struct MapVisitor<K>(std::marker::PhantomData<K>);
 
-    impl<'de> Visitor<'de> for MapVisitor {
-        type Value = BTreeMap<i32, chrono::DateTime<chrono::Utc>>;
+    impl<'de, K> Visitor<'de> for MapVisitor<K>
+    where
+        K: std::str::FromStr + Ord,
+    {
+        type Value = BTreeMap<K, chrono::DateTime<chrono::Utc>>;
 
         fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
             write!(formatter, "map of unix timestamps")
@@ -132,7 +279,7 @@ where
         }
     }
 
-    deserializer.deserialize_map(MapVisitor)
+    deserializer.deserialize_map(MapVisitor(std::marker::PhantomData))
 }
 
 pub(crate) fn empty_dict_is_empty_array<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
@@ -187,14 +334,11 @@ where
     D: Deserializer<'de>,
     I: TryFrom<i64>,
 {
-    let num = i64::deserialize(deserializer)?;
-
-    if num == 0 {
-        Ok(None)
-    } else {
-        Ok(Some(num.try_into().map_err(|_| {
+    match Option::<i64>::deserialize(deserializer)? {
+        None | Some(0) => Ok(None),
+        Some(num) => Ok(Some(num.try_into().map_err(|_| {
             D::Error::invalid_value(Unexpected::Signed(num), &std::any::type_name::<I>())
-        })?))
+        })?)),
     }
 }
 
@@ -207,8 +351,304 @@ where
     Ok(Option::deserialize(deserializer)?.unwrap_or_default())
 }
 
+pub(crate) fn null_is_empty_btree_map<'de, D, K, V>(
+    deserializer: D,
+) -> Result<BTreeMap<K, V>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: std::cmp::Ord + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Accepts `null` and `[]`, which PHP encodes empty dicts as, in place of an empty object.
+pub(crate) fn empty_array_is_empty_btree_map<'de, D, K, V>(
+    deserializer: D,
+) -> Result<BTreeMap<K, V>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: std::cmp::Ord + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    struct MapVisitor<K, V>(std::marker::PhantomData<(K, V)>);
+
+    impl<'de, K, V> Visitor<'de> for MapVisitor<K, V>
+    where
+        K: std::cmp::Ord + Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = BTreeMap<K, V>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "object or empty array")
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(BTreeMap::default())
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            match seq.next_element::<serde::de::IgnoredAny>()? {
+                None => Ok(BTreeMap::default()),
+                Some(_) => Err(A::Error::invalid_length(1, &"empty array")),
+            }
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let mut result = BTreeMap::default();
+            while let Some((key, value)) = map.next_entry()? {
+                result.insert(key, value);
+            }
+
+            Ok(result)
+        }
+    }
+
+    deserializer.deserialize_any(MapVisitor(std::marker::PhantomData))
+}
+
+/// For maps keyed by ids in structs using `#[serde(flatten)]`, which buffer map keys as strings
+/// that the id types don't parse. Like [`empty_array_is_empty_btree_map`], `null` and `[]` are
+/// empty maps.
+pub(crate) fn id_keyed_map<'de, D, K, V, M>(deserializer: D) -> Result<M, D::Error>
+where
+    D: Deserializer<'de>,
+    K: From<i64>,
+    V: Deserialize<'de>,
+    M: Default + Extend<(K, V)> + IntoIterator<Item = (K, V)>,
+{
+    struct IdKey(i64);
+
+    impl<'de> Deserialize<'de> for IdKey {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            string_i64(deserializer).map(IdKey)
+        }
+    }
+
+    struct MapVisitor<K, V, M>(std::marker::PhantomData<(K, V, M)>);
+
+    impl<'de, K, V, M> Visitor<'de> for MapVisitor<K, V, M>
+    where
+        K: From<i64>,
+        V: Deserialize<'de>,
+        M: Default + Extend<(K, V)> + IntoIterator<Item = (K, V)>,
+    {
+        type Value = M;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "map keyed by ids")
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(M::default())
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            match seq.next_element::<serde::de::IgnoredAny>()? {
+                None => Ok(M::default()),
+                Some(_) => Err(A::Error::invalid_length(1, &"empty array")),
+            }
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let mut result = M::default();
+            while let Some((IdKey(key), value)) = map.next_entry()? {
+                result.extend([(K::from(key), value)]);
+            }
+
+            Ok(result)
+        }
+    }
+
+    deserializer.deserialize_any(MapVisitor(std::marker::PhantomData))
+}
+
+pub(crate) fn null_is_empty_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Deserializes an object key on its own, parsing numeric keys like serde_json does for maps.
+#[cfg(feature = "rayon")]
+pub(crate) struct MapKeyDeserializer<'de>(pub &'de str);
+
+#[cfg(feature = "rayon")]
+macro_rules! deserialize_parsed_key {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                serde_json::Deserializer::from_str(self.0).$method(visitor)
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "rayon")]
+impl<'de> Deserializer<'de> for MapKeyDeserializer<'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf option unit unit_struct seq tuple tuple_struct map
+        struct enum identifier ignored_any
+    }
+}
+
+pub(crate) fn string_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DumbVisitor;
+
+    impl<'de> Visitor<'de> for DumbVisitor {
+        type Value = i64;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "integer or integer as string")
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            v.try_into()
+                .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(v)
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            // `i64::MAX as f64` rounds up to 2^63, which is already out of range
+            if v.fract() == 0.0 && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+                Ok(v as i64)
+            } else {
+                Err(E::invalid_value(Unexpected::Float(v), &self))
+            }
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            v.trim()
+                .replace(',', "")
+                .parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+
+    deserializer.deserialize_any(DumbVisitor)
+}
+
+pub(crate) fn string_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DumbVisitor;
+
+    impl<'de> Visitor<'de> for DumbVisitor {
+        type Value = f64;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "number or number as string")
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(v as f64)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(v as f64)
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(v)
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            v.trim()
+                .replace(',', "")
+                .parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+
+    deserializer.deserialize_any(DumbVisitor)
+}
+
 #[cfg(feature = "decimal")]
-pub(crate) fn string_or_decimal<'de, D>(deserializer: D) -> Result<rust_decimal::Decimal, D::Error>
+pub(crate) fn string_decimal<'de, D>(deserializer: D) -> Result<rust_decimal::Decimal, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -235,13 +675,171 @@ where
             Ok(v.into())
         }
 
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            rust_decimal::Decimal::try_from(v).map_err(E::custom)
+        }
+
         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
         where
             E: Error,
         {
-            rust_decimal::Decimal::from_str_exact(v).map_err(E::custom)
+            rust_decimal::Decimal::from_str_exact(&v.trim().replace(',', "")).map_err(E::custom)
         }
     }
 
     deserializer.deserialize_any(DumbVisitor)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_i64_accepts_both_encodings() {
+        #[derive(serde::Deserialize)]
+        struct Wrapper(#[serde(deserialize_with = "string_i64")] i64);
+
+        let Wrapper(num) = serde_json::from_str("1234").unwrap();
+        assert_eq!(num, 1234);
+
+        let Wrapper(string) = serde_json::from_str("\"1,234\"").unwrap();
+        assert_eq!(string, 1234);
+
+        let Wrapper(float) = serde_json::from_str("1234.0").unwrap();
+        assert_eq!(float, 1234);
+
+        assert!(serde_json::from_str::<Wrapper>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<Wrapper>("12.5").is_err());
+        assert!(serde_json::from_str::<Wrapper>("1e19").is_err());
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn string_decimal_normalises_strings() {
+        #[derive(serde::Deserialize)]
+        struct Wrapper(#[serde(deserialize_with = "string_decimal")] rust_decimal::Decimal);
+
+        let Wrapper(num) = serde_json::from_str("\" 1,234.5 \"").unwrap();
+        assert_eq!(num, rust_decimal::Decimal::new(12345, 1));
+    }
+
+    #[test]
+    fn empty_array_map() {
+        #[derive(serde::Deserialize)]
+        struct Wrapper(
+            #[serde(deserialize_with = "empty_array_is_empty_btree_map")] BTreeMap<String, i32>,
+        );
+
+        for empty in ["[]", "null", "{}"] {
+            let Wrapper(map) = serde_json::from_str(empty).unwrap();
+            assert!(map.is_empty());
+        }
+
+        let Wrapper(map) = serde_json::from_str(r#"{"a":1}"#).unwrap();
+        assert_eq!(map["a"], 1);
+
+        assert!(serde_json::from_str::<Wrapper>("[1]").is_err());
+    }
+
+    #[test]
+    fn id_keyed_flattened_map() {
+        #[derive(serde::Deserialize)]
+        struct Wrapper {
+            #[serde(deserialize_with = "id_keyed_map")]
+            members: BTreeMap<crate::UserId, i32>,
+            #[serde(flatten)]
+            rest: BTreeMap<String, serde_json::Value>,
+        }
+
+        let wrapper: Wrapper =
+            serde_json::from_str(r#"{"members":{"28":1,"2111649":2},"extra":true}"#).unwrap();
+        assert_eq!(wrapper.members[&crate::UserId(28)], 1);
+        assert!(wrapper.rest.contains_key("extra"));
+
+        let wrapper: Wrapper = serde_json::from_str(r#"{"members":[]}"#).unwrap();
+        assert!(wrapper.members.is_empty());
+    }
+
+    #[test]
+    fn durations() {
+        #[derive(serde::Deserialize)]
+        struct Timers {
+            #[serde(deserialize_with = "seconds_duration")]
+            full: Duration,
+            #[serde(deserialize_with = "zero_duration_is_none")]
+            cooldown: Option<Duration>,
+        }
+
+        let timers: Timers = serde_json::from_str(r#"{"full":90,"cooldown":0}"#).unwrap();
+        assert_eq!(timers.full, Duration::from_secs(90));
+        assert_eq!(timers.cooldown, None);
+
+        let timers: Timers = serde_json::from_str(r#"{"full":-5,"cooldown":60}"#).unwrap();
+        assert_eq!(timers.full, Duration::ZERO);
+        assert_eq!(timers.cooldown, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn boolean_strings() {
+        #[derive(serde::Deserialize)]
+        struct Flags {
+            #[serde(deserialize_with = "string_is_bool")]
+            string: bool,
+            #[serde(deserialize_with = "string_is_bool")]
+            int: bool,
+            #[serde(deserialize_with = "yes_no_is_bool")]
+            yes_no: bool,
+        }
+
+        let flags: Flags =
+            serde_json::from_str(r#"{"string":"1","int":0,"yes_no":"Yes"}"#).unwrap();
+        assert!(flags.string);
+        assert!(!flags.int);
+        assert!(flags.yes_no);
+
+        assert!(serde_json::from_str::<Flags>(r#"{"string":"2","int":0,"yes_no":"no"}"#).is_err());
+        assert!(
+            serde_json::from_str::<Flags>(r#"{"string":"0","int":0,"yes_no":"maybe"}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn null_collections_are_empty() {
+        #[derive(serde::Deserialize)]
+        struct Collections {
+            #[serde(deserialize_with = "null_is_empty_vec")]
+            vec: Vec<i32>,
+            #[serde(deserialize_with = "null_is_empty_btree_map")]
+            btree: BTreeMap<i32, i32>,
+            #[serde(deserialize_with = "null_is_empty_dict")]
+            hash: HashMap<String, i32>,
+        }
+
+        let empty: Collections =
+            serde_json::from_str(r#"{"vec":null,"btree":null,"hash":null}"#).unwrap();
+        assert!(empty.vec.is_empty());
+        assert!(empty.btree.is_empty());
+        assert!(empty.hash.is_empty());
+
+        let filled: Collections =
+            serde_json::from_str(r#"{"vec":[1],"btree":{"1":2},"hash":{"a":3}}"#).unwrap();
+        assert_eq!(filled.vec, vec![1]);
+        assert_eq!(filled.btree.get(&1), Some(&2));
+        assert_eq!(filled.hash.get("a"), Some(&3));
+    }
+
+    #[test]
+    fn string_f64_accepts_both_encodings() {
+        #[derive(serde::Deserialize)]
+        struct Wrapper(#[serde(deserialize_with = "string_f64")] f64);
+
+        let Wrapper(num) = serde_json::from_str("12.5").unwrap();
+        assert_eq!(num, 12.5);
+
+        let Wrapper(string) = serde_json::from_str("\"12.5\"").unwrap();
+        assert_eq!(string, 12.5);
+    }
+}
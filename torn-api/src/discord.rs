@@ -0,0 +1,148 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use thiserror::Error;
+
+use crate::{
+    send::{ApiClient, ApiProvider, RequestExecutor},
+    user, SelectionError, UserId,
+};
+
+#[derive(Error, Debug)]
+pub enum ResolveError<E>
+where
+    E: std::error::Error,
+{
+    #[error(transparent)]
+    Api(E),
+
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+}
+
+/// Resolves Discord ids to the ids of the linked Torn players and remembers the resolved pairs,
+/// so that only unknown Discord ids are requested.
+#[derive(Debug, Default)]
+pub struct DiscordResolver {
+    cache: Mutex<HashMap<i64, UserId>>,
+}
+
+impl DiscordResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cached(&self, discord_id: i64) -> Option<UserId> {
+        self.cache.lock().unwrap().get(&discord_id).copied()
+    }
+
+    /// Add a known pair, e.g. one loaded from persistent storage.
+    pub fn insert(&self, discord_id: i64, user_id: UserId) {
+        self.cache.lock().unwrap().insert(discord_id, user_id);
+    }
+
+    /// Forget a pair, e.g. after the user unlinked their account.
+    pub fn forget(&self, discord_id: i64) -> Option<UserId> {
+        self.cache.lock().unwrap().remove(&discord_id)
+    }
+
+    /// Resolve `discord_ids`, requesting the ones which aren't cached yet in bulk through
+    /// `provider`. Discord ids without a linked account resolve to `None` and aren't cached.
+    pub async fn resolve<C, E, L>(
+        &self,
+        provider: &ApiProvider<'_, C, E>,
+        discord_ids: L,
+    ) -> HashMap<i64, Result<Option<UserId>, ResolveError<E::Error>>>
+    where
+        C: ApiClient,
+        E: RequestExecutor<C>,
+        L: IntoIterator<Item = i64>,
+    {
+        let mut result = HashMap::new();
+        let mut missing = HashSet::new();
+        {
+            let cache = self.cache.lock().unwrap();
+            for discord_id in discord_ids {
+                match cache.get(&discord_id) {
+                    Some(user_id) => {
+                        result.insert(discord_id, Ok(Some(*user_id)));
+                    }
+                    None => {
+                        missing.insert(discord_id);
+                    }
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            return result;
+        }
+
+        let responses = provider
+            .users(missing, |b| b.selections([user::Selection::Discord]))
+            .await;
+
+        let mut cache = self.cache.lock().unwrap();
+        for (discord_id, response) in responses {
+            let resolved = response
+                .map_err(ResolveError::Api)
+                .and_then(|r| Ok(r.discord()?.user_id));
+            if let Ok(Some(user_id)) = resolved {
+                cache.insert(discord_id, user_id);
+            }
+            result.insert(discord_id, resolved);
+        }
+
+        result
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+
+    #[tokio::test]
+    async fn resolve_batch() {
+        let discord = |user_id: serde_json::Value, discord_id: &str| {
+            serde_json::json!({
+                "discord": { "userID": user_id, "discordID": discord_id }
+            })
+        };
+        let client = MockClient::new()
+            .respond(
+                Some(374272176892674048i64),
+                [user::Selection::Discord],
+                discord(2111649.into(), "374272176892674048"),
+            )
+            .respond(
+                Some(1i64),
+                [user::Selection::Discord],
+                discord("".into(), ""),
+            );
+        let provider = client.torn_api("key");
+
+        let resolver = DiscordResolver::new();
+        resolver.insert(2, UserId(28));
+
+        let resolved = resolver
+            .resolve(&provider, [374272176892674048, 1, 2])
+            .await;
+        assert_eq!(
+            resolved[&374272176892674048].as_ref().unwrap(),
+            &Some(UserId(2111649))
+        );
+        assert_eq!(resolved[&1].as_ref().unwrap(), &None);
+        assert_eq!(resolved[&2].as_ref().unwrap(), &Some(UserId(28)));
+        assert_eq!(client.requests().len(), 2);
+
+        assert_eq!(resolver.cached(374272176892674048), Some(UserId(2111649)));
+        assert_eq!(resolver.cached(1), None);
+
+        // resolved pairs aren't requested again
+        resolver.resolve(&provider, [374272176892674048]).await;
+        assert_eq!(client.requests().len(), 2);
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
 
 use chrono::{DateTime, TimeZone, Utc};
 use serde::{
@@ -8,21 +11,40 @@ use serde::{
 
 use torn_api_macros::{ApiCategory, IntoOwned};
 
-use crate::de_util::{self, null_is_empty_dict};
+use crate::{
+    de_util::{
+        self, empty_array_is_empty_btree_map, empty_dict_is_empty_array, null_is_empty_btree_map,
+        null_is_empty_dict, null_is_empty_vec, unix_timestamp,
+    },
+    FactionId, ItemId, UserId,
+};
 
-pub use crate::common::{Attack, AttackFull, LastAction, Status, Territory};
+pub use crate::common::{
+    ApplicationStatus, Attack, AttackFull, LastAction, Permissions, Position, RankedWar,
+    RankedWarFaction, RankedWarInfo, Status, Territory,
+};
 
 #[derive(Debug, Clone, Copy, ApiCategory)]
 #[api(category = "faction")]
 #[non_exhaustive]
 pub enum FactionSelection {
-    #[api(type = "Basic", flatten)]
+    #[api(type = "Basic<'a>", flatten)]
     Basic,
 
-    #[api(type = "BTreeMap<i32, Attack>", field = "attacks")]
+    #[api(
+        type = "BTreeMap<i64, Attack<'a>>",
+        field = "attacks",
+        with = "empty_array_is_empty_btree_map",
+        parallel
+    )]
     AttacksFull,
 
-    #[api(type = "BTreeMap<i32, AttackFull>", field = "attacks")]
+    #[api(
+        type = "BTreeMap<i64, AttackFull<'a>>",
+        field = "attacks",
+        with = "empty_array_is_empty_btree_map",
+        parallel
+    )]
     Attacks,
 
     #[api(
@@ -32,8 +54,116 @@ pub enum FactionSelection {
     )]
     Territory,
 
+    #[api(
+        type = "Vec<FactionTerritoryWar<'a>>",
+        field = "territory_wars",
+        with = "empty_dict_is_empty_array"
+    )]
+    TerritoryWars,
+
     #[api(type = "Option<Chain>", field = "chain", with = "deserialize_chain")]
     Chain,
+
+    #[api(
+        type = "BTreeMap<i64, ChainSummary>",
+        field = "chains",
+        with = "null_is_empty_btree_map"
+    )]
+    Chains,
+
+    /// The report of the chain with the given id, or of the latest chain without one.
+    #[api(type = "ChainReport", field = "chainreport")]
+    ChainReport,
+
+    #[api(
+        type = "BTreeMap<i32, RankedWar<'a>>",
+        field = "rankedwars",
+        with = "null_is_empty_btree_map"
+    )]
+    RankedWars,
+
+    #[api(
+        type = "BTreeMap<i32, Upgrade<'a>>",
+        field = "upgrades",
+        with = "null_is_empty_btree_map"
+    )]
+    Upgrades,
+
+    #[api(type = "BTreeMap<String, Permissions>", field = "positions")]
+    Positions,
+
+    #[api(
+        type = "Vec<ArmoryItem<'a>>",
+        field = "weapons",
+        with = "null_is_empty_vec"
+    )]
+    Weapons,
+
+    #[api(
+        type = "Vec<ArmoryItem<'a>>",
+        field = "armor",
+        with = "null_is_empty_vec"
+    )]
+    Armor,
+
+    #[api(
+        type = "Vec<ArmoryItem<'a>>",
+        field = "temporary",
+        with = "null_is_empty_vec"
+    )]
+    Temporary,
+
+    #[api(
+        type = "Vec<ArmoryItem<'a>>",
+        field = "drugs",
+        with = "null_is_empty_vec"
+    )]
+    Drugs,
+
+    #[api(
+        type = "Vec<ArmoryItem<'a>>",
+        field = "medical",
+        with = "null_is_empty_vec"
+    )]
+    Medical,
+
+    #[api(
+        type = "Vec<ArmoryItem<'a>>",
+        field = "boosters",
+        with = "null_is_empty_vec"
+    )]
+    Boosters,
+
+    #[api(
+        type = "BTreeMap<i64, OrganizedCrime<'a>>",
+        field = "crimes",
+        with = "null_is_empty_btree_map"
+    )]
+    Crimes,
+
+    #[api(
+        type = "BTreeMap<i64, Application<'a>>",
+        field = "applications",
+        with = "null_is_empty_btree_map"
+    )]
+    Applications,
+
+    #[api(
+        type = "BTreeMap<UserId, Donation<'a>>",
+        field = "donations",
+        with = "null_is_empty_btree_map"
+    )]
+    Donations,
+
+    /// The faction's money and points.
+    #[api(type = "Funds", flatten)]
+    Currency,
+
+    #[api(type = "Vec<&'a str>", field = "selections")]
+    Lookup,
+
+    #[api(type = "DateTime<Utc>", field = "timestamp", with = "unix_timestamp")]
+    Timestamp,
 }
 
 pub type Selection = FactionSelection;
@@ -43,17 +173,47 @@ pub struct Member<'a> {
     pub name: &'a str,
     pub level: i16,
     pub days_in_faction: i16,
-    pub position: &'a str,
+    pub position: Position<&'a str>,
     pub status: Status<'a>,
     pub last_action: LastAction,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, IntoOwned, Deserialize)]
+impl<'a> Member<'a> {
+    pub fn activity(&self) -> MemberActivity {
+        self.activity_at(Utc::now())
+    }
+
+    pub fn activity_at(&self, now: DateTime<Utc>) -> MemberActivity {
+        MemberActivity {
+            online: self.last_action.is_online(),
+            active_24h: self
+                .last_action
+                .is_active_within_at(Duration::from_secs(24 * 3600), now),
+            idle_days: self.last_action.seconds_since_at(now) / (24 * 3600),
+            last_action: self.last_action.timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemberActivity {
+    pub online: bool,
+    /// Active at some point in the last 24 hours.
+    pub active_24h: bool,
+    /// Full days since the member's last action.
+    pub idle_days: i64,
+    pub last_action: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
 pub struct FactionTerritoryWar<'a> {
     pub territory_war_id: i32,
     pub territory: &'a str,
-    pub assaulting_faction: i32,
-    pub defending_faction: i32,
+    pub assaulting_faction: FactionId,
+    pub defending_faction: FactionId,
     pub score: i32,
     pub required_score: i32,
 
@@ -64,12 +224,43 @@ pub struct FactionTerritoryWar<'a> {
     pub end_time: DateTime<Utc>,
 }
 
+/// The side a faction is fighting on in a territory war.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarSide {
+    Assaulting,
+    Defending,
+}
+
+impl<'a> FactionTerritoryWar<'a> {
+    /// The side of the given faction, or `None` if it isn't involved in the war.
+    pub fn side(&self, faction_id: FactionId) -> Option<WarSide> {
+        if self.assaulting_faction == faction_id {
+            Some(WarSide::Assaulting)
+        } else if self.defending_faction == faction_id {
+            Some(WarSide::Defending)
+        } else {
+            None
+        }
+    }
+
+    /// Share of the required score the assault has reached so far.
+    pub fn progress(&self) -> f64 {
+        if self.required_score == 0 {
+            0.0
+        } else {
+            self.score as f64 / self.required_score as f64
+        }
+    }
+}
+
 #[derive(Debug, IntoOwned, Deserialize)]
 pub struct Basic<'a> {
     #[serde(rename = "ID")]
-    pub id: i32,
+    pub id: FactionId,
     pub name: &'a str,
-    pub leader: i32,
+    pub leader: UserId,
+    #[serde(rename = "co-leader", deserialize_with = "de_util::zero_is_none")]
+    pub co_leader: Option<UserId>,
 
     pub respect: i32,
     pub age: i16,
@@ -80,25 +271,313 @@ pub struct Basic<'a> {
     pub tag_image: Option<&'a str>,
 
     #[serde(borrow)]
-    pub members: BTreeMap<i32, Member<'a>>,
+    pub members: BTreeMap<UserId, Member<'a>>,
 
     #[serde(deserialize_with = "de_util::datetime_map")]
-    pub peace: BTreeMap<i32, DateTime<Utc>>,
+    pub peace: BTreeMap<FactionId, DateTime<Utc>>,
 
     #[serde(borrow, deserialize_with = "de_util::empty_dict_is_empty_array")]
     pub territory_wars: Vec<FactionTerritoryWar<'a>>,
 }
 
-#[derive(Debug)]
+impl<'a> Basic<'a> {
+    /// Activity of every member, by their id.
+    pub fn activity(&self) -> BTreeMap<UserId, MemberActivity> {
+        self.activity_at(Utc::now())
+    }
+
+    pub fn activity_at(&self, now: DateTime<Utc>) -> BTreeMap<UserId, MemberActivity> {
+        self.members
+            .iter()
+            .map(|(id, member)| (*id, member.activity_at(now)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Chain {
     pub current: i32,
     pub max: i32,
     #[cfg(feature = "decimal")]
     pub modifier: rust_decimal::Decimal,
-    pub timeout: Option<i32>,
-    pub cooldown: Option<i32>,
+    pub timeout: Option<Duration>,
+    pub cooldown: Option<Duration>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// An item stocked in one of the armory's categories.
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct ArmoryItem<'a> {
+    #[serde(rename = "ID")]
+    pub item_id: ItemId,
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub item_type: &'a str,
+    pub quantity: i32,
+    /// Only listed for items which can be loaned.
+    pub available: Option<i32>,
+    pub loaned: Option<i32>,
+    #[serde(default, deserialize_with = "loaned_to")]
+    pub loaned_to: Vec<UserId>,
+}
+
+impl ArmoryItem<'_> {
+    pub fn is_loaned_to(&self, user_id: UserId) -> bool {
+        self.loaned_to.contains(&user_id)
+    }
+}
+
+/// Loans are sent as a comma separated list of user ids.
+fn loaned_to<'de, D>(deserializer: D) -> Result<Vec<UserId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(raw) = Option::<std::borrow::Cow<'de, str>>::deserialize(deserializer)? else {
+        return Ok(Vec::new());
+    };
+
+    raw.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse()
+                .map_err(|_| D::Error::invalid_value(Unexpected::Str(id), &"user id"))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct OrganizedCrime<'a> {
+    pub crime_id: i16,
+    pub crime_name: &'a str,
+    #[serde(deserialize_with = "participants")]
+    pub participants: Vec<UserId>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub time_started: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub time_ready: DateTime<Utc>,
+    #[serde(deserialize_with = "de_util::zero_date_is_none")]
+    pub time_completed: Option<DateTime<Utc>>,
+    #[serde(deserialize_with = "de_util::int_is_bool")]
+    pub initiated: bool,
+    #[serde(deserialize_with = "de_util::zero_is_none")]
+    pub initiated_by: Option<UserId>,
+    #[serde(deserialize_with = "de_util::zero_is_none")]
+    pub planned_by: Option<UserId>,
+    #[serde(deserialize_with = "de_util::int_is_bool")]
+    pub success: bool,
+    pub money_gain: i64,
+    pub respect_gain: i32,
+}
+
+impl OrganizedCrime<'_> {
+    pub fn is_ready_at(&self, now: DateTime<Utc>) -> bool {
+        !self.initiated && self.time_ready <= now
+    }
+}
+
+/// Participants are sent as a list of single entry maps from their id to their status.
+fn participants<'de, D>(deserializer: D) -> Result<Vec<UserId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<Vec<HashMap<UserId, serde::de::IgnoredAny>>> =
+        Option::deserialize(deserializer)?;
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(HashMap::into_keys)
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ApplicationStats {
+    pub strength: i64,
+    pub speed: i64,
+    pub dexterity: i64,
+    pub defense: i64,
+}
+
+impl ApplicationStats {
+    pub fn total(&self) -> i64 {
+        self.strength + self.speed + self.dexterity + self.defense
+    }
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct Application<'a> {
+    #[serde(rename = "userID")]
+    pub user_id: UserId,
+    pub name: &'a str,
+    pub level: i16,
+    /// Only present if the applicant chose to share their battle stats.
+    #[serde(default)]
+    pub stats: Option<ApplicationStats>,
+    pub message: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub expires: DateTime<Utc>,
+    pub status: ApplicationStatus,
+}
+
+impl Application<'_> {
+    pub fn is_pending(&self) -> bool {
+        self.status == ApplicationStatus::Active
+    }
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct Upgrade<'a> {
+    pub branch: &'a str,
+    #[serde(rename = "branchorder")]
+    pub branch_order: i16,
+    pub name: &'a str,
+    pub level: i16,
+    #[serde(rename = "basecost")]
+    pub base_cost: i64,
+    pub ability: String,
+}
+
+/// A member's balance in the faction bank.
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct Donation<'a> {
+    pub name: &'a str,
+    pub money_balance: i64,
+    pub points_balance: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Funds {
+    pub faction_id: FactionId,
+    pub money: i64,
+    pub points: i64,
+}
+
+impl Funds {
+    /// Money held by the faction itself, i.e. not owed to any member.
+    pub fn unallocated_money<'a, 'b: 'a, I>(&self, donations: I) -> i64
+    where
+        I: IntoIterator<Item = &'a Donation<'b>>,
+    {
+        self.money - donations.into_iter().map(|d| d.money_balance).sum::<i64>()
+    }
+
+    /// Points held by the faction itself, i.e. not owed to any member.
+    pub fn unallocated_points<'a, 'b: 'a, I>(&self, donations: I) -> i64
+    where
+        I: IntoIterator<Item = &'a Donation<'b>>,
+    {
+        self.points - donations.into_iter().map(|d| d.points_balance).sum::<i64>()
+    }
+}
+
+/// Hits which award bonus respect.
+pub const BONUS_HITS: [i32; 13] = [
+    10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000, 25000, 50000, 100000,
+];
+
+impl Chain {
+    /// The next bonus hit, `None` once the last one has been made.
+    pub fn next_bonus(&self) -> Option<i32> {
+        BONUS_HITS.into_iter().find(|hit| *hit > self.current)
+    }
+
+    /// Hits needed to reach the next bonus hit.
+    pub fn hits_to_bonus(&self) -> Option<i32> {
+        self.next_bonus().map(|hit| hit - self.current)
+    }
+}
+
+/// A finished chain, as listed in the `chains` selection.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainSummary {
+    #[serde(rename = "chain")]
+    pub length: i32,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub respect: f64,
+    #[serde(with = "chrono::serde::ts_seconds")]
     pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainReportMember {
+    #[serde(rename = "userID")]
+    pub user_id: UserId,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub respect: f64,
+    pub attacks: i32,
+    #[serde(rename = "leave")]
+    pub leaves: i32,
+    #[serde(rename = "mug")]
+    pub mugs: i32,
+    #[serde(rename = "hospitalize")]
+    pub hospitalizations: i32,
+    pub overseas: i32,
+    #[serde(rename = "war")]
+    pub war_hits: i32,
+    #[serde(rename = "retaliation")]
+    pub retaliations: i32,
+    #[serde(rename = "assist")]
+    pub assists: i32,
+    #[serde(rename = "bonus")]
+    pub bonus_hits: i32,
+    #[serde(rename = "besthit", deserialize_with = "de_util::string_f64")]
+    pub best_hit: f64,
+}
+
+/// A bonus hit of a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ChainBonus {
+    pub user_id: UserId,
+    pub hit: i32,
+    pub respect: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainReport {
+    #[serde(rename = "factionID")]
+    pub faction_id: FactionId,
+    #[serde(rename = "chain")]
+    pub length: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
     pub end: DateTime<Utc>,
+    #[serde(deserialize_with = "de_util::string_f64")]
+    pub respect: f64,
+    pub targets: i32,
+    #[serde(rename = "warhits")]
+    pub war_hits: i32,
+    #[serde(rename = "besthit", deserialize_with = "de_util::string_f64")]
+    pub best_hit: f64,
+    pub retaliations: i32,
+    #[serde(default, deserialize_with = "de_util::id_keyed_map")]
+    pub members: BTreeMap<UserId, ChainReportMember>,
+    /// Sent as `[user_id, hit, respect]` triples.
+    #[serde(default, deserialize_with = "chain_bonuses")]
+    pub bonuses: Vec<ChainBonus>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+fn chain_bonuses<'de, D>(deserializer: D) -> Result<Vec<ChainBonus>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<Vec<(UserId, i32, f64)>> = Option::deserialize(deserializer)?;
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(user_id, hit, respect)| ChainBonus {
+            user_id,
+            hit,
+            respect,
+        })
+        .collect())
 }
 
 fn deserialize_chain<'de, D>(deserializer: D) -> Result<Option<Chain>, D::Error>
@@ -107,6 +586,9 @@ where
 {
     struct ChainVisitor;
 
+    #[derive(Deserialize)]
+    struct Seconds(#[serde(deserialize_with = "de_util::seconds_duration")] Duration);
+
     impl<'de> Visitor<'de> for ChainVisitor {
         type Value = Option<Chain>;
 
@@ -159,16 +641,12 @@ where
                         }
                     }
                     Fields::Timeout => {
-                        match map.next_value()? {
-                            0 => timeout = Some(None),
-                            val => timeout = Some(Some(val)),
-                        };
+                        let Seconds(value) = map.next_value()?;
+                        timeout = Some((!value.is_zero()).then_some(value));
                     }
                     Fields::Cooldown => {
-                        match map.next_value()? {
-                            0 => cooldown = Some(None),
-                            val => cooldown = Some(Some(val)),
-                        };
+                        let Seconds(value) = map.next_value()?;
+                        cooldown = Some((!value.is_zero()).then_some(value));
                     }
                     Fields::Start => {
                         let ts: i64 = map.next_value()?;
@@ -228,6 +706,8 @@ mod tests {
                     Selection::Attacks,
                     Selection::Territory,
                     Selection::Chain,
+                    Selection::Positions,
+                    Selection::Crimes,
                 ])
             })
             .await
@@ -238,6 +718,8 @@ mod tests {
         response.attacks_full().unwrap();
         response.territory().unwrap();
         response.chain().unwrap();
+        response.positions().unwrap();
+        response.crimes().unwrap();
     }
 
     #[async_test]
@@ -247,8 +729,12 @@ mod tests {
         let response = Client::default()
             .torn_api(key)
             .faction(|b| {
-                b.id(7049)
-                    .selections([Selection::Basic, Selection::Territory, Selection::Chain])
+                b.id(7049).selections([
+                    Selection::Basic,
+                    Selection::Territory,
+                    Selection::Chain,
+                    Selection::RankedWars,
+                ])
             })
             .await
             .unwrap();
@@ -256,6 +742,7 @@ mod tests {
         response.basic().unwrap();
         response.territory().unwrap();
         response.chain().unwrap();
+        response.ranked_wars().unwrap();
     }
 
     #[async_test]
@@ -275,4 +762,409 @@ mod tests {
         response.territory().unwrap();
         assert!(response.chain().unwrap().is_none());
     }
+
+    #[async_test]
+    async fn lookup() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .faction(|b| b.selections([Selection::Lookup, Selection::Timestamp]))
+            .await
+            .unwrap();
+
+        assert!(response.lookup().unwrap().contains(&"basic"));
+        assert!(response.timestamp().unwrap() > Utc.timestamp_opt(1_650_000_000, 0).unwrap());
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn attack_ids() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [Selection::AttacksFull],
+            serde_json::json!({
+                "attacks": {
+                    "3000000000": {
+                        "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                        "timestamp_started": 1_700_000_000,
+                        "timestamp_ended": 1_700_000_100,
+                        "attacker_id": "",
+                        "attacker_faction": "",
+                        "defender_id": 28,
+                        "defender_faction": 7,
+                        "result": "Lost",
+                        "stealthed": 1,
+                        "respect": 0.0,
+                    }
+                }
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .faction(|b| b.selections([Selection::AttacksFull]))
+            .await
+            .unwrap();
+
+        // attack ids have outgrown i32
+        assert!(response
+            .attacks_full()
+            .unwrap()
+            .contains_key(&3_000_000_000));
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn territory_wars() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new()
+            .respond(
+                Some(7),
+                [Selection::TerritoryWars],
+                serde_json::json!({
+                    "territory_wars": [{
+                        "territory_war_id": 1,
+                        "territory": "ABC",
+                        "assaulting_faction": 8,
+                        "defending_faction": 7,
+                        "score": 250,
+                        "required_score": 1000,
+                        "start_time": 1_700_000_000,
+                        "end_time": 1_700_100_000,
+                    }]
+                }),
+            )
+            .respond(
+                Some(9),
+                [Selection::TerritoryWars],
+                serde_json::json!({ "territory_wars": {} }),
+            );
+
+        let response = client
+            .torn_api("key")
+            .faction(|b| b.id(7).selections([Selection::TerritoryWars]))
+            .await
+            .unwrap();
+        let wars = response.territory_wars().unwrap();
+        assert_eq!(wars[0].side(FactionId(7)), Some(WarSide::Defending));
+        assert_eq!(wars[0].side(FactionId(8)), Some(WarSide::Assaulting));
+        assert_eq!(wars[0].side(FactionId(9)), None);
+        assert_eq!(wars[0].progress(), 0.25);
+
+        let response = client
+            .torn_api("key")
+            .faction(|b| b.id(9).selections([Selection::TerritoryWars]))
+            .await
+            .unwrap();
+        assert!(response.territory_wars().unwrap().is_empty());
+    }
+
+    #[test]
+    fn activity() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let member = |status: &str, ago: i64| {
+            serde_json::json!({
+                "name": "Chedburn",
+                "level": 15,
+                "days_in_faction": 100,
+                "position": "Member",
+                "status": {
+                    "description": "Okay",
+                    "details": "",
+                    "state": "Okay",
+                    "color": "green",
+                    "until": 0,
+                },
+                "last_action": {
+                    "status": status,
+                    "timestamp": 1_700_000_000 - ago,
+                    "relative": "",
+                },
+            })
+        };
+        let json = serde_json::json!({
+            "1": member("Online", 0),
+            "2": member("Offline", 3600),
+            "3": member("Offline", 3 * 24 * 3600 + 60),
+        })
+        .to_string();
+        // members borrow from the input, so they can't be read from a `Value`
+        let members: BTreeMap<UserId, Member> = serde_json::from_str(&json).unwrap();
+
+        let activity: Vec<_> = members.values().map(|m| m.activity_at(now)).collect();
+        assert!(activity[0].online && activity[0].active_24h);
+        assert!(!activity[1].online && activity[1].active_24h);
+        assert_eq!(activity[1].idle_days, 0);
+        assert!(!activity[2].active_24h);
+        assert_eq!(activity[2].idle_days, 3);
+    }
+
+    #[async_test]
+    async fn chains() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .faction(|b| b.selections([Selection::Chains, Selection::ChainReport]))
+            .await
+            .unwrap();
+
+        response.chains().unwrap();
+        response.chain_report().unwrap();
+    }
+
+    #[test]
+    fn ranked_war_score() {
+        let json = serde_json::json!({
+            "factions": {
+                "7": { "name": "Seven", "score": 1200, "chain": 40 },
+                "9": { "name": "Nine", "score": 1500, "chain": 12 },
+            },
+            "war": { "start": 1_700_000_000, "end": 0, "target": 3000, "winner": 0 },
+        })
+        .to_string();
+        let war: RankedWar = serde_json::from_str(&json).unwrap();
+
+        assert!(!war.is_over());
+        assert_eq!(war.war.winner, None);
+        assert_eq!(war.opponent(FactionId(7)), Some(FactionId(9)));
+        assert_eq!(war.score_differential(FactionId(7)), Some(-300));
+        assert_eq!(war.score_differential(FactionId(9)), Some(300));
+        assert_eq!(war.score_differential(FactionId(8)), None);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn applications() {
+        use crate::mock::MockClient;
+
+        let application = |user_id: i32, stats: serde_json::Value| {
+            serde_json::json!({
+                "userID": user_id,
+                "name": "Applicant",
+                "level": 20,
+                "stats": stats,
+                "message": "Let me in, \"please\"",
+                "expires": 1_700_000_000,
+                "status": "active",
+            })
+        };
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [Selection::Applications],
+            serde_json::json!({
+                "applications": {
+                    "10": application(1, serde_json::json!({
+                        "strength": 100, "speed": 200, "dexterity": 300, "defense": 400,
+                    })),
+                    "11": application(2, serde_json::Value::Null),
+                }
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .faction(|b| b.selections([Selection::Applications]))
+            .await
+            .unwrap();
+        let applications = response.applications().unwrap();
+
+        assert!(applications[&10].is_pending());
+        assert_eq!(applications[&10].stats.unwrap().total(), 1000);
+        assert_eq!(applications[&10].message, "Let me in, \"please\"");
+        assert!(applications[&11].stats.is_none());
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn funds() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [Selection::Donations, Selection::Currency],
+            serde_json::json!({
+                "donations": {
+                    "1": { "name": "Chedburn", "money_balance": 1_000_000, "points_balance": 20 },
+                    "2": { "name": "Duke", "money_balance": 500_000, "points_balance": 0 },
+                },
+                "faction_id": 7,
+                "money": 10_000_000,
+                "points": 100,
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .faction(|b| b.selections([Selection::Donations, Selection::Currency]))
+            .await
+            .unwrap();
+        let donations = response.donations().unwrap();
+        let funds = response.currency().unwrap();
+
+        assert_eq!(donations[&UserId(1)].money_balance, 1_000_000);
+        assert_eq!(funds.unallocated_money(donations.values()), 8_500_000);
+        assert_eq!(funds.unallocated_points(donations.values()), 80);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn upgrades_and_positions() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new().respond(
+            None::<i32>,
+            [Selection::Upgrades, Selection::Positions],
+            serde_json::json!({
+                "upgrades": {
+                    "1": {
+                        "branch": "Core", "branchorder": 0, "branchmultiplier": 1,
+                        "name": "Faction headquarters", "level": 1, "basecost": 0,
+                        "ability": "Base of operations", "unlocked": "01-01-2020",
+                    },
+                },
+                "positions": {
+                    "Banker": {
+                        "default": 0, "canUseMedicalItem": 1, "canGiveMoney": "1",
+                        "canAdjustMemberBalance": 1,
+                    },
+                },
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .faction(|b| b.selections([Selection::Upgrades, Selection::Positions]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.upgrades().unwrap()[&1].branch, "Core");
+        let positions = response.positions().unwrap();
+        let granted: Vec<_> = positions["Banker"].granted().collect();
+        assert_eq!(
+            granted,
+            [
+                "can_use_medical_item",
+                "can_adjust_member_balance",
+                "can_give_money"
+            ]
+        );
+    }
+
+    #[test]
+    fn bonus_hits() {
+        let chain = Chain {
+            current: 97,
+            max: 100,
+            #[cfg(feature = "decimal")]
+            modifier: rust_decimal::Decimal::ONE,
+            timeout: None,
+            cooldown: None,
+            start: Utc::now(),
+            end: Utc::now(),
+        };
+        assert_eq!(chain.next_bonus(), Some(100));
+        assert_eq!(chain.hits_to_bonus(), Some(3));
+
+        let chain = Chain {
+            current: 100_000,
+            ..chain
+        };
+        assert_eq!(chain.next_bonus(), None);
+    }
+
+    #[async_test]
+    async fn armory() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .faction(|b| {
+                b.selections([
+                    Selection::Weapons,
+                    Selection::Armor,
+                    Selection::Temporary,
+                    Selection::Drugs,
+                    Selection::Medical,
+                    Selection::Boosters,
+                ])
+            })
+            .await
+            .unwrap();
+
+        response.weapons().unwrap();
+        response.armor().unwrap();
+        response.temporary().unwrap();
+        response.drugs().unwrap();
+        response.medical().unwrap();
+        response.boosters().unwrap();
+    }
+
+    #[test]
+    fn organized_crime() {
+        let crime: OrganizedCrime = serde_json::from_str(
+            r#"{"crime_id":8,"crime_name":"Political Assassination",
+                "participants":[{"28":{"description":"Okay","state":"Okay"}},{"4":null}],
+                "time_started":1700000000,"time_ready":1700500000,"time_left":0,
+                "time_completed":0,"initiated":0,"initiated_by":0,"planned_by":28,"success":0,
+                "money_gain":0,"respect_gain":0}"#,
+        )
+        .unwrap();
+
+        assert_eq!(crime.participants, [UserId(28), UserId(4)]);
+        assert_eq!(crime.time_completed, None);
+        assert_eq!(crime.initiated_by, None);
+        assert!(crime.is_ready_at(Utc.timestamp_opt(1_700_500_000, 0).unwrap()));
+    }
+
+    #[test]
+    fn expired_chain_timeout() {
+        let chain = deserialize_chain(&serde_json::json!({
+            "current": 25,
+            "max": 100,
+            "modifier": 1.1,
+            "timeout": -2,
+            "cooldown": 0,
+            "start": 1700000000,
+            "end": 1700003600,
+        }))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(chain.current, 25);
+        assert_eq!(chain.timeout, None);
+        assert_eq!(chain.cooldown, None);
+    }
+
+    #[test]
+    fn loans() {
+        let item: ArmoryItem = serde_json::from_str(
+            r#"{"ID":1,"name":"Hammer","type":"Melee","quantity":3,"available":1,"loaned":2,
+                "loaned_to":"28,2111649"}"#,
+        )
+        .unwrap();
+        assert!(item.is_loaned_to(UserId(2111649)));
+
+        let item: ArmoryItem =
+            serde_json::from_str(r#"{"ID":180,"name":"Beer","type":"Alcohol","quantity":3}"#)
+                .unwrap();
+        assert!(item.loaned_to.is_empty());
+    }
+
+    #[async_test]
+    async fn bulk() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .factions([7049, 8981], |b| b.selections([Selection::Basic]))
+            .await;
+
+        response.get(&7049).as_ref().unwrap().as_ref().unwrap();
+        response.get(&8981).as_ref().unwrap().as_ref().unwrap();
+    }
 }
@@ -0,0 +1,297 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{common::Country, into_owned::IntoOwned};
+
+const NAMES: &[&str] = &[
+    "Ace", "Bandit", "Cobra", "Dagger", "Echo", "Falcon", "Ghost", "Hydra", "Iris", "Jinx",
+];
+
+const RESULTS: &[&str] = &[
+    "Attacked",
+    "Mugged",
+    "Hospitalized",
+    "Lost",
+    "Escape",
+    "Stalemate",
+    "Assist",
+    "Timeout",
+];
+
+/// Generates random but valid api payloads, either as json for feeding into e.g. a
+/// [`MockClient`](crate::mock::MockClient), or already decoded into the owned models.
+pub struct Faker<R = StdRng> {
+    rng: R,
+}
+
+impl Faker<StdRng> {
+    pub fn seeded(seed: u64) -> Self {
+        Self::new(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<R> Faker<R>
+where
+    R: Rng,
+{
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+
+    pub fn rng(&mut self) -> &mut R {
+        &mut self.rng
+    }
+
+    fn name(&mut self) -> String {
+        format!(
+            "{}{}",
+            NAMES.choose(&mut self.rng).unwrap(),
+            self.rng.gen_range(1..10_000)
+        )
+    }
+
+    fn timestamp(&mut self) -> i64 {
+        self.rng.gen_range(1_500_000_000..1_700_000_000)
+    }
+
+    fn country(&mut self) -> Country {
+        let abroad: Vec<_> = Country::all().filter(|c| *c != Country::Torn).collect();
+        *abroad.choose(&mut self.rng).unwrap()
+    }
+
+    pub fn status(&mut self) -> Value {
+        let until = self.timestamp();
+        match self.rng.gen_range(0..5) {
+            0 => json!({
+                "description": "In hospital for 12 mins",
+                "details": "Hospitalized by someone",
+                "state": "Hospital",
+                "color": "red",
+                "until": until,
+            }),
+            1 => json!({
+                "description": "In jail for 5 mins",
+                "details": "Caught shoplifting",
+                "state": "Jail",
+                "color": "red",
+                "until": until,
+            }),
+            2 => json!({
+                "description": format!("Traveling to {}", self.country()),
+                "details": "",
+                "state": "Traveling",
+                "color": "blue",
+                "until": 0,
+            }),
+            3 => json!({
+                "description": format!("In {}", self.country()),
+                "details": "",
+                "state": "Abroad",
+                "color": "blue",
+                "until": 0,
+            }),
+            _ => json!({
+                "description": "Okay",
+                "details": "",
+                "state": "Okay",
+                "color": "green",
+                "until": 0,
+            }),
+        }
+    }
+
+    pub fn last_action(&mut self) -> Value {
+        let status = *["Online", "Idle", "Offline"].choose(&mut self.rng).unwrap();
+        json!({
+            "status": status,
+            "timestamp": self.timestamp(),
+            "relative": "some time ago",
+        })
+    }
+
+    #[cfg(feature = "user")]
+    pub fn profile(&mut self, id: i64) -> Value {
+        let maximum = self.rng.gen_range(100..10_000);
+        let faction_id = self.rng.gen_range(0..50_000);
+        let faction = if faction_id == 0 || self.rng.gen_bool(0.2) {
+            json!({
+                "position": "None",
+                "faction_id": 0,
+                "days_in_faction": 0,
+                "faction_name": "None",
+                "faction_tag": "None",
+            })
+        } else {
+            let position = *["Leader", "Co-leader", "Member", "Recruit"]
+                .choose(&mut self.rng)
+                .unwrap();
+            json!({
+                "position": position,
+                "faction_id": faction_id,
+                "days_in_faction": self.rng.gen_range(0..3000),
+                "faction_name": self.name(),
+                "faction_tag": "TAG",
+            })
+        };
+        let gender = *["Male", "Female", "Enby"].choose(&mut self.rng).unwrap();
+
+        json!({
+            "player_id": id,
+            "name": self.name(),
+            "rank": "Average Civilian",
+            "level": self.rng.gen_range(1..=100),
+            "gender": gender,
+            "age": self.rng.gen_range(0..7000),
+            "life": {
+                "current": self.rng.gen_range(0..=maximum),
+                "maximum": maximum,
+                "increment": maximum / 20,
+                "interval": 300,
+                "ticktime": self.rng.gen_range(0..300),
+                "fulltime": self.rng.gen_range(0..7200),
+            },
+            "last_action": self.last_action(),
+            "faction": faction,
+            "job": {
+                "job": "Employee",
+                "position": "Employee",
+                "company_id": self.rng.gen_range(1..100_000),
+                "company_name": self.name(),
+                "company_type": self.rng.gen_range(1..=40),
+            },
+            "status": self.status(),
+            "competition": null,
+            "revivable": self.rng.gen_range(0..=1),
+        })
+    }
+
+    #[cfg(feature = "user")]
+    pub fn profile_owned(&mut self, id: i64) -> crate::user::ProfileOwned {
+        let value = self.profile(id);
+        crate::user::Profile::deserialize(&value)
+            .expect("generated profile")
+            .into_owned()
+    }
+
+    pub fn attack(&mut self) -> Value {
+        let started = self.timestamp();
+        let result = *RESULTS.choose(&mut self.rng).unwrap();
+        let respect = if matches!(result, "Attacked" | "Mugged" | "Hospitalized") {
+            self.rng.gen_range(0.0..10.0)
+        } else {
+            0.0
+        };
+        let stealthed = self.rng.gen_bool(0.3);
+        let attacker_faction = self.rng.gen_range(1..50_000);
+
+        json!({
+            "code": format!("{:032x}", self.rng.gen::<u128>()),
+            "timestamp_started": started,
+            "timestamp_ended": started + self.rng.gen_range(1..300),
+            "attacker_id": if stealthed { json!("") } else { json!(self.rng.gen_range(1..3_000_000)) },
+            "attacker_name": if stealthed { json!("") } else { json!(self.name()) },
+            "attacker_faction": if stealthed { json!("") } else { json!(attacker_faction) },
+            "attacker_factionname": if stealthed { json!("") } else { json!(self.name()) },
+            "defender_id": self.rng.gen_range(1..3_000_000),
+            "defender_name": self.name(),
+            "defender_faction": "",
+            "defender_factionname": "",
+            "result": result,
+            "stealthed": u8::from(stealthed),
+            "raid": 0,
+            "ranked_war": 0,
+            "respect": respect,
+            "respect_loss": 0.0,
+            "modifiers": {
+                "fair_fight": self.rng.gen_range(1.0..3.0),
+                "war": 1,
+                "retaliation": 1,
+                "group_attack": 1,
+                "overseas": 1,
+                "chain_bonus": 1,
+            },
+        })
+    }
+
+    pub fn attack_owned(&mut self) -> crate::common::AttackFullOwned {
+        let value = self.attack();
+        crate::common::AttackFull::deserialize(&value)
+            .expect("generated attack")
+            .into_owned()
+    }
+
+    pub fn faction_member(&mut self) -> Value {
+        let position = *["Member", "Recruit", "Lieutenant"]
+            .choose(&mut self.rng)
+            .unwrap();
+        json!({
+            "name": self.name(),
+            "level": self.rng.gen_range(1..=100),
+            "days_in_faction": self.rng.gen_range(0..3000),
+            "position": position,
+            "status": self.status(),
+            "last_action": self.last_action(),
+        })
+    }
+
+    #[cfg(feature = "faction")]
+    pub fn faction(&mut self, id: i64, members: usize) -> Value {
+        let mut member_map = serde_json::Map::new();
+        while member_map.len() < members {
+            let member_id = self.rng.gen_range(1..3_000_000).to_string();
+            member_map.insert(member_id, self.faction_member());
+        }
+        let leader = member_map
+            .keys()
+            .next()
+            .map(|id| id.parse::<i64>().unwrap())
+            .unwrap_or_default();
+        let co_leader = member_map
+            .keys()
+            .nth(1)
+            .map(|id| id.parse::<i64>().unwrap())
+            .unwrap_or_default();
+
+        json!({
+            "ID": id,
+            "name": self.name(),
+            "leader": leader,
+            "co-leader": co_leader,
+            "respect": self.rng.gen_range(0..5_000_000),
+            "age": self.rng.gen_range(0..6000),
+            "capacity": 100,
+            "best_chain": self.rng.gen_range(0..100_000),
+            "tag_image": "",
+            "members": member_map,
+            "peace": {},
+            "territory_wars": {},
+        })
+    }
+
+    #[cfg(feature = "faction")]
+    pub fn faction_owned(&mut self, id: i64, members: usize) -> crate::faction::BasicOwned {
+        let value = self.faction(id, members);
+        crate::faction::Basic::deserialize(&value)
+            .expect("generated faction")
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_models_decode() {
+        let mut faker = Faker::seeded(2111649);
+
+        for id in 0..50 {
+            #[cfg(feature = "user")]
+            assert_eq!(faker.profile_owned(id).player_id.get(), id);
+            faker.attack_owned();
+            #[cfg(feature = "faction")]
+            assert_eq!(faker.faction_owned(id, 10).members.len(), 10);
+        }
+    }
+}
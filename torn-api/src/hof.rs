@@ -0,0 +1,238 @@
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    send::ApiClient, ApiResponse, ApiVersion, FactionId, ResponseError, SelectionError, UserId,
+};
+
+const BASE_URL: &str = "https://api.torn.com/v2/torn/hof";
+
+/// Maximum number of entries per page.
+pub const MAX_LIMIT: usize = 100;
+
+#[derive(Error, Debug)]
+pub enum HofError<C>
+where
+    C: std::error::Error,
+{
+    #[error(transparent)]
+    Client(C),
+
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HofCategory {
+    Level,
+    Rank,
+    Networth,
+    Awards,
+    BattleStats,
+    WorkingStats,
+    TravelTime,
+    RacingSkill,
+    RacingPoints,
+    RacingWins,
+}
+
+impl HofCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Level => "level",
+            Self::Rank => "rank",
+            Self::Networth => "networth",
+            Self::Awards => "awards",
+            Self::BattleStats => "battlestats",
+            Self::WorkingStats => "workingstats",
+            Self::TravelTime => "traveltime",
+            Self::RacingSkill => "racingskill",
+            Self::RacingPoints => "racingpoints",
+            Self::RacingWins => "racingwins",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HofRequest {
+    pub category: HofCategory,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl HofRequest {
+    pub fn new(category: HofCategory) -> Self {
+        Self {
+            category,
+            offset: 0,
+            limit: MAX_LIMIT,
+        }
+    }
+
+    #[must_use]
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit.clamp(1, MAX_LIMIT);
+        self
+    }
+
+    pub fn url(&self, key: &str) -> String {
+        format!(
+            "{BASE_URL}?cat={}&offset={}&limit={}&key={key}",
+            self.category.as_str(),
+            self.offset,
+            self.limit
+        )
+    }
+}
+
+/// The ranked value, e.g. a level or a networth. Some categories rank by a title instead of a
+/// number.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum HofValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HofEntry {
+    pub id: UserId,
+    pub username: String,
+    pub faction_id: Option<FactionId>,
+    pub level: i16,
+    #[serde(with = "ts_seconds")]
+    pub last_action: DateTime<Utc>,
+    pub rank_name: String,
+    pub rank_number: i16,
+    pub position: i32,
+    #[serde(with = "ts_seconds")]
+    pub signed_up: DateTime<Utc>,
+    pub age_in_days: i32,
+    pub value: HofValue,
+    pub rank: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HofPage {
+    pub entries: Vec<HofEntry>,
+    /// The request for the following page, if there is one.
+    pub next: Option<HofRequest>,
+}
+
+/// A client for the hall of fame listings, which are only available in v2 of the API.
+pub struct HofProvider<'a, C>
+where
+    C: ApiClient,
+{
+    client: &'a C,
+    key: String,
+}
+
+impl<'a, C> HofProvider<'a, C>
+where
+    C: ApiClient,
+{
+    pub fn new<S>(client: &'a C, key: S) -> Self
+    where
+        S: ToString,
+    {
+        Self {
+            client,
+            key: key.to_string(),
+        }
+    }
+
+    pub async fn ranks(&self, request: HofRequest) -> Result<HofPage, HofError<C::Error>> {
+        #[derive(Deserialize)]
+        struct Links {
+            next: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct Metadata {
+            links: Links,
+        }
+
+        let raw = self
+            .client
+            .request(request.url(&self.key))
+            .await
+            .map_err(HofError::Client)?;
+        let response = ApiResponse::from_raw(raw)?;
+
+        let entries = response.decode_versioned("hof", "Vec<HofEntry>", "hof", ApiVersion::V2)?;
+        let metadata: Metadata =
+            response.decode_versioned("hof", "Metadata", "_metadata", ApiVersion::V2)?;
+
+        Ok(HofPage {
+            entries,
+            next: metadata
+                .links
+                .next
+                .map(|_| request.offset(request.offset + request.limit)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RawResponse;
+
+    struct Canned(&'static str);
+
+    #[async_trait::async_trait]
+    impl ApiClient for Canned {
+        type Error = std::convert::Infallible;
+
+        async fn request(&self, _url: String) -> Result<RawResponse, Self::Error> {
+            Ok(RawResponse {
+                status: 200,
+                content_type: Some("application/json".to_owned()),
+                body: self.0.as_bytes().to_vec(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn ranks() {
+        let client = Canned(
+            r#"{"hof":[{"id":1,"username":"Chedburn","faction_id":null,"level":15,
+                "last_action":1650000000,"rank_name":"Reasonable","rank_number":8,"position":1,
+                "signed_up":1068000000,"age_in_days":7000,"value":100,"rank":null}],
+                "_metadata":{"links":{"prev":null,"next":"https://api.torn.com/v2/torn/hof?cat=level&offset=1&limit=1"}}}"#,
+        );
+        let request = HofRequest::new(HofCategory::Level).limit(1);
+        assert_eq!(
+            request.url("key"),
+            "https://api.torn.com/v2/torn/hof?cat=level&offset=0&limit=1&key=key"
+        );
+
+        let page = HofProvider::new(&client, "key")
+            .ranks(request)
+            .await
+            .unwrap();
+        assert_eq!(page.entries[0].id, UserId(1));
+        assert_eq!(page.entries[0].value, HofValue::Int(100));
+        assert_eq!(page.next.unwrap().offset, 1);
+
+        let client = Canned(r#"{"error":{"code":2,"error":"Incorrect key"}}"#);
+        let err = HofProvider::new(&client, "key")
+            .ranks(HofRequest::new(HofCategory::Networth))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HofError::Response(e) if e.is_key_error()));
+    }
+}
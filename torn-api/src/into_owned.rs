@@ -53,7 +53,7 @@ impl<Z> IntoOwned for chrono::DateTime<Z> where Z: chrono::TimeZone {
     }
 }
 
-impl<'a> IntoOwned for &'a str {
+impl IntoOwned for &str {
     type Owned = String;
 
     fn into_owned(self) -> Self::Owned {
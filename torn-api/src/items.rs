@@ -0,0 +1,92 @@
+pub mod ids {
+    use crate::ItemId;
+
+    // medical
+    pub const MORPHINE: ItemId = ItemId(66);
+    pub const FIRST_AID_KIT: ItemId = ItemId(67);
+    pub const SMALL_FIRST_AID_KIT: ItemId = ItemId(68);
+
+    // drugs
+    pub const CANNABIS: ItemId = ItemId(196);
+    pub const ECSTASY: ItemId = ItemId(197);
+    pub const KETAMINE: ItemId = ItemId(198);
+    pub const LSD: ItemId = ItemId(199);
+    pub const OPIUM: ItemId = ItemId(200);
+    pub const PCP: ItemId = ItemId(201);
+    pub const SHROOMS: ItemId = ItemId(203);
+    pub const SPEED: ItemId = ItemId(204);
+    pub const VICODIN: ItemId = ItemId(205);
+    pub const XANAX: ItemId = ItemId(206);
+
+    // energy drinks
+    pub const CAN_OF_MUNSTER: ItemId = ItemId(530);
+    pub const CAN_OF_RED_COW: ItemId = ItemId(532);
+    pub const CAN_OF_TAURINE: ItemId = ItemId(533);
+
+    // temporary weapons
+    pub const SMOKE_GRENADE: ItemId = ItemId(226);
+    pub const TEAR_GAS: ItemId = ItemId(256);
+    pub const PEPPER_SPRAY: ItemId = ItemId(392);
+
+    // flowers
+    pub const DAHLIA: ItemId = ItemId(260);
+    pub const CROCUS: ItemId = ItemId(263);
+    pub const ORCHID: ItemId = ItemId(264);
+    pub const HEATHER: ItemId = ItemId(267);
+    pub const CEIBO_FLOWER: ItemId = ItemId(271);
+    pub const EDELWEISS: ItemId = ItemId(272);
+    pub const PEONY: ItemId = ItemId(276);
+    pub const CHERRY_BLOSSOM: ItemId = ItemId(277);
+    pub const AFRICAN_VIOLET: ItemId = ItemId(282);
+    pub const TRIBULUS_OMANENSE: ItemId = ItemId(385);
+    pub const BANANA_ORCHID: ItemId = ItemId(617);
+
+    // plushies
+    pub const SHEEP_PLUSHIE: ItemId = ItemId(186);
+    pub const TEDDY_BEAR_PLUSHIE: ItemId = ItemId(187);
+    pub const KITTEN_PLUSHIE: ItemId = ItemId(215);
+    pub const JAGUAR_PLUSHIE: ItemId = ItemId(258);
+    pub const WOLVERINE_PLUSHIE: ItemId = ItemId(261);
+    pub const NESSIE_PLUSHIE: ItemId = ItemId(266);
+    pub const RED_FOX_PLUSHIE: ItemId = ItemId(268);
+    pub const MONKEY_PLUSHIE: ItemId = ItemId(269);
+    pub const CHAMOIS_PLUSHIE: ItemId = ItemId(273);
+    pub const PANDA_PLUSHIE: ItemId = ItemId(274);
+    pub const LION_PLUSHIE: ItemId = ItemId(281);
+    pub const CAMEL_PLUSHIE: ItemId = ItemId(384);
+    pub const STINGRAY_PLUSHIE: ItemId = ItemId(618);
+
+    pub const DRUGS: &[ItemId] = &[
+        CANNABIS, ECSTASY, KETAMINE, LSD, OPIUM, PCP, SHROOMS, SPEED, VICODIN, XANAX,
+    ];
+
+    pub const FLOWERS: &[ItemId] = &[
+        DAHLIA,
+        CROCUS,
+        ORCHID,
+        HEATHER,
+        CEIBO_FLOWER,
+        EDELWEISS,
+        PEONY,
+        CHERRY_BLOSSOM,
+        AFRICAN_VIOLET,
+        TRIBULUS_OMANENSE,
+        BANANA_ORCHID,
+    ];
+
+    pub const PLUSHIES: &[ItemId] = &[
+        SHEEP_PLUSHIE,
+        TEDDY_BEAR_PLUSHIE,
+        KITTEN_PLUSHIE,
+        JAGUAR_PLUSHIE,
+        WOLVERINE_PLUSHIE,
+        NESSIE_PLUSHIE,
+        RED_FOX_PLUSHIE,
+        MONKEY_PLUSHIE,
+        CHAMOIS_PLUSHIE,
+        PANDA_PLUSHIE,
+        LION_PLUSHIE,
+        CAMEL_PLUSHIE,
+        STINGRAY_PLUSHIE,
+    ];
+}
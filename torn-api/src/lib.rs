@@ -1,8 +1,14 @@
 #![warn(clippy::all, clippy::perf, clippy::style, clippy::suspicious)]
 
+pub mod bulk;
+pub mod connection;
+pub mod ids;
 pub mod into_owned;
+pub mod items;
 pub mod local;
+pub mod selection_set;
 pub mod send;
+pub mod stats;
 
 #[cfg(feature = "user")]
 pub mod user;
@@ -10,12 +16,21 @@ pub mod user;
 #[cfg(feature = "faction")]
 pub mod faction;
 
+#[cfg(feature = "company")]
+pub mod company;
+
 #[cfg(feature = "market")]
 pub mod market;
 
 #[cfg(feature = "torn")]
 pub mod torn;
 
+#[cfg(feature = "torn")]
+pub mod hof;
+
+#[cfg(feature = "torn")]
+pub mod stocks;
+
 #[cfg(feature = "key")]
 pub mod key;
 
@@ -25,96 +40,1151 @@ pub mod awc;
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
 
+#[cfg(feature = "wiremock")]
+pub mod wiremock;
+
+#[cfg(feature = "fake")]
+pub mod fake;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "bumpalo")]
+pub mod arena;
+
+#[cfg(feature = "rkyv")]
+pub mod snapshot;
+
+#[cfg(feature = "stream")]
+pub mod stream;
+
+#[cfg(feature = "tornstats")]
+pub mod tornstats;
+
+#[cfg(feature = "yata")]
+pub mod yata;
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+#[cfg(feature = "tokio")]
+pub mod limit;
+
+#[cfg(feature = "csv")]
+pub mod csv;
+
 #[cfg(feature = "__common")]
 pub mod common;
 
+#[cfg(any(feature = "user", feature = "faction"))]
+pub mod attack_sync;
+
+#[cfg(all(feature = "torn", feature = "faction"))]
+pub mod territory;
+
+#[cfg(feature = "user")]
+pub mod discord;
+
+#[cfg(feature = "__common")]
+pub mod v1;
+
+#[cfg(feature = "__common")]
+pub mod v2;
+
 mod de_util;
 
-use std::fmt::Write;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, OnceLock},
+};
 
 use chrono::{DateTime, Utc};
-use serde::{de::Error as DeError, Deserialize};
+use serde::Deserialize;
 use thiserror::Error;
 
+pub use connection::{ConnectionOptions, HttpVersion};
+pub use ids::{CompanyId, CourseId, FactionId, ItemId, UserId};
 pub use into_owned::IntoOwned;
+pub use selection_set::SelectionSet;
+
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
 
+/// A successful response. Selections are kept as raw JSON and only parsed when they are first
+/// accessed, after which the parsed value is reused by later accesses.
 pub struct ApiResponse {
-    pub value: serde_json::Value,
+    source: ResponseSource,
+    value: OnceLock<serde_json::Value>,
+    hook: Option<Arc<dyn DeserializeHook>>,
+    strictness: Strictness,
+    buffers: Option<Arc<bulk::BufferPool>>,
+    /// The selections of the request, as a [`SelectionSet`] of the response's category.
+    requested: Option<u64>,
+}
+
+enum ResponseSource {
+    /// The body as received, with the boundaries of its top level fields.
+    Raw {
+        body: String,
+        fields: HashMap<String, LazyField>,
+    },
+    /// A response which was parsed already, e.g. by a caller of [`ApiResponse::from_value`].
+    Value(serde_json::Value),
+}
+
+/// How values which are only partially understood, like unknown enum variants, are handled when
+/// deserialising selections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Strictness {
+    /// Fall back to `Other` variants and similar, so that new values in the API don't break
+    /// existing code.
+    #[default]
+    Lenient,
+    /// Fail instead of falling back, e.g. to notice API changes when testing against fixtures.
+    Strict,
+}
+
+/// A version of the Torn API. Some selections have a different shape in each version, see the
+/// `v1` and `v2` modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+/// Customises how the fields of a response are turned into selections, e.g. to use a different
+/// JSON parser, to instrument deserialisation or to enrich deserialisation errors.
+pub trait DeserializeHook: Send + Sync {
+    /// Parse the raw JSON of a top level field, or of the whole body.
+    fn parse(&self, raw: &str) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_str(raw)
+    }
+
+    /// Wraps the typed deserialisation of every selection, which is performed by `decode`. With
+    /// the `path-to-error` feature, hooks can use `Decode::run_tracked` to find out which value
+    /// failed to deserialise.
+    fn deserialize(&self, selection: &'static str, decode: Decode<'_>) -> serde_json::Result<()> {
+        _ = selection;
+        decode.run()
+    }
+
+    /// Called with every selection which failed to deserialise, before it is returned.
+    fn on_error(&self, error: DeserializeError) -> DeserializeError {
+        error
+    }
+}
+
+impl std::fmt::Debug for dyn DeserializeHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DeserializeHook")
+    }
+}
+
+/// The typed deserialisation of a selection, see [`DeserializeHook::deserialize`].
+pub struct Decode<'a> {
+    value: &'a serde_json::Value,
+    run: &'a mut dyn FnMut(bool) -> serde_json::Result<()>,
+}
+
+impl Decode<'_> {
+    /// The JSON the selection is deserialised from.
+    pub fn value(&self) -> &serde_json::Value {
+        self.value
+    }
+
+    /// Deserialise the selection.
+    pub fn run(self) -> serde_json::Result<()> {
+        (self.run)(false)
+    }
+
+    /// Deserialise the selection, recording the path of the value which failed in
+    /// [`DeserializeError::path`].
+    #[cfg(feature = "path-to-error")]
+    pub fn run_tracked(self) -> serde_json::Result<()> {
+        (self.run)(true)
+    }
+}
+
+/// The deserializer selections are decoded from. With the `path-to-error` feature it records the
+/// path of the value which failed to deserialise.
+#[cfg(feature = "path-to-error")]
+type SelectionDeserializer<'t, 'de> =
+    serde_path_to_error::Deserializer<'t, 't, &'de serde_json::Value>;
+#[cfg(not(feature = "path-to-error"))]
+type SelectionDeserializer<'t, 'de> = &'de serde_json::Value;
+
+/// Run `fun` with the deserializer of `value`, returning the path of the failed value if `tracked`.
+#[cfg(feature = "path-to-error")]
+fn deserialize_selection<'de, V>(
+    value: &'de serde_json::Value,
+    tracked: bool,
+    fun: impl FnOnce(SelectionDeserializer<'_, 'de>) -> serde_json::Result<V>,
+) -> Result<V, (serde_json::Error, Option<String>)> {
+    let mut track = serde_path_to_error::Track::new();
+    fun(serde_path_to_error::Deserializer::new(value, &mut track))
+        .map_err(|source| (source, tracked.then(|| track.path().to_string())))
+}
+
+#[cfg(not(feature = "path-to-error"))]
+fn deserialize_selection<'de, V>(
+    value: &'de serde_json::Value,
+    _tracked: bool,
+    fun: impl FnOnce(SelectionDeserializer<'_, 'de>) -> serde_json::Result<V>,
+) -> Result<V, (serde_json::Error, Option<String>)> {
+    fun(value).map_err(|source| (source, None))
+}
+
+struct LazyField {
+    range: std::ops::Range<usize>,
+    value: OnceLock<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ApiErrorCode {
+    Unknown,
+    KeyEmpty,
+    IncorrectKey,
+    WrongType,
+    WrongFields,
+    TooManyRequests,
+    IncorrectId,
+    IncorrectIdEntityRelation,
+    IpBlock,
+    ApiDisabled,
+    KeyOwnerInFederalJail,
+    KeyChangeError,
+    KeyReadError,
+    KeyOwnerInactive,
+    DailyReadLimit,
+    TemporaryError,
+    AccessLevelTooLow,
+    BackendError,
+    KeyPaused,
+    CrimesMigrationRequired,
+    RaceNotFinished,
+    IncorrectCategory,
+    OnlyInV1,
+    OnlyInV2,
+    ClosedTemporarily,
+    /// A code which this version of the crate doesn't know yet.
+    Other(UnknownErrorCode),
+}
+
+/// The number of an [`ApiErrorCode::Other`]. It can only be created from codes which aren't
+/// known, so that each code has exactly one representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownErrorCode(u8);
+
+impl UnknownErrorCode {
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl ApiErrorCode {
+    /// The number Torn uses for the error.
+    pub fn code(self) -> u8 {
+        self.into()
+    }
+
+    /// The key won't work again without the owner doing something about it, e.g. because it was
+    /// deleted or the owner is in federal jail, so it should be removed from any key pool.
+    pub fn is_key_invalid(self) -> bool {
+        matches!(
+            self,
+            Self::IncorrectKey
+                | Self::KeyOwnerInFederalJail
+                | Self::KeyOwnerInactive
+                | Self::KeyPaused
+        )
+    }
+
+    pub fn is_key_error(self) -> bool {
+        matches!(
+            self,
+            Self::KeyEmpty
+                | Self::IncorrectKey
+                | Self::TooManyRequests
+                | Self::KeyOwnerInFederalJail
+                | Self::KeyChangeError
+                | Self::KeyReadError
+                | Self::KeyOwnerInactive
+                | Self::DailyReadLimit
+                | Self::AccessLevelTooLow
+                | Self::KeyPaused
+                | Self::CrimesMigrationRequired
+        )
+    }
+
+    pub fn is_request_error(self) -> bool {
+        matches!(
+            self,
+            Self::WrongType
+                | Self::WrongFields
+                | Self::IncorrectId
+                | Self::IncorrectIdEntityRelation
+                | Self::RaceNotFinished
+                | Self::IncorrectCategory
+                | Self::OnlyInV1
+                | Self::OnlyInV2
+        )
+    }
+
+    pub fn is_rate_limited(self) -> bool {
+        matches!(
+            self,
+            Self::TooManyRequests | Self::IpBlock | Self::DailyReadLimit
+        )
+    }
+
+    /// The same request may succeed when it is retried later, e.g. once the rate limit resets or
+    /// Torn is back up. Unknown errors are assumed to be temporary.
+    pub fn is_temporary(self) -> bool {
+        matches!(
+            self,
+            Self::Unknown
+                | Self::TooManyRequests
+                | Self::IpBlock
+                | Self::ApiDisabled
+                | Self::KeyChangeError
+                | Self::KeyReadError
+                | Self::DailyReadLimit
+                | Self::TemporaryError
+                | Self::BackendError
+                | Self::ClosedTemporarily
+        )
+    }
+}
+
+impl From<u8> for ApiErrorCode {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => Self::Unknown,
+            1 => Self::KeyEmpty,
+            2 => Self::IncorrectKey,
+            3 => Self::WrongType,
+            4 => Self::WrongFields,
+            5 => Self::TooManyRequests,
+            6 => Self::IncorrectId,
+            7 => Self::IncorrectIdEntityRelation,
+            8 => Self::IpBlock,
+            9 => Self::ApiDisabled,
+            10 => Self::KeyOwnerInFederalJail,
+            11 => Self::KeyChangeError,
+            12 => Self::KeyReadError,
+            13 => Self::KeyOwnerInactive,
+            14 => Self::DailyReadLimit,
+            15 => Self::TemporaryError,
+            16 => Self::AccessLevelTooLow,
+            17 => Self::BackendError,
+            18 => Self::KeyPaused,
+            19 => Self::CrimesMigrationRequired,
+            20 => Self::RaceNotFinished,
+            21 => Self::IncorrectCategory,
+            22 => Self::OnlyInV1,
+            23 => Self::OnlyInV2,
+            24 => Self::ClosedTemporarily,
+            other => Self::Other(UnknownErrorCode(other)),
+        }
+    }
+}
+
+impl From<ApiErrorCode> for u8 {
+    fn from(code: ApiErrorCode) -> Self {
+        match code {
+            ApiErrorCode::Unknown => 0,
+            ApiErrorCode::KeyEmpty => 1,
+            ApiErrorCode::IncorrectKey => 2,
+            ApiErrorCode::WrongType => 3,
+            ApiErrorCode::WrongFields => 4,
+            ApiErrorCode::TooManyRequests => 5,
+            ApiErrorCode::IncorrectId => 6,
+            ApiErrorCode::IncorrectIdEntityRelation => 7,
+            ApiErrorCode::IpBlock => 8,
+            ApiErrorCode::ApiDisabled => 9,
+            ApiErrorCode::KeyOwnerInFederalJail => 10,
+            ApiErrorCode::KeyChangeError => 11,
+            ApiErrorCode::KeyReadError => 12,
+            ApiErrorCode::KeyOwnerInactive => 13,
+            ApiErrorCode::DailyReadLimit => 14,
+            ApiErrorCode::TemporaryError => 15,
+            ApiErrorCode::AccessLevelTooLow => 16,
+            ApiErrorCode::BackendError => 17,
+            ApiErrorCode::KeyPaused => 18,
+            ApiErrorCode::CrimesMigrationRequired => 19,
+            ApiErrorCode::RaceNotFinished => 20,
+            ApiErrorCode::IncorrectCategory => 21,
+            ApiErrorCode::OnlyInV1 => 22,
+            ApiErrorCode::OnlyInV2 => 23,
+            ApiErrorCode::ClosedTemporarily => 24,
+            ApiErrorCode::Other(other) => other.get(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum ResponseError {
-    #[error("API: {reason}")]
-    Api { code: u8, reason: String },
+    #[error("Key error {code}: {reason}")]
+    Key { code: ApiErrorCode, reason: String },
+
+    #[error("Request error {code}: {reason}")]
+    Request { code: ApiErrorCode, reason: String },
+
+    #[error("API error {code}: {reason}")]
+    Service { code: ApiErrorCode, reason: String },
 
     #[error(transparent)]
     MalformedResponse(#[from] serde_json::Error),
+
+    #[error("Received non-JSON response with status {status} and content type {content_type:?}")]
+    NonJson {
+        status: u16,
+        content_type: Option<String>,
+    },
 }
 
 impl ResponseError {
-    pub fn api_code(&self) -> Option<u8> {
+    pub fn from_code(code: ApiErrorCode, reason: String) -> Self {
+        if code.is_key_error() {
+            Self::Key { code, reason }
+        } else if code.is_request_error() {
+            Self::Request { code, reason }
+        } else {
+            Self::Service { code, reason }
+        }
+    }
+
+    pub fn api_code(&self) -> Option<ApiErrorCode> {
         match self {
-            Self::Api { code, .. } => Some(*code),
+            Self::Key { code, .. } | Self::Request { code, .. } | Self::Service { code, .. } => {
+                Some(*code)
+            }
             _ => None,
         }
     }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Key { reason, .. }
+            | Self::Request { reason, .. }
+            | Self::Service { reason, .. } => Some(reason),
+            _ => None,
+        }
+    }
+
+    pub fn is_key_error(&self) -> bool {
+        matches!(self, Self::Key { .. })
+    }
+
+    pub fn is_request_error(&self) -> bool {
+        matches!(self, Self::Request { .. })
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiErrorDto {
+    code: u8,
+    #[serde(rename = "error")]
+    reason: String,
+}
+
+impl Drop for ApiResponse {
+    fn drop(&mut self) {
+        if let (Some(buffers), ResponseSource::Raw { body, .. }) = (&self.buffers, &mut self.source)
+        {
+            buffers.put(std::mem::take(body).into_bytes());
+        }
+    }
 }
 
 impl ApiResponse {
-    pub fn from_value(mut value: serde_json::Value) -> Result<Self, ResponseError> {
-        #[derive(serde::Deserialize)]
-        struct ApiErrorDto {
-            code: u8,
-            #[serde(rename = "error")]
-            reason: String,
-        }
-        match value.get_mut("error") {
-            Some(error) => {
-                let dto: ApiErrorDto = serde_json::from_value(error.take())?;
-                Err(ResponseError::Api {
-                    code: dto.code,
-                    reason: dto.reason,
-                })
+    /// Takes over the body of `raw` without copying it.
+    pub fn from_raw(raw: RawResponse) -> Result<Self, ResponseError> {
+        Self::check_json(&raw)?;
+        let body =
+            String::from_utf8(raw.body).map_err(<serde_json::Error as serde::de::Error>::custom)?;
+        Self::from_body(body)
+    }
+
+    /// Like [`ApiResponse::from_raw`], but the body is handed back to `buffers` once the response
+    /// is dropped, or right away if it isn't a valid response.
+    pub fn from_raw_pooled(
+        raw: RawResponse,
+        buffers: &Arc<bulk::BufferPool>,
+    ) -> Result<Self, ResponseError> {
+        if let Err(why) = Self::check_json(&raw) {
+            buffers.put(raw.body);
+            return Err(why);
+        }
+        let body = match String::from_utf8(raw.body) {
+            Ok(body) => body,
+            Err(why) => {
+                let source = <serde_json::Error as serde::de::Error>::custom(why.utf8_error());
+                buffers.put(why.into_bytes());
+                return Err(source.into());
             }
-            None => Ok(Self { value }),
+        };
+
+        let fields = match Self::split_fields(&body) {
+            Ok(fields) => fields,
+            Err(why) => {
+                buffers.put(body.into_bytes());
+                return Err(why);
+            }
+        };
+
+        let mut response = Self::with_source(ResponseSource::Raw { body, fields });
+        response.buffers = Some(buffers.clone());
+        Ok(response)
+    }
+
+    fn check_json(raw: &RawResponse) -> Result<(), ResponseError> {
+        let json_content = raw
+            .content_type
+            .as_deref()
+            .is_none_or(|c| c.contains("json"));
+        let json_body = matches!(
+            raw.body.iter().find(|b| !b.is_ascii_whitespace()),
+            Some(b'{' | b'[')
+        );
+
+        if !json_content || !json_body {
+            return Err(ResponseError::NonJson {
+                status: raw.status,
+                content_type: raw.content_type.clone(),
+            });
         }
+
+        Ok(())
+    }
+
+    /// A response which was parsed already. Its selections are deserialised from `value`
+    /// directly.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, ResponseError> {
+        if let Some(error) = value.get("error") {
+            let dto = ApiErrorDto::deserialize(error)?;
+            return Err(ResponseError::from_code(dto.code.into(), dto.reason));
+        }
+
+        Ok(Self::with_source(ResponseSource::Value(value)))
+    }
+
+    fn from_body(body: String) -> Result<Self, ResponseError> {
+        let fields = Self::split_fields(&body)?;
+        Ok(Self::with_source(ResponseSource::Raw { body, fields }))
+    }
+
+    fn with_source(source: ResponseSource) -> Self {
+        Self {
+            source,
+            value: OnceLock::new(),
+            hook: None,
+            strictness: Strictness::default(),
+            buffers: None,
+            requested: None,
+        }
+    }
+
+    // only the boundaries of the top level fields are determined here, the values themselves are
+    // parsed on first access
+    fn split_fields(body: &str) -> Result<HashMap<String, LazyField>, ResponseError> {
+        if !body.trim_start().starts_with('{') {
+            serde_json::from_str::<serde::de::IgnoredAny>(body)?;
+            return Ok(HashMap::new());
+        }
+
+        let raw: HashMap<&str, &serde_json::value::RawValue> = serde_json::from_str(body)?;
+        if let Some(error) = raw.get("error") {
+            let dto: ApiErrorDto = serde_json::from_str(error.get())?;
+            return Err(ResponseError::from_code(dto.code.into(), dto.reason));
+        }
+
+        Ok(raw
+            .into_iter()
+            .map(|(name, value)| {
+                let start = value.get().as_ptr() as usize - body.as_ptr() as usize;
+                let field = LazyField {
+                    range: start..start + value.get().len(),
+                    value: OnceLock::new(),
+                };
+                (name.to_owned(), field)
+            })
+            .collect())
+    }
+
+    /// The whole response, parsing the body if it wasn't parsed yet. Replaces the former public
+    /// `value` field.
+    pub fn into_value(mut self) -> serde_json::Result<serde_json::Value> {
+        self.value()?;
+        Ok(match (&mut self.source, self.value.take()) {
+            (ResponseSource::Value(value), _) => std::mem::take(value),
+            (ResponseSource::Raw { .. }, value) => value.unwrap_or_default(),
+        })
+    }
+
+    /// Use `hook` for all selections which haven't been accessed yet.
+    pub fn with_hook(mut self, hook: Arc<dyn DeserializeHook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// Deserialise all selections which haven't been accessed yet with `strictness`.
+    pub fn with_strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Remember which selections were requested, so converting the response into its owned form
+    /// can tell selections which failed to decode from ones which weren't requested. The
+    /// providers do this for every response they return.
+    pub fn with_selections<A>(mut self, selections: SelectionSet<A>) -> Self
+    where
+        A: ApiSelection,
+    {
+        self.requested = Some(selections.bits());
+        self
     }
 
+    /// Whether `selection` was requested, if known.
     #[allow(dead_code)]
-    fn decode<'de, D>(&'de self) -> serde_json::Result<D>
+    fn was_requested<A>(&self, selection: A) -> Option<bool>
+    where
+        A: ApiSelection,
+    {
+        self.requested
+            .map(|bits| SelectionSet::<A>::from_bits(bits).contains(selection))
+    }
+
+    fn parse_raw(&self, raw: &str) -> serde_json::Result<serde_json::Value> {
+        match &self.hook {
+            Some(hook) => hook.parse(raw),
+            None => serde_json::from_str(raw),
+        }
+    }
+
+    fn deserialize_error(&self, error: DeserializeError) -> SelectionError {
+        match &self.hook {
+            Some(hook) => hook.on_error(error).into(),
+            None => error.into(),
+        }
+    }
+
+    /// Deserialise `value` with `fun`, through the hook if there is one.
+    fn run_decode<'de, V, F>(
+        &self,
+        selection: &'static str,
+        type_name: &'static str,
+        value: &'de serde_json::Value,
+        fun: F,
+    ) -> Result<V, SelectionError>
+    where
+        F: FnOnce(SelectionDeserializer<'_, 'de>) -> serde_json::Result<V>,
+    {
+        let mut fun = Some(fun);
+        let result = de_util::with_strictness(self.strictness, || {
+            decode_hooked(self.hook.as_deref(), selection, value, |tracked| {
+                let fun = fun.take().expect("selections are only decoded once");
+                deserialize_selection(value, tracked, fun)
+            })
+        });
+        result.map_err(|(source, path)| {
+            let error = DeserializeError::new(selection, type_name, Some(value), source).at(path);
+            self.deserialize_error(error)
+        })
+    }
+
+    /// The time at which Torn generated the response, if the `timestamp` selection was
+    /// requested. Torn caches some selections, so this can be a while ago.
+    pub fn server_time(&self) -> Option<DateTime<Utc>> {
+        let value = self.field_value("timestamp")?.ok()?;
+        de_util::unix_timestamp(value).ok()
+    }
+
+    /// Time since Torn generated the response, see [`ApiResponse::server_time`].
+    pub fn age(&self) -> Option<chrono::Duration> {
+        self.server_time().map(|time| Utc::now() - time)
+    }
+
+    /// The raw JSON of the whole response, unless it was created from an already parsed value.
+    pub fn raw(&self) -> Option<&str> {
+        match &self.source {
+            ResponseSource::Raw { body, .. } => Some(body),
+            ResponseSource::Value(_) => None,
+        }
+    }
+
+    /// The whole response, parsed on first access. Only fails if the [`DeserializeHook`] can't
+    /// parse the body.
+    pub fn value(&self) -> serde_json::Result<&serde_json::Value> {
+        match &self.source {
+            ResponseSource::Raw { body, .. } => {
+                if let Some(value) = self.value.get() {
+                    return Ok(value);
+                }
+                let value = self.parse_raw(body)?;
+                Ok(self.value.get_or_init(|| value))
+            }
+            ResponseSource::Value(value) => Ok(value),
+        }
+    }
+
+    fn field_value(&self, field: &str) -> Option<serde_json::Result<&serde_json::Value>> {
+        let (body, lazy) = match &self.source {
+            ResponseSource::Raw { body, fields } => (body, fields.get(field)?),
+            ResponseSource::Value(value) => return value.get(field).map(Ok),
+        };
+        if let Some(value) = lazy.value.get() {
+            return Some(Ok(value));
+        }
+
+        Some(
+            self.parse_raw(&body[lazy.range.clone()])
+                .map(|value| lazy.value.get_or_init(|| value)),
+        )
+    }
+
+    #[allow(dead_code)]
+    fn has_field(&self, field: &str) -> bool {
+        match &self.source {
+            ResponseSource::Raw { fields, .. } => fields.contains_key(field),
+            ResponseSource::Value(value) => value.get(field).is_some(),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn decode<'de, D>(
+        &'de self,
+        selection: &'static str,
+        type_name: &'static str,
+    ) -> Result<D, SelectionError>
     where
         D: Deserialize<'de>,
     {
-        D::deserialize(&self.value)
+        match self.value() {
+            Ok(value) => self.run_decode(selection, type_name, value, |de| D::deserialize(de)),
+            Err(source) => {
+                Err(self
+                    .deserialize_error(DeserializeError::new(selection, type_name, None, source)))
+            }
+        }
     }
 
     #[allow(dead_code)]
-    fn decode_field<'de, D>(&'de self, field: &'static str) -> serde_json::Result<D>
+    fn decode_field<'de, D>(
+        &'de self,
+        selection: &'static str,
+        type_name: &'static str,
+        field: &'static str,
+    ) -> Result<D, SelectionError>
     where
         D: Deserialize<'de>,
     {
-        self.value
-            .get(field)
-            .ok_or_else(|| serde_json::Error::missing_field(field))
-            .and_then(D::deserialize)
+        self.decode_field_with(selection, type_name, field, |de| D::deserialize(de))
     }
 
     #[allow(dead_code)]
-    fn decode_field_with<'de, V, F>(&'de self, field: &'static str, fun: F) -> serde_json::Result<V>
+    fn decode_versioned<'de, D>(
+        &'de self,
+        selection: &'static str,
+        type_name: &'static str,
+        field: &'static str,
+        version: ApiVersion,
+    ) -> Result<D, SelectionError>
     where
-        F: FnOnce(&'de serde_json::Value) -> serde_json::Result<V>,
+        D: Deserialize<'de>,
     {
-        self.value
-            .get(field)
-            .ok_or_else(|| serde_json::Error::missing_field(field))
-            .and_then(fun)
+        self.decode_versioned_with(selection, type_name, field, version, |de| {
+            D::deserialize(de)
+        })
+    }
+
+    #[allow(dead_code)]
+    fn decode_field_with<'de, V, F>(
+        &'de self,
+        selection: &'static str,
+        type_name: &'static str,
+        field: &'static str,
+        fun: F,
+    ) -> Result<V, SelectionError>
+    where
+        F: FnOnce(SelectionDeserializer<'_, 'de>) -> serde_json::Result<V>,
+    {
+        self.decode_versioned_with(selection, type_name, field, ApiVersion::V1, fun)
+    }
+
+    /// Like [`Self::decode_field_with`], for models of the given version of the API.
+    #[allow(dead_code)]
+    fn decode_versioned_with<'de, V, F>(
+        &'de self,
+        selection: &'static str,
+        type_name: &'static str,
+        field: &'static str,
+        version: ApiVersion,
+        fun: F,
+    ) -> Result<V, SelectionError>
+    where
+        F: FnOnce(SelectionDeserializer<'_, 'de>) -> serde_json::Result<V>,
+    {
+        self.check_version(selection, field, version)?;
+        match self.field_value(field) {
+            Some(Ok(value)) => self.run_decode(selection, type_name, value, fun),
+            Some(Err(source)) => {
+                Err(self
+                    .deserialize_error(DeserializeError::new(selection, type_name, None, source)))
+            }
+            None => Err(SelectionError::Missing { selection }),
+        }
+    }
+
+    /// Guess which version of the API returned `field`, based on the shape of the fields which
+    /// differ between the versions. `None` if the response doesn't tell.
+    pub fn field_version(&self, field: &str) -> Option<ApiVersion> {
+        use serde_json::Value;
+
+        let value = self.field_value(field)?.ok()?;
+        match (field, value) {
+            // v1 sends empty collections as `[]` too
+            ("attacks" | "members", Value::Array(items)) if !items.is_empty() => {
+                Some(ApiVersion::V2)
+            }
+            ("attacks" | "members", Value::Object(_)) => Some(ApiVersion::V1),
+            ("personalstats", Value::Object(map)) if map.values().any(Value::is_object) => {
+                Some(ApiVersion::V2)
+            }
+            ("personalstats", Value::Object(_)) => Some(ApiVersion::V1),
+            _ if self.has_field("_metadata") => Some(ApiVersion::V2),
+            _ => None,
+        }
+    }
+
+    fn check_version(
+        &self,
+        selection: &'static str,
+        field: &str,
+        expected: ApiVersion,
+    ) -> Result<(), SelectionError> {
+        match self.field_version(field) {
+            Some(found) if found != expected => Err(SelectionError::Version {
+                selection,
+                expected,
+                found,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Run `decode` through `hook`. Errors come with the path of the value which failed, if the hook
+/// asked for it.
+fn decode_hooked<V>(
+    hook: Option<&dyn DeserializeHook>,
+    selection: &'static str,
+    value: &serde_json::Value,
+    mut decode: impl FnMut(bool) -> Result<V, (serde_json::Error, Option<String>)>,
+) -> Result<V, (serde_json::Error, Option<String>)> {
+    let mut decoded = None;
+    let mut path = None;
+    let mut run = |tracked: bool| match decode(tracked) {
+        Ok(value) => {
+            decoded = Some(value);
+            Ok(())
+        }
+        Err((source, at)) => {
+            path = at;
+            Err(source)
+        }
+    };
+
+    let result = match hook {
+        Some(hook) => hook.deserialize(
+            selection,
+            Decode {
+                value,
+                run: &mut run,
+            },
+        ),
+        None => run(false),
+    };
+    match (result, decoded) {
+        (Ok(()), Some(decoded)) => Ok(decoded),
+        (Ok(()), None) => Err((
+            <serde_json::Error as serde::de::Error>::custom(
+                "the deserialize hook didn't deserialise the selection",
+            ),
+            None,
+        )),
+        (Err(source), _) => Err((source, path)),
     }
 }
 
-pub trait ApiSelection: Send + Sync {
+#[cfg(feature = "rayon")]
+impl ApiResponse {
+    /// Deserializes the entries of a map selection on the rayon thread pool, including parsing
+    /// the raw JSON of the field. The calling task only copies the field's JSON and then waits
+    /// for the result without blocking its thread, so large payloads don't stall the executor.
+    /// Entries are decoded with `entry`, which is handed the raw key and the value of an entry.
+    #[allow(dead_code)]
+    async fn decode_field_par<K, V, M, F>(
+        &self,
+        selection: &'static str,
+        type_name: &'static str,
+        field: &'static str,
+        entry: F,
+    ) -> Result<M, SelectionError>
+    where
+        K: Send,
+        V: Send,
+        M: rayon::iter::FromParallelIterator<(K, V)> + Default + Send + 'static,
+        F: Fn(&str, SelectionDeserializer<'_, '_>) -> serde_json::Result<(K, V)>
+            + Send
+            + Sync
+            + 'static,
+    {
+        enum Input {
+            Raw(String),
+            Value(serde_json::Value),
+        }
+
+        self.check_version(selection, field, ApiVersion::V1)?;
+        let input = match &self.source {
+            ResponseSource::Raw { body, fields } => match fields.get(field) {
+                Some(lazy) => match lazy.value.get() {
+                    Some(value) => Input::Value(value.clone()),
+                    None => Input::Raw(body[lazy.range.clone()].to_owned()),
+                },
+                None => return Err(SelectionError::Missing { selection }),
+            },
+            ResponseSource::Value(value) => match value.get(field) {
+                Some(value) => Input::Value(value.clone()),
+                None => return Err(SelectionError::Missing { selection }),
+            },
+        };
+
+        let hook = self.hook.clone();
+        let strictness = self.strictness;
+        let (tx, rx) = futures::channel::oneshot::channel();
+        rayon::spawn(move || {
+            let value = match input {
+                Input::Value(value) => value,
+                Input::Raw(raw) => {
+                    let parsed = match &hook {
+                        Some(hook) => hook.parse(&raw),
+                        None => serde_json::from_str(&raw),
+                    };
+                    match parsed {
+                        Ok(value) => value,
+                        Err(source) => {
+                            let error =
+                                DeserializeError::with_raw(selection, type_name, Some(raw), source);
+                            _ = tx.send(Err(error));
+                            return;
+                        }
+                    }
+                }
+            };
+
+            let result = decode_hooked(hook.as_deref(), selection, &value, |tracked| {
+                decode_entries_par(&value, strictness, tracked, &entry)
+            });
+            _ = tx.send(result.map_err(|(source, path)| {
+                DeserializeError::new(selection, type_name, Some(&value), source).at(path)
+            }));
+        });
+
+        let result = rx
+            .await
+            .unwrap_or_else(|_| panic!("decoding the '{field}' field panicked"));
+        result.map_err(|error| match &self.hook {
+            Some(hook) => hook.on_error(error).into(),
+            None => error.into(),
+        })
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn decode_entries_par<K, V, M, F>(
+    value: &serde_json::Value,
+    strictness: Strictness,
+    tracked: bool,
+    entry: &F,
+) -> Result<M, (serde_json::Error, Option<String>)>
+where
+    K: Send,
+    V: Send,
+    M: rayon::iter::FromParallelIterator<(K, V)> + Default,
+    F: Fn(&str, SelectionDeserializer<'_, '_>) -> serde_json::Result<(K, V)> + Sync,
+{
+    use rayon::prelude::*;
+
+    let entries: Vec<_> = match value {
+        serde_json::Value::Object(map) => map.iter().collect(),
+        serde_json::Value::Null => return Ok(M::default()),
+        serde_json::Value::Array(items) if items.is_empty() => return Ok(M::default()),
+        _ => {
+            let source = <serde_json::Error as serde::de::Error>::invalid_type(
+                serde::de::Unexpected::Other("non-object value"),
+                &"map",
+            );
+            return Err((source, None));
+        }
+    };
+
+    entries
+        .into_par_iter()
+        .with_min_len(64)
+        .map(|(key, value)| {
+            de_util::with_strictness(strictness, || {
+                deserialize_selection(value, tracked, |de| entry(key, de))
+            })
+            .map_err(|(source, path)| {
+                // the path is relative to the entry
+                let path = path.map(|path| match path.as_str() {
+                    "." => key.clone(),
+                    _ if path.starts_with('[') => format!("{key}{path}"),
+                    _ => format!("{key}.{path}"),
+                });
+                (source, path)
+            })
+        })
+        .collect()
+}
+
+const RAW_BODY_LIMIT: usize = 4096;
+
+#[derive(Error, Debug)]
+pub enum SelectionError {
+    #[error("Selection '{selection}' is missing from the response, was it requested?")]
+    Missing { selection: &'static str },
+
+    #[error(transparent)]
+    Malformed(#[from] DeserializeError),
+
+    #[error(
+        "Selection '{selection}' was returned by API {found:?} but its model is for API {expected:?}"
+    )]
+    Version {
+        selection: &'static str,
+        expected: ApiVersion,
+        found: ApiVersion,
+    },
+}
+
+impl SelectionError {
+    pub fn selection(&self) -> &'static str {
+        match self {
+            Self::Missing { selection } => selection,
+            Self::Malformed(err) => err.selection,
+            Self::Version { selection, .. } => selection,
+        }
+    }
+
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing { .. })
+    }
+}
+
+#[derive(Error, Debug)]
+#[error(
+    "Failed to deserialize selection '{selection}' into '{type_name}'{}: {source}",
+    .path.as_ref().map(|path| format!(" at '{path}'")).unwrap_or_default()
+)]
+pub struct DeserializeError {
+    pub selection: &'static str,
+    pub type_name: &'static str,
+    pub raw: Option<String>,
+    /// The path of the value which failed within the selection, if the [`DeserializeHook`]
+    /// asked for it with `Decode::run_tracked`, see the `path-to-error` feature.
+    pub path: Option<String>,
+    #[source]
+    pub source: serde_json::Error,
+}
+
+impl DeserializeError {
+    fn new(
+        selection: &'static str,
+        type_name: &'static str,
+        value: Option<&serde_json::Value>,
+        source: serde_json::Error,
+    ) -> Self {
+        Self::with_raw(selection, type_name, value.map(ToString::to_string), source)
+    }
+
+    fn with_raw(
+        selection: &'static str,
+        type_name: &'static str,
+        raw: Option<String>,
+        source: serde_json::Error,
+    ) -> Self {
+        let raw = raw.map(|mut raw| {
+            if raw.len() > RAW_BODY_LIMIT {
+                let mut end = RAW_BODY_LIMIT;
+                while !raw.is_char_boundary(end) {
+                    end -= 1;
+                }
+                raw.truncate(end);
+                raw.push_str("...");
+            }
+            raw
+        });
+
+        Self {
+            selection,
+            type_name,
+            raw,
+            path: None,
+            source,
+        }
+    }
+
+    fn at(mut self, path: Option<String>) -> Self {
+        self.path = path;
+        self
+    }
+}
+
+pub trait ApiSelection: Send + Sync + Copy + 'static {
     fn raw_value(self) -> &'static str;
 
     fn category() -> &'static str;
+
+    fn index(self) -> usize;
+
+    fn all() -> &'static [Self];
+
+    fn requires_id(self) -> bool {
+        false
+    }
+
+    fn conflicts_with(self, _other: Self) -> bool {
+        false
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RequestValidationError {
+    #[error("Selection '{selection}' requires an id")]
+    MissingId { selection: &'static str },
+
+    #[error("Selections '{first}' and '{second}' can't be requested together")]
+    Conflict {
+        first: &'static str,
+        second: &'static str,
+    },
+
+    #[error("Invalid time range: from={from} is after to={to}")]
+    InvalidTimeRange { from: i64, to: i64 },
 }
 
 pub trait ApiCategoryResponse: Send + Sync {
@@ -125,6 +1195,9 @@ pub trait ApiCategoryResponse: Send + Sync {
 
 pub struct DirectExecutor<C> {
     key: String,
+    buffers: Arc<bulk::BufferPool>,
+    hook: Option<Arc<dyn DeserializeHook>>,
+    strictness: Strictness,
     _marker: std::marker::PhantomData<C>,
 }
 
@@ -132,9 +1205,21 @@ impl<C> DirectExecutor<C> {
     fn new(key: String) -> Self {
         Self {
             key,
+            buffers: Default::default(),
+            hook: None,
+            strictness: Strictness::default(),
             _marker: Default::default(),
         }
     }
+
+    fn parse(&self, raw: RawResponse) -> Result<ApiResponse, ResponseError> {
+        let response =
+            ApiResponse::from_raw_pooled(raw, &self.buffers)?.with_strictness(self.strictness);
+        Ok(match &self.hook {
+            Some(hook) => response.with_hook(hook.clone()),
+            None => response,
+        })
+    }
 }
 
 #[derive(Error, Debug)]
@@ -147,42 +1232,138 @@ where
 
     #[error(transparent)]
     Response(#[from] ResponseError),
+
+    #[error(transparent)]
+    Validation(#[from] RequestValidationError),
 }
 
 impl<C> ApiClientError<C>
 where
     C: std::error::Error,
 {
-    pub fn api_code(&self) -> Option<u8> {
+    pub fn api_code(&self) -> Option<ApiErrorCode> {
         match self {
             Self::Response(err) => err.api_code(),
             _ => None,
         }
     }
+
+    pub fn is_key_error(&self) -> bool {
+        matches!(self, Self::Response(err) if err.is_key_error())
+    }
+
+    pub fn is_request_error(&self) -> bool {
+        matches!(self, Self::Response(err) if err.is_request_error())
+            || matches!(self, Self::Validation(_))
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ApiRequest<A>
 where
     A: ApiSelection,
 {
-    pub selections: Vec<&'static str>,
+    pub selections: SelectionSet<A>,
     pub from: Option<i64>,
     pub to: Option<i64>,
+    pub timestamp: Option<i64>,
     pub comment: Option<String>,
+    /// Also request the `timestamp` selection, see [`ApiResponse::server_time`].
+    pub server_time: bool,
+    // shared between copies of a request, so templates only render their query once
+    query: std::sync::Arc<OnceLock<CachedQuery<A>>>,
     phantom: std::marker::PhantomData<A>,
 }
 
+#[derive(Debug)]
+struct CachedQuery<A>
+where
+    A: ApiSelection,
+{
+    selections: SelectionSet<A>,
+    from: Option<i64>,
+    to: Option<i64>,
+    timestamp: Option<i64>,
+    comment: Option<String>,
+    server_time: bool,
+    // everything between the id and the key
+    head: String,
+    // everything after the key
+    tail: String,
+}
+
+impl<A> CachedQuery<A>
+where
+    A: ApiSelection,
+{
+    fn render(request: &ApiRequest<A>) -> Self {
+        let mut selections = request.selections.query_value();
+        if request.server_time
+            && !request
+                .selections
+                .iter()
+                .any(|s| s.raw_value() == "timestamp")
+        {
+            if !selections.is_empty() {
+                selections.push(',');
+            }
+            selections.push_str("timestamp");
+        }
+        let head = format!("?selections={}&key=", selections);
+
+        let mut tail = String::new();
+        if let Some(from) = request.from {
+            write!(tail, "&from={}", from).unwrap();
+        }
+
+        if let Some(to) = request.to {
+            write!(tail, "&to={}", to).unwrap();
+        }
+
+        if let Some(timestamp) = request.timestamp {
+            write!(tail, "&timestamp={}", timestamp).unwrap();
+        }
+
+        if let Some(comment) = &request.comment {
+            write!(tail, "&comment={}", comment).unwrap();
+        }
+
+        Self {
+            selections: request.selections,
+            from: request.from,
+            to: request.to,
+            timestamp: request.timestamp,
+            comment: request.comment.clone(),
+            server_time: request.server_time,
+            head,
+            tail,
+        }
+    }
+
+    // the public fields of a request may have been changed after the query was rendered
+    fn is_current(&self, request: &ApiRequest<A>) -> bool {
+        self.selections == request.selections
+            && self.from == request.from
+            && self.to == request.to
+            && self.timestamp == request.timestamp
+            && self.comment == request.comment
+            && self.server_time == request.server_time
+    }
+}
+
 impl<A> std::default::Default for ApiRequest<A>
 where
     A: ApiSelection,
 {
     fn default() -> Self {
         Self {
-            selections: Vec::default(),
+            selections: SelectionSet::new(),
             from: None,
             to: None,
+            timestamp: None,
             comment: None,
+            server_time: false,
+            query: Default::default(),
             phantom: Default::default(),
         }
     }
@@ -193,36 +1374,78 @@ where
     A: ApiSelection,
 {
     pub fn url(&self, key: &str, id: Option<&str>) -> String {
-        let mut url = format!("https://api.torn.com/{}/", A::category());
+        let rendered;
+        let query = match self.query.get() {
+            Some(query) if query.is_current(self) => query,
+            Some(_) => {
+                rendered = CachedQuery::render(self);
+                &rendered
+            }
+            None => self.query.get_or_init(|| CachedQuery::render(self)),
+        };
 
-        if let Some(id) = id {
-            write!(url, "{}", id).unwrap();
-        }
+        const BASE: &str = "https://api.torn.com/";
+        let category = A::category();
+        let id = id.unwrap_or_default();
+        let mut url = String::with_capacity(
+            BASE.len()
+                + category.len()
+                + 1
+                + id.len()
+                + query.head.len()
+                + key.len()
+                + query.tail.len(),
+        );
+        url.push_str(BASE);
+        url.push_str(category);
+        url.push('/');
+        url.push_str(id);
+        url.push_str(&query.head);
+        url.push_str(key);
+        url.push_str(&query.tail);
 
-        write!(url, "?selections={}&key={}", self.selections.join(","), key).unwrap();
+        url
+    }
 
-        if let Some(from) = self.from {
-            write!(url, "&from={}", from).unwrap();
+    pub fn validate(&self, with_id: bool) -> Result<(), RequestValidationError> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(RequestValidationError::InvalidTimeRange { from, to });
+            }
         }
 
-        if let Some(to) = self.to {
-            write!(url, "&to={}", to).unwrap();
-        }
+        for selection in self.selections.iter() {
+            if !with_id && selection.requires_id() {
+                return Err(RequestValidationError::MissingId {
+                    selection: selection.raw_value(),
+                });
+            }
 
-        if let Some(comment) = &self.comment {
-            write!(url, "&comment={}", comment).unwrap();
+            if let Some(other) = self
+                .selections
+                .iter()
+                .filter(|other| other.index() > selection.index())
+                .find(|other| selection.conflicts_with(*other))
+            {
+                return Err(RequestValidationError::Conflict {
+                    first: selection.raw_value(),
+                    second: other.raw_value(),
+                });
+            }
         }
 
-        url
+        Ok(())
     }
 }
 
+#[derive(Clone)]
 pub struct ApiRequestBuilder<A>
 where
     A: ApiSelection,
 {
     request: ApiRequest<A>,
     id: Option<String>,
+    retries: usize,
 }
 
 impl<A> Default for ApiRequestBuilder<A>
@@ -233,6 +1456,7 @@ where
         Self {
             request: Default::default(),
             id: None,
+            retries: 0,
         }
     }
 }
@@ -241,14 +1465,22 @@ impl<A> ApiRequestBuilder<A>
 where
     A: ApiSelection,
 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy of this builder targeting `id`, so a configured builder can be reused as a template.
+    #[must_use]
+    pub fn with_id<I>(&self, id: I) -> Self
+    where
+        I: ToString,
+    {
+        self.clone().id(id)
+    }
+
     #[must_use]
     pub fn selections(mut self, selections: impl IntoIterator<Item = A>) -> Self {
-        self.request.selections.append(
-            &mut selections
-                .into_iter()
-                .map(ApiSelection::raw_value)
-                .collect(),
-        );
+        self.request.selections.extend(selections);
         self
     }
 
@@ -276,12 +1508,40 @@ where
         self
     }
 
+    /// Request historical data as of `at`, where the selection supports it (e.g. personalstats).
+    #[must_use]
+    pub fn at(mut self, at: DateTime<Utc>) -> Self {
+        self.request.timestamp = Some(at.timestamp());
+        self
+    }
+
+    #[must_use]
+    pub fn at_timestamp(mut self, timestamp: i64) -> Self {
+        self.request.timestamp = Some(timestamp);
+        self
+    }
+
     #[must_use]
     pub fn comment(mut self, comment: String) -> Self {
         self.request.comment = Some(comment);
         self
     }
 
+    /// Also request the server time, so the age of the response can be checked.
+    #[must_use]
+    pub fn server_time(mut self) -> Self {
+        self.request.server_time = true;
+        self
+    }
+
+    /// Bulk requests only: re-request IDs that failed with an error which retrying right away
+    /// may fix, like a backend error, up to `retries` times.
+    #[must_use]
+    pub fn retry_failed(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
     #[must_use]
     pub fn id<I>(mut self, id: I) -> Self
     where
@@ -329,6 +1589,406 @@ pub(crate) mod tests {
         assert_eq!(user::Selection::Basic.raw_value(), "basic");
     }
 
+    #[cfg(feature = "rayon")]
+    #[tokio::test]
+    async fn parallel_map_decode() {
+        fn entry<K, V>(
+            key: &str,
+            value: SelectionDeserializer<'_, '_>,
+        ) -> serde_json::Result<(K, V)>
+        where
+            K: serde::de::DeserializeOwned,
+            V: serde::de::DeserializeOwned,
+        {
+            Ok((
+                K::deserialize(de_util::MapKeyDeserializer(key))?,
+                V::deserialize(value)?,
+            ))
+        }
+
+        let items: serde_json::Map<_, _> = (1..1000)
+            .map(|id| (id.to_string(), serde_json::json!(id * 2)))
+            .collect();
+        let value = serde_json::json!({ "items": items, "empty": null });
+        let parsed = ApiResponse::from_value(value.clone()).unwrap();
+        let raw = ApiResponse::from_body(value.to_string()).unwrap();
+
+        for response in [parsed, raw] {
+            let parallel: std::collections::BTreeMap<ItemId, i32> = response
+                .decode_field_par("items", "BTreeMap<ItemId, i32>", "items", entry)
+                .await
+                .unwrap();
+            let sequential: std::collections::BTreeMap<ItemId, i32> = response
+                .decode_field("items", "BTreeMap<ItemId, i32>", "items")
+                .unwrap();
+            assert_eq!(parallel, sequential);
+            assert_eq!(parallel[&ItemId(500)], 1000);
+
+            let empty: std::collections::HashMap<String, i32> = response
+                .decode_field_par("empty", "", "empty", entry)
+                .await
+                .unwrap();
+            assert!(empty.is_empty());
+            assert!(response
+                .decode_field_par::<String, i32, std::collections::HashMap<_, _>, _>(
+                    "missing", "", "missing", entry
+                )
+                .await
+                .unwrap_err()
+                .is_missing());
+        }
+    }
+
+    #[cfg(all(feature = "user", feature = "torn"))]
+    #[test]
+    fn request_validation() {
+        let request = ApiRequestBuilder::default()
+            .selections([user::Selection::Attacks, user::Selection::AttacksFull])
+            .request;
+        assert_eq!(
+            request.validate(false),
+            Err(RequestValidationError::Conflict {
+                first: "attacksfull",
+                second: "attacks"
+            })
+        );
+
+        let request = ApiRequestBuilder::default()
+            .selections([torn::Selection::TerritoryWarReport])
+            .request;
+        assert!(request.validate(false).is_err());
+        assert!(request.validate(true).is_ok());
+
+        let request = ApiRequestBuilder::<user::Selection>::default()
+            .from_timestamp(10)
+            .to_timestamp(5)
+            .request;
+        assert_eq!(
+            request.validate(true),
+            Err(RequestValidationError::InvalidTimeRange { from: 10, to: 5 })
+        );
+    }
+
+    #[test]
+    fn api_error_code_roundtrip() {
+        for code in 0..=u8::MAX {
+            assert_eq!(u8::from(ApiErrorCode::from(code)), code);
+        }
+
+        assert!(ApiErrorCode::from(2).is_key_invalid());
+        assert!(ApiErrorCode::from(5).is_rate_limited());
+        assert!(!ApiErrorCode::from(6).is_temporary());
+    }
+
+    #[test]
+    fn api_error_code_names() {
+        assert_eq!(
+            ApiErrorCode::from(19),
+            ApiErrorCode::CrimesMigrationRequired
+        );
+        assert_eq!(ApiErrorCode::from(20), ApiErrorCode::RaceNotFinished);
+        assert_eq!(ApiErrorCode::from(21), ApiErrorCode::IncorrectCategory);
+        assert_eq!(ApiErrorCode::from(22), ApiErrorCode::OnlyInV1);
+        assert_eq!(ApiErrorCode::from(23), ApiErrorCode::OnlyInV2);
+        assert_eq!(ApiErrorCode::from(24), ApiErrorCode::ClosedTemporarily);
+        assert!(matches!(ApiErrorCode::from(25), ApiErrorCode::Other(other) if other.get() == 25));
+        assert_eq!(ApiErrorCode::from(25).code(), 25);
+
+        assert!(ApiErrorCode::ClosedTemporarily.is_temporary());
+    }
+
+    #[test]
+    fn error_scope() {
+        let key = ResponseError::from_code(ApiErrorCode::IncorrectKey, String::new());
+        assert!(key.is_key_error());
+        assert!(!key.is_request_error());
+
+        let request = ResponseError::from_code(ApiErrorCode::IncorrectId, String::new());
+        assert!(request.is_request_error());
+        assert!(!request.is_key_error());
+
+        let service = ResponseError::from_code(ApiErrorCode::BackendError, String::new());
+        assert!(matches!(service, ResponseError::Service { .. }));
+        assert_eq!(service.api_code(), Some(ApiErrorCode::BackendError));
+    }
+
+    #[test]
+    fn non_json_response() {
+        let raw = RawResponse {
+            status: 503,
+            content_type: Some("text/html; charset=UTF-8".to_owned()),
+            body: b"<!DOCTYPE html><html></html>".to_vec(),
+        };
+
+        assert!(matches!(
+            ApiResponse::from_raw(raw),
+            Err(ResponseError::NonJson { status: 503, .. })
+        ));
+
+        let raw = RawResponse {
+            status: 200,
+            content_type: Some("application/json".to_owned()),
+            body: br#"{"error":{"code":5,"error":"Too many requests"}}"#.to_vec(),
+        };
+
+        assert_eq!(
+            ApiResponse::from_raw(raw).err().unwrap().api_code(),
+            Some(ApiErrorCode::TooManyRequests)
+        );
+    }
+
+    #[cfg(feature = "user")]
+    #[test]
+    fn cached_query() {
+        let template = ApiRequestBuilder::default()
+            .selections([user::Selection::Basic, user::Selection::Discord])
+            .from_timestamp(10);
+        let request = template.with_id(1).request;
+
+        assert_eq!(
+            request.url("key", Some("1")),
+            "https://api.torn.com/user/1?selections=basic,discord&key=key&from=10"
+        );
+        assert!(template.request.query.get().is_some());
+        assert_eq!(
+            template.request.url("other", None),
+            "https://api.torn.com/user/?selections=basic,discord&key=other&from=10"
+        );
+
+        let mut changed = request.clone();
+        changed.to = Some(20);
+        assert_eq!(
+            changed.url("key", None),
+            "https://api.torn.com/user/?selections=basic,discord&key=key&from=10&to=20"
+        );
+    }
+
+    #[cfg(feature = "user")]
+    #[test]
+    fn server_time() {
+        use chrono::TimeZone;
+
+        let request = ApiRequestBuilder::<user::Selection>::new()
+            .selections([user::Selection::Basic])
+            .server_time()
+            .request;
+        assert_eq!(
+            request.url("key", None),
+            "https://api.torn.com/user/?selections=basic,timestamp&key=key"
+        );
+
+        let response = ApiResponse::from_value(serde_json::json!({
+            "level": 15,
+            "timestamp": 1_650_000_000,
+        }))
+        .unwrap();
+        assert_eq!(
+            response.server_time(),
+            Some(Utc.timestamp_opt(1_650_000_000, 0).unwrap())
+        );
+        assert!(response.age().unwrap() > chrono::Duration::zero());
+
+        let response = ApiResponse::from_value(serde_json::json!({ "level": 15 })).unwrap();
+        assert!(response.server_time().is_none());
+    }
+
+    #[test]
+    fn lazy_selections() {
+        let buffers = Arc::new(bulk::BufferPool::default());
+        let raw = RawResponse {
+            status: 200,
+            content_type: Some("application/json".to_owned()),
+            body: br#"{"level":15,"attacks":{"1":{"code":"abc"}}}"#.to_vec(),
+        };
+        let response = ApiResponse::from_raw_pooled(raw, &buffers).unwrap();
+        let ResponseSource::Raw { fields, .. } = &response.source else {
+            panic!("expected a raw response");
+        };
+        assert!(fields.values().all(|f| f.value.get().is_none()));
+
+        assert_eq!(
+            response
+                .decode_field::<i16>("basic", "i16", "level")
+                .unwrap(),
+            15
+        );
+        assert!(fields["level"].value.get().is_some());
+        assert!(fields["attacks"].value.get().is_none());
+
+        let first = response.field_value("level").unwrap().unwrap();
+        let second = response.field_value("level").unwrap().unwrap();
+        assert!(std::ptr::eq(first, second));
+
+        // the body goes back to the pool once the response is done with
+        assert_eq!(buffers.idle(), 0);
+        drop(response);
+        assert_eq!(buffers.idle(), 1);
+    }
+
+    #[test]
+    fn parsed_responses() {
+        let response = ApiResponse::from_value(serde_json::json!({ "level": 15 })).unwrap();
+        assert_eq!(response.raw(), None);
+        assert_eq!(
+            response
+                .decode_field::<i16>("basic", "i16", "level")
+                .unwrap(),
+            15
+        );
+        assert_eq!(
+            response.into_value().unwrap(),
+            serde_json::json!({ "level": 15 })
+        );
+
+        let error = serde_json::json!({ "error": { "code": 2, "error": "Incorrect key" } });
+        assert_eq!(
+            ApiResponse::from_value(error).err().unwrap().api_code(),
+            Some(ApiErrorCode::IncorrectKey)
+        );
+    }
+
+    #[test]
+    fn deserialize_error_context() {
+        let response = ApiResponse::from_value(serde_json::json!({ "level": "high" })).unwrap();
+
+        let err = response
+            .decode_field::<i16>("basic", "i16", "level")
+            .unwrap_err();
+        let SelectionError::Malformed(err) = err else {
+            panic!("expected a malformed selection");
+        };
+        assert_eq!(err.selection, "basic");
+        assert_eq!(err.raw.as_deref(), Some("\"high\""));
+
+        let err = response
+            .decode_field::<i16>("profile", "i16", "age")
+            .unwrap_err();
+        assert!(err.is_missing());
+        assert_eq!(err.selection(), "profile");
+    }
+
+    #[test]
+    fn deserialize_hook() {
+        struct Hook;
+
+        impl DeserializeHook for Hook {
+            fn parse(&self, raw: &str) -> serde_json::Result<serde_json::Value> {
+                // treat the API's quoted numbers as numbers
+                let value: serde_json::Value = serde_json::from_str(raw)?;
+                Ok(match value.as_str().and_then(|s| s.parse::<i64>().ok()) {
+                    Some(number) => number.into(),
+                    None => value,
+                })
+            }
+
+            fn on_error(&self, mut error: DeserializeError) -> DeserializeError {
+                error.raw = None;
+                error
+            }
+        }
+
+        let response = ApiResponse::from_body(r#"{"level":"15","age":"old"}"#.to_owned())
+            .unwrap()
+            .with_hook(Arc::new(Hook));
+
+        assert_eq!(
+            response
+                .decode_field::<i16>("basic", "i16", "level")
+                .unwrap(),
+            15
+        );
+
+        let SelectionError::Malformed(err) = response
+            .decode_field::<i16>("profile", "i16", "age")
+            .unwrap_err()
+        else {
+            panic!("expected a malformed selection");
+        };
+        assert!(err.raw.is_none());
+    }
+
+    #[cfg(feature = "path-to-error")]
+    #[test]
+    fn deserialize_hook_paths() {
+        struct Tracking;
+
+        impl DeserializeHook for Tracking {
+            fn deserialize(
+                &self,
+                _selection: &'static str,
+                decode: Decode<'_>,
+            ) -> serde_json::Result<()> {
+                decode.run_tracked()
+            }
+        }
+
+        let response = ApiResponse::from_body(r#"{"items":{"206":[1,"x"]}}"#.to_owned())
+            .unwrap()
+            .with_hook(Arc::new(Tracking));
+
+        let SelectionError::Malformed(err) = response
+            .decode_field::<std::collections::BTreeMap<ItemId, Vec<i16>>>(
+                "items",
+                "BTreeMap<ItemId, Vec<i16>>",
+                "items",
+            )
+            .unwrap_err()
+        else {
+            panic!("expected a malformed selection");
+        };
+        assert_eq!(err.path.as_deref(), Some("206[1]"));
+
+        // untracked errors don't have a path
+        let SelectionError::Malformed(err) = ApiResponse::from_body(r#"{"level":"x"}"#.to_owned())
+            .unwrap()
+            .decode_field::<i16>("basic", "i16", "level")
+            .unwrap_err()
+        else {
+            panic!("expected a malformed selection");
+        };
+        assert!(err.path.is_none());
+    }
+
+    #[test]
+    fn deserialize_hook_parse_error() {
+        struct Failing;
+
+        impl DeserializeHook for Failing {
+            fn parse(&self, _raw: &str) -> serde_json::Result<serde_json::Value> {
+                Err(<serde_json::Error as serde::de::Error>::custom(
+                    "unsupported",
+                ))
+            }
+        }
+
+        let response = ApiResponse::from_body(r#"{"level":15}"#.to_owned())
+            .unwrap()
+            .with_hook(Arc::new(Failing));
+        assert!(response.value().is_err());
+        assert!(response.into_value().is_err());
+    }
+
+    #[cfg(feature = "user")]
+    #[test]
+    fn strictness() {
+        let response =
+            || ApiResponse::from_value(serde_json::json!({ "gender": "Agender" })).unwrap();
+
+        let lenient = response();
+        let gender = lenient
+            .decode_field::<user::Gender>("profile", "Gender", "gender")
+            .unwrap();
+        assert_eq!(gender, user::Gender::Other("Agender".to_owned()));
+
+        response()
+            .with_strictness(Strictness::Strict)
+            .decode_field::<user::Gender>("profile", "Gender", "gender")
+            .unwrap_err();
+
+        // the mode doesn't leak into deserialisation outside the response
+        serde_json::from_str::<user::Gender>(r#""Agender""#).unwrap();
+    }
+
     #[cfg(all(feature = "reqwest", feature = "user"))]
     #[tokio::test]
     async fn reqwest() {
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{bulk::BufferPool, RawResponse};
+
+/// Caps the number of requests in flight at the same time. Clones share their permits, so one
+/// limit can be applied to several clients.
+///
+/// Only the synchronisation primitives of tokio are used, so the limit works with any runtime.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    permits: Arc<Semaphore>,
+}
+
+/// Returns its slot to the [`ConcurrencyLimit`] when dropped.
+pub type Permit = OwnedSemaphorePermit;
+
+impl ConcurrencyLimit {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(
+                max_in_flight.clamp(1, Semaphore::MAX_PERMITS),
+            )),
+        }
+    }
+
+    /// Wait for a free slot. Waiters are served in order.
+    pub async fn acquire(&self) -> Permit {
+        self.permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed")
+    }
+}
+
+/// Wraps a client so it never has more than a fixed number of requests in flight, regardless
+/// of the keys they are made with.
+pub struct LimitedClient<C> {
+    inner: C,
+    limit: ConcurrencyLimit,
+}
+
+impl<C> LimitedClient<C> {
+    pub fn new(inner: C, limit: ConcurrencyLimit) -> Self {
+        Self { inner, limit }
+    }
+
+    pub fn limit(&self) -> &ConcurrencyLimit {
+        &self.limit
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<C> crate::send::ApiClient for LimitedClient<C>
+where
+    C: crate::send::ApiClient,
+{
+    type Error = C::Error;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let _permit = self.limit.acquire().await;
+        self.inner.request(url).await
+    }
+
+    async fn request_buffered(
+        &self,
+        url: String,
+        buffers: &BufferPool,
+    ) -> Result<RawResponse, Self::Error> {
+        let _permit = self.limit.acquire().await;
+        self.inner.request_buffered(url, buffers).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<C> crate::local::ApiClient for LimitedClient<C>
+where
+    C: crate::local::ApiClient,
+{
+    type Error = C::Error;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let _permit = self.limit.acquire().await;
+        self.inner.request(url).await
+    }
+
+    async fn request_buffered(
+        &self,
+        url: String,
+        buffers: &BufferPool,
+    ) -> Result<RawResponse, Self::Error> {
+        let _permit = self.limit.acquire().await;
+        self.inner.request_buffered(url, buffers).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::send::ApiClient;
+
+    #[derive(Default)]
+    struct Slow {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ApiClient for Slow {
+        type Error = std::convert::Infallible;
+
+        async fn request(&self, _url: String) -> Result<RawResponse, Self::Error> {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.current.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(RawResponse {
+                status: 200,
+                content_type: Some("application/json".to_owned()),
+                body: b"{}".to_vec(),
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn in_flight() {
+        let client = LimitedClient::new(Slow::default(), ConcurrencyLimit::new(2));
+
+        futures::future::join_all((0..5).map(|i| client.request(i.to_string()))).await;
+
+        assert_eq!(client.inner.max.load(Ordering::SeqCst), 2);
+        assert_eq!(client.inner.current.load(Ordering::SeqCst), 0);
+    }
+}
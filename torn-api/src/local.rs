@@ -1,9 +1,12 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 
 use crate::{
-    ApiCategoryResponse, ApiClientError, ApiRequest, ApiResponse, ApiSelection, DirectExecutor,
+    bulk::{BufferPool, BulkCheckpoint, ClassifyFailure},
+    ApiCategoryResponse, ApiClientError, ApiRequest, ApiResponse, ApiSelection, DeserializeHook,
+    DirectExecutor, RawResponse, Strictness,
 };
 
 pub struct ApiProvider<'a, C, E>
@@ -26,6 +29,174 @@ where
         Self { client, executor }
     }
 
+    async fn execute_many_retrying<A, I>(
+        &self,
+        builder: crate::ApiRequestBuilder<A>,
+        ids: Vec<I>,
+    ) -> HashMap<I, Result<ApiResponse, E::Error>>
+    where
+        A: ApiSelection,
+        I: ToString + std::hash::Hash + std::cmp::Eq,
+    {
+        let mut results = self
+            .executor
+            .execute_many(self.client, builder.request.clone(), ids)
+            .await;
+
+        for _ in 0..builder.retries {
+            let (failed, mut done): (HashMap<_, _>, HashMap<_, _>) = results.into_iter().partition(
+                |(_, result)| matches!(result, Err(why) if why.cause().is_immediately_retryable()),
+            );
+
+            if failed.is_empty() {
+                return done;
+            }
+
+            done.extend(
+                self.executor
+                    .execute_many(
+                        self.client,
+                        builder.request.clone(),
+                        failed.into_keys().collect(),
+                    )
+                    .await,
+            );
+            results = done;
+        }
+
+        results
+    }
+
+    pub async fn fetch<R>(
+        &self,
+        builder: crate::ApiRequestBuilder<R::Selection>,
+    ) -> Result<R, E::Error>
+    where
+        R: ApiCategoryResponse,
+    {
+        let selections = builder.request.selections;
+        self.executor
+            .execute(self.client, builder.request, builder.id)
+            .await
+            .map(|r| R::from_response(r.with_selections(selections)))
+    }
+
+    pub async fn fetch_many<R, L, I>(
+        &self,
+        ids: L,
+        builder: crate::ApiRequestBuilder<R::Selection>,
+    ) -> HashMap<I, Result<R, E::Error>>
+    where
+        R: ApiCategoryResponse,
+        I: ToString + std::hash::Hash + std::cmp::Eq,
+        L: IntoIterator<Item = I>,
+    {
+        let selections = builder.request.selections;
+        self.execute_many_retrying(builder, Vec::from_iter(ids))
+            .await
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    k,
+                    v.map(|r| R::from_response(r.with_selections(selections))),
+                )
+            })
+            .collect()
+    }
+
+    pub async fn bulk<R, F, L, I>(&self, ids: L, build: F) -> HashMap<I, Result<R, E::Error>>
+    where
+        R: ApiCategoryResponse,
+        F: FnOnce(crate::ApiRequestBuilder<R::Selection>) -> crate::ApiRequestBuilder<R::Selection>,
+        I: ToString + std::hash::Hash + std::cmp::Eq,
+        L: IntoIterator<Item = I>,
+    {
+        self.fetch_many(ids, build(crate::ApiRequestBuilder::default()))
+            .await
+    }
+
+    /// Work through the pending ids of `checkpoint` in chunks of `chunk_size`, passing the
+    /// updated checkpoint and the results of every chunk to `on_chunk`. Stops early if ids fail
+    /// with a retryable error, e.g. because the keys ran out of calls, leaving them pending until
+    /// they run out of retries.
+    pub async fn bulk_checkpointed<R, F, I, P>(
+        &self,
+        checkpoint: &mut BulkCheckpoint<I>,
+        chunk_size: usize,
+        build: F,
+        mut on_chunk: P,
+    ) where
+        R: ApiCategoryResponse,
+        F: FnOnce(crate::ApiRequestBuilder<R::Selection>) -> crate::ApiRequestBuilder<R::Selection>,
+        I: ToString + std::hash::Hash + std::cmp::Eq + Clone,
+        P: FnMut(&BulkCheckpoint<I>, HashMap<I, Result<R, E::Error>>),
+    {
+        let builder = build(crate::ApiRequestBuilder::default());
+
+        while !checkpoint.is_done() {
+            let rest = checkpoint
+                .pending
+                .split_off(chunk_size.clamp(1, checkpoint.pending.len()));
+            let chunk = std::mem::replace(&mut checkpoint.pending, rest);
+
+            let results = self.fetch_many(chunk.clone(), builder.clone()).await;
+            let interrupted = checkpoint.record(&chunk, &results);
+            on_chunk(checkpoint, results);
+
+            if interrupted {
+                return;
+            }
+        }
+    }
+
+    /// Like [`ApiProvider::bulk`], but yields the results in the order of `ids`. At most `window`
+    /// responses are requested or waiting to be yielded at any time, so exports can write them
+    /// out sequentially without holding the whole batch in memory.
+    pub fn bulk_ordered<'b, R, F, L, I>(
+        &'b self,
+        ids: L,
+        window: usize,
+        build: F,
+    ) -> impl Stream<Item = (I, Result<R, E::Error>)> + 'b
+    where
+        R: ApiCategoryResponse + 'b,
+        F: FnOnce(crate::ApiRequestBuilder<R::Selection>) -> crate::ApiRequestBuilder<R::Selection>,
+        I: ToString + 'b,
+        L: IntoIterator<Item = I>,
+        L::IntoIter: 'b,
+    {
+        let builder = build(crate::ApiRequestBuilder::default());
+        let (request, retries) = (builder.request, builder.retries);
+
+        futures::stream::iter(ids)
+            .map(move |id| {
+                let request = request.clone();
+                async move {
+                    let id_string = id.to_string();
+                    let mut result = self
+                        .executor
+                        .execute(self.client, request.clone(), Some(id_string.clone()))
+                        .await;
+                    for _ in 0..retries {
+                        if !matches!(&result, Err(why) if why.cause().is_immediately_retryable()) {
+                            break;
+                        }
+                        result = self
+                            .executor
+                            .execute(self.client, request.clone(), Some(id_string.clone()))
+                            .await;
+                    }
+
+                    let selections = request.selections;
+                    (
+                        id,
+                        result.map(|r| R::from_response(r.with_selections(selections))),
+                    )
+                }
+            })
+            .buffered(window.max(1))
+    }
+
     #[cfg(feature = "user")]
     pub async fn user<F>(&self, build: F) -> Result<crate::user::Response, E::Error>
     where
@@ -35,11 +206,12 @@ where
     {
         let mut builder = crate::ApiRequestBuilder::default();
         builder = build(builder);
+        let selections = builder.request.selections;
 
         self.executor
             .execute(self.client, builder.request, builder.id)
             .await
-            .map(crate::user::Response::from_response)
+            .map(|r| crate::user::Response::from_response(r.with_selections(selections)))
     }
 
     #[cfg(feature = "user")]
@@ -55,15 +227,7 @@ where
         I: ToString + std::hash::Hash + std::cmp::Eq,
         L: IntoIterator<Item = I>,
     {
-        let mut builder = crate::ApiRequestBuilder::default();
-        builder = build(builder);
-
-        self.executor
-            .execute_many(self.client, builder.request, Vec::from_iter(ids))
-            .await
-            .into_iter()
-            .map(|(k, v)| (k, v.map(crate::user::Response::from_response)))
-            .collect()
+        self.bulk(ids, build).await
     }
 
     #[cfg(feature = "faction")]
@@ -75,11 +239,12 @@ where
     {
         let mut builder = crate::ApiRequestBuilder::default();
         builder = build(builder);
+        let selections = builder.request.selections;
 
         self.executor
             .execute(self.client, builder.request, builder.id)
             .await
-            .map(crate::faction::Response::from_response)
+            .map(|r| crate::faction::Response::from_response(r.with_selections(selections)))
     }
 
     #[cfg(feature = "faction")]
@@ -94,16 +259,41 @@ where
         ) -> crate::ApiRequestBuilder<crate::faction::Selection>,
         I: ToString + std::hash::Hash + std::cmp::Eq,
         L: IntoIterator<Item = I>,
+    {
+        self.bulk(ids, build).await
+    }
+
+    #[cfg(feature = "company")]
+    pub async fn company<F>(&self, build: F) -> Result<crate::company::Response, E::Error>
+    where
+        F: FnOnce(
+            crate::ApiRequestBuilder<crate::company::Selection>,
+        ) -> crate::ApiRequestBuilder<crate::company::Selection>,
     {
         let mut builder = crate::ApiRequestBuilder::default();
         builder = build(builder);
+        let selections = builder.request.selections;
 
         self.executor
-            .execute_many(self.client, builder.request, Vec::from_iter(ids))
+            .execute(self.client, builder.request, builder.id)
             .await
-            .into_iter()
-            .map(|(k, v)| (k, v.map(crate::faction::Response::from_response)))
-            .collect()
+            .map(|r| crate::company::Response::from_response(r.with_selections(selections)))
+    }
+
+    #[cfg(feature = "company")]
+    pub async fn companies<F, L, I>(
+        &self,
+        ids: L,
+        build: F,
+    ) -> HashMap<I, Result<crate::company::Response, E::Error>>
+    where
+        F: FnOnce(
+            crate::ApiRequestBuilder<crate::company::Selection>,
+        ) -> crate::ApiRequestBuilder<crate::company::Selection>,
+        I: ToString + std::hash::Hash + std::cmp::Eq,
+        L: IntoIterator<Item = I>,
+    {
+        self.bulk(ids, build).await
     }
 
     #[cfg(feature = "market")]
@@ -115,11 +305,12 @@ where
     {
         let mut builder = crate::ApiRequestBuilder::default();
         builder = build(builder);
+        let selections = builder.request.selections;
 
         self.executor
             .execute(self.client, builder.request, builder.id)
             .await
-            .map(crate::market::Response::from_response)
+            .map(|r| crate::market::Response::from_response(r.with_selections(selections)))
     }
 
     #[cfg(feature = "market")]
@@ -135,15 +326,7 @@ where
         I: ToString + std::hash::Hash + std::cmp::Eq,
         L: IntoIterator<Item = I>,
     {
-        let mut builder = crate::ApiRequestBuilder::default();
-        builder = build(builder);
-
-        self.executor
-            .execute_many(self.client, builder.request, Vec::from_iter(ids))
-            .await
-            .into_iter()
-            .map(|(k, v)| (k, v.map(crate::market::Response::from_response)))
-            .collect()
+        self.bulk(ids, build).await
     }
 
     #[cfg(feature = "torn")]
@@ -155,11 +338,12 @@ where
     {
         let mut builder = crate::ApiRequestBuilder::default();
         builder = build(builder);
+        let selections = builder.request.selections;
 
         self.executor
             .execute(self.client, builder.request, builder.id)
             .await
-            .map(crate::torn::Response::from_response)
+            .map(|r| crate::torn::Response::from_response(r.with_selections(selections)))
     }
 
     #[cfg(feature = "torn")]
@@ -175,15 +359,7 @@ where
         I: ToString + std::hash::Hash + std::cmp::Eq,
         L: IntoIterator<Item = I>,
     {
-        let mut builder = crate::ApiRequestBuilder::default();
-        builder = build(builder);
-
-        self.executor
-            .execute_many(self.client, builder.request, Vec::from_iter(ids))
-            .await
-            .into_iter()
-            .map(|(k, v)| (k, v.map(crate::torn::Response::from_response)))
-            .collect()
+        self.bulk(ids, build).await
     }
 
     #[cfg(feature = "key")]
@@ -195,11 +371,29 @@ where
     {
         let mut builder = crate::ApiRequestBuilder::default();
         builder = build(builder);
+        let selections = builder.request.selections;
 
         self.executor
             .execute(self.client, builder.request, builder.id)
             .await
-            .map(crate::key::Response::from_response)
+            .map(|r| crate::key::Response::from_response(r.with_selections(selections)))
+    }
+}
+
+impl<'a, C> ApiProvider<'a, C, DirectExecutor<C>>
+where
+    C: ApiClient,
+{
+    /// Deserialise the selections of all responses using `hook`.
+    pub fn deserialize_hook(mut self, hook: Arc<dyn DeserializeHook>) -> Self {
+        self.executor.hook = Some(hook);
+        self
+    }
+
+    /// Deserialise the selections of all responses with `strictness`.
+    pub fn strictness(mut self, strictness: Strictness) -> Self {
+        self.executor.strictness = strictness;
+        self
     }
 }
 
@@ -208,7 +402,7 @@ pub trait RequestExecutor<C>
 where
     C: ApiClient,
 {
-    type Error: std::error::Error;
+    type Error: std::error::Error + ClassifyFailure;
 
     async fn execute<A>(
         &self,
@@ -246,11 +440,16 @@ where
     where
         A: ApiSelection,
     {
+        request.validate(id.is_some())?;
+
         let url = request.url(&self.key, id.as_deref());
 
-        let value = client.request(url).await.map_err(ApiClientError::Client)?;
+        let raw = client
+            .request_buffered(url, &self.buffers)
+            .await
+            .map_err(ApiClientError::Client)?;
 
-        Ok(ApiResponse::from_value(value)?)
+        Ok(self.parse(raw)?)
     }
 
     async fn execute_many<A, I>(
@@ -263,17 +462,24 @@ where
         A: ApiSelection,
         I: ToString + std::hash::Hash + std::cmp::Eq,
     {
+        if let Err(why) = request.validate(true) {
+            return ids
+                .into_iter()
+                .map(|i| (i, Err(ApiClientError::Validation(why.clone()))))
+                .collect();
+        }
+
         let request_ref = &request;
         let tuples = futures::future::join_all(ids.into_iter().map(|i| async move {
             let id_string = i.to_string();
             let url = request_ref.url(&self.key, Some(&id_string));
 
-            let value = client.request(url).await.map_err(ApiClientError::Client);
+            let raw = client
+                .request_buffered(url, &self.buffers)
+                .await
+                .map_err(ApiClientError::Client);
 
-            (
-                i,
-                value.and_then(|v| ApiResponse::from_value(v).map_err(Into::into)),
-            )
+            (i, raw.and_then(|r| self.parse(r).map_err(Into::into)))
         }))
         .await;
 
@@ -285,9 +491,19 @@ where
 pub trait ApiClient {
     type Error: std::error::Error;
 
-    async fn request(&self, url: String) -> Result<serde_json::Value, Self::Error>;
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error>;
+
+    /// Like [`ApiClient::request`], but reads the body into a buffer taken from `buffers`.
+    /// Clients which can't fill a provided buffer simply allocate a new one.
+    async fn request_buffered(
+        &self,
+        url: String,
+        _buffers: &BufferPool,
+    ) -> Result<RawResponse, Self::Error> {
+        self.request(url).await
+    }
 
-    fn torn_api<S>(&self, key: S) -> ApiProvider<Self, DirectExecutor<Self>>
+    fn torn_api<S>(&self, key: S) -> ApiProvider<'_, Self, DirectExecutor<Self>>
     where
         Self: Sized,
         S: ToString,
@@ -1,21 +1,73 @@
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 use torn_api_macros::ApiCategory;
 
+use crate::{
+    de_util::{null_is_empty_btree_map, null_is_empty_vec},
+    ItemId,
+};
+
 #[derive(Debug, Clone, Copy, ApiCategory)]
 #[api(category = "market")]
 pub enum MarketSelection {
-    #[api(type = "Vec<BazaarItem>", field = "bazaar")]
+    #[api(
+        type = "Vec<BazaarItem>",
+        field = "bazaar",
+        with = "null_is_empty_vec",
+        requires_id
+    )]
     Bazaar,
+
+    /// The cheapest item market listings of the item with the given id.
+    #[api(
+        type = "Vec<ItemMarketListing>",
+        field = "itemmarket",
+        with = "null_is_empty_vec",
+        requires_id
+    )]
+    ItemMarket,
+
+    #[api(
+        type = "BTreeMap<i64, PointsListing>",
+        field = "pointsmarket",
+        with = "null_is_empty_btree_map"
+    )]
+    PointsMarket,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct BazaarItem {
     #[serde(rename = "ID")]
-    pub id: u32,
+    pub id: ItemId,
     pub cost: u64,
     pub quantity: u32,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct ItemMarketListing {
+    #[serde(rename = "ID")]
+    pub listing_id: i64,
+    pub cost: u64,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PointsListing {
+    /// Price per point.
+    pub cost: u64,
+    pub quantity: u32,
+    pub total_cost: u64,
+}
+
+/// The cheapest of `listings` by price per unit.
+pub fn cheapest<'a, I>(listings: I) -> Option<&'a ItemMarketListing>
+where
+    I: IntoIterator<Item = &'a ItemMarketListing>,
+{
+    listings.into_iter().min_by_key(|l| l.cost)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -33,4 +85,57 @@ mod test {
 
         _ = response.bazaar().unwrap();
     }
+
+    #[async_test]
+    async fn market_item_and_points() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .market(|b| {
+                b.id(ItemId(206))
+                    .selections([MarketSelection::ItemMarket, MarketSelection::PointsMarket])
+            })
+            .await
+            .unwrap();
+
+        _ = response.item_market().unwrap();
+        _ = response.points_market().unwrap();
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn listings() {
+        use crate::mock::MockClient;
+
+        let client = MockClient::new().respond(
+            Some(206),
+            [MarketSelection::ItemMarket, MarketSelection::PointsMarket],
+            serde_json::json!({
+                "itemmarket": [
+                    { "ID": 2, "cost": 830_000, "quantity": 1 },
+                    { "ID": 1, "cost": 825_000, "quantity": 1 },
+                ],
+                "pointsmarket": {
+                    "12345": { "cost": 45_000, "quantity": 25, "total_cost": 1_125_000 },
+                },
+            }),
+        );
+
+        let response = client
+            .torn_api("key")
+            .market(|b| {
+                b.id(206)
+                    .selections([MarketSelection::ItemMarket, MarketSelection::PointsMarket])
+            })
+            .await
+            .unwrap();
+
+        let items = response.item_market().unwrap();
+        assert_eq!(cheapest(&items).unwrap().listing_id, 1);
+        assert_eq!(
+            response.points_market().unwrap()[&12345].total_cost,
+            1_125_000
+        );
+    }
 }
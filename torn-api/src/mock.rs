@@ -0,0 +1,442 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{ApiErrorCode, ApiSelection, RawResponse, SelectionSet};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MockError {
+    #[error("No fixture for request '{0}'")]
+    NoFixture(String),
+
+    #[error("Malformed request url '{0}'")]
+    InvalidUrl(String),
+}
+
+#[derive(Error, Debug)]
+pub enum RecordError<E>
+where
+    E: std::error::Error,
+{
+    #[error(transparent)]
+    Client(E),
+
+    #[error("Failed to store recording: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FixtureKey {
+    category: String,
+    id: Option<String>,
+    selections: String,
+}
+
+impl FixtureKey {
+    fn from_url(url: &str) -> Option<Self> {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut segments = path.split('/').skip(1).filter(|s| !s.is_empty());
+        let category = match segments.next()? {
+            // v2 urls carry the version in front of the category and the selection in the path
+            "v2" => format!("v2.{}", segments.next()?),
+            category => category.to_owned(),
+        };
+        let id = segments.next().map(ToOwned::to_owned);
+
+        let selections = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("selections="))
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| segments.collect::<Vec<_>>().join("/"));
+
+        Some(Self {
+            category,
+            id,
+            selections,
+        })
+    }
+
+    fn file_name(&self) -> String {
+        let selections = if self.selections.is_empty() {
+            "none".to_owned()
+        } else {
+            self.selections.replace([',', '/'], "+")
+        };
+        let id: String = self
+            .id
+            .as_deref()
+            .unwrap_or("self")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("{}.{}.{}.json", self.category, id, selections)
+    }
+}
+
+fn redact_key(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_owned();
+    };
+    let query: Vec<_> = query
+        .split('&')
+        .map(|param| {
+            if param.starts_with("key=") {
+                "key=REDACTED"
+            } else {
+                param
+            }
+        })
+        .collect();
+    format!("{}?{}", base, query.join("&"))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    url: String,
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+}
+
+/// An [`ApiClient`](crate::send::ApiClient) answering requests with canned responses instead of
+/// talking to the Torn API.
+#[derive(Debug, Default)]
+pub struct MockClient {
+    fixtures: HashMap<FixtureKey, RawResponse>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl MockClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all responses stored by a [`RecordingClient`] in `dir`.
+    pub fn replay(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut client = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            let recording: Recording = serde_json::from_slice(&std::fs::read(&path)?)?;
+            let Some(key) = FixtureKey::from_url(&recording.url) else {
+                continue;
+            };
+            client.fixtures.insert(
+                key,
+                RawResponse {
+                    status: recording.status,
+                    content_type: recording.content_type,
+                    body: recording.body.into_bytes(),
+                },
+            );
+        }
+        Ok(client)
+    }
+
+    /// Respond with `body` to requests for exactly `selections` of the entity `id`.
+    #[must_use]
+    pub fn respond<A, I>(
+        mut self,
+        id: Option<I>,
+        selections: impl IntoIterator<Item = A>,
+        body: serde_json::Value,
+    ) -> Self
+    where
+        A: ApiSelection,
+        I: ToString,
+    {
+        let key = FixtureKey {
+            category: A::category().to_owned(),
+            id: id.map(|i| i.to_string()),
+            selections: selections
+                .into_iter()
+                .collect::<SelectionSet<A>>()
+                .query_value(),
+        };
+        self.fixtures.insert(
+            key,
+            RawResponse {
+                status: 200,
+                content_type: Some("application/json".to_owned()),
+                body: serde_json::to_vec(&body).unwrap(),
+            },
+        );
+        self
+    }
+
+    /// Respond with `body` to requests for `url`, for endpoints which aren't modelled as
+    /// selections, like the ones of v2. Query parameters other than the selections are ignored.
+    #[must_use]
+    pub fn respond_url(mut self, url: &str, body: serde_json::Value) -> Self {
+        let key = FixtureKey::from_url(url).expect("valid fixture url");
+        self.fixtures.insert(
+            key,
+            RawResponse {
+                status: 200,
+                content_type: Some("application/json".to_owned()),
+                body: serde_json::to_vec(&body).unwrap(),
+            },
+        );
+        self
+    }
+
+    #[must_use]
+    pub fn respond_error<A, I>(
+        self,
+        id: Option<I>,
+        selections: impl IntoIterator<Item = A>,
+        code: ApiErrorCode,
+    ) -> Self
+    where
+        A: ApiSelection,
+        I: ToString,
+    {
+        let body = serde_json::json!({
+            "error": { "code": code.code(), "error": format!("{:?}", code) }
+        });
+        self.respond(id, selections, body)
+    }
+
+    /// Urls of all requests made so far, including the ones without a fixture.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn handle(&self, url: String) -> Result<RawResponse, MockError> {
+        let key = FixtureKey::from_url(&url).ok_or_else(|| MockError::InvalidUrl(url.clone()))?;
+        self.requests.lock().unwrap().push(url.clone());
+
+        self.fixtures
+            .get(&key)
+            .cloned()
+            .ok_or(MockError::NoFixture(url))
+    }
+}
+
+#[async_trait]
+impl crate::send::ApiClient for MockClient {
+    type Error = MockError;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        self.handle(url)
+    }
+}
+
+#[async_trait(?Send)]
+impl crate::local::ApiClient for MockClient {
+    type Error = MockError;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        self.handle(url)
+    }
+}
+
+#[cfg(feature = "yata")]
+#[async_trait]
+impl crate::yata::send::PostClient for MockClient {
+    async fn post_json(&self, url: String, _body: Vec<u8>) -> Result<RawResponse, Self::Error> {
+        self.handle(url)
+    }
+}
+
+#[cfg(feature = "yata")]
+#[async_trait(?Send)]
+impl crate::yata::local::PostClient for MockClient {
+    async fn post_json(&self, url: String, _body: Vec<u8>) -> Result<RawResponse, Self::Error> {
+        self.handle(url)
+    }
+}
+
+/// Wraps a client and stores every response it receives in `dir`, with the api key redacted,
+/// so it can later be replayed through [`MockClient::replay`].
+pub struct RecordingClient<C> {
+    inner: C,
+    dir: PathBuf,
+}
+
+impl<C> RecordingClient<C> {
+    pub fn new(inner: C, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+        }
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn store(&self, url: &str, raw: &RawResponse) -> std::io::Result<()> {
+        let url = redact_key(url);
+        let Some(key) = FixtureKey::from_url(&url) else {
+            return Ok(());
+        };
+        let recording = Recording {
+            url,
+            status: raw.status,
+            content_type: raw.content_type.clone(),
+            body: String::from_utf8_lossy(&raw.body).into_owned(),
+        };
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.dir.join(key.file_name()),
+            serde_json::to_vec_pretty(&recording)?,
+        )
+    }
+}
+
+#[async_trait]
+impl<C> crate::send::ApiClient for RecordingClient<C>
+where
+    C: crate::send::ApiClient,
+{
+    type Error = RecordError<C::Error>;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let raw = self
+            .inner
+            .request(url.clone())
+            .await
+            .map_err(RecordError::Client)?;
+        self.store(&url, &raw)?;
+        Ok(raw)
+    }
+}
+
+#[async_trait(?Send)]
+impl<C> crate::local::ApiClient for RecordingClient<C>
+where
+    C: crate::local::ApiClient,
+{
+    type Error = RecordError<C::Error>;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let raw = self
+            .inner
+            .request(url.clone())
+            .await
+            .map_err(RecordError::Client)?;
+        self.store(&url, &raw)?;
+        Ok(raw)
+    }
+}
+
+#[cfg(all(test, feature = "user"))]
+mod tests {
+    use super::*;
+    use crate::{send::ApiClient, user, UserId};
+
+    #[tokio::test]
+    async fn canned_responses() {
+        let client = MockClient::new()
+            .respond(
+                Some(28),
+                [user::Selection::Basic],
+                serde_json::json!({
+                    "player_id": 28,
+                    "name": "Chedburn",
+                    "level": 100,
+                    "gender": "Male",
+                    "status": {
+                        "description": "Okay",
+                        "details": "",
+                        "state": "Okay",
+                        "color": "green",
+                        "until": 0
+                    }
+                }),
+            )
+            .respond_error(Some(1), [user::Selection::Basic], ApiErrorCode::IncorrectId);
+
+        let provider = client.torn_api("key");
+
+        let response = provider
+            .user(|b| b.id(28).selections([user::Selection::Basic]))
+            .await
+            .unwrap();
+        assert_eq!(response.basic().unwrap().player_id, UserId(28));
+
+        let err = provider
+            .user(|b| b.id(1).selections([user::Selection::Basic]))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.api_code(), Some(ApiErrorCode::IncorrectId));
+
+        assert!(provider
+            .user(|b| b.id(2).selections([user::Selection::Basic]))
+            .await
+            .is_err());
+        assert_eq!(client.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn ordered_bulk() {
+        use futures::StreamExt;
+
+        let mut client = MockClient::new();
+        for id in [4, 28, 1] {
+            client = client.respond(
+                Some(id),
+                [user::Selection::Discord],
+                serde_json::json!({ "discord": { "userID": id, "discordID": "" } }),
+            );
+        }
+
+        let provider = client.torn_api("key");
+        let results: Vec<_> = provider
+            .bulk_ordered::<user::Response, _, _, _>([28, 1, 2, 4], 2, |b| {
+                b.selections([user::Selection::Discord])
+            })
+            .collect()
+            .await;
+
+        let ids: Vec<_> = results.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [28, 1, 2, 4]);
+        assert!(results[2].1.is_err());
+        assert_eq!(
+            results[3].1.as_ref().unwrap().discord().unwrap().user_id,
+            Some(UserId(4))
+        );
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("torn-api-recording-{}", std::process::id()));
+        let live = MockClient::new().respond(
+            Some(28),
+            [user::Selection::Discord],
+            serde_json::json!({ "discord": { "userID": 28, "discordID": "", "verified": false } }),
+        );
+
+        let recorder = RecordingClient::new(live, &dir);
+        recorder
+            .torn_api("secret")
+            .user(|b| b.id(28).selections([user::Selection::Discord]))
+            .await
+            .unwrap();
+
+        let stored = std::fs::read_to_string(dir.join("user.28.discord.json")).unwrap();
+        assert!(!stored.contains("secret"));
+
+        let replay = MockClient::replay(&dir).unwrap();
+        let response = replay
+            .torn_api("other")
+            .user(|b| b.id(28).selections([user::Selection::Discord]))
+            .await
+            .unwrap();
+        assert_eq!(response.discord().unwrap().user_id, Some(UserId(28)));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    common::{AttackFullOwned, Respect},
+    user::{PersonalStats, ProfileOwned},
+};
+
+#[cfg(feature = "decimal")]
+fn respect(respect: Respect) -> f64 {
+    use rust_decimal::prelude::ToPrimitive;
+    respect.to_f64().unwrap_or_default()
+}
+
+#[cfg(not(feature = "decimal"))]
+fn respect(respect: Respect) -> f64 {
+    respect.into()
+}
+
+/// Postgres accepts at most this many bind parameters per statement, so multi-row inserts are
+/// split into chunks of `BIND_LIMIT / columns` rows.
+const BIND_LIMIT: usize = u16::MAX as usize;
+
+/// Stores snapshots of owned models in plain tables, e.g. for tracking the progress of players
+/// over time. Profiles and personal stats are keyed by player and time of the snapshot, attacks
+/// by their id and chain reports by faction and start of the chain.
+#[derive(Debug, Clone)]
+pub struct PgSnapshotStorage {
+    pool: PgPool,
+}
+
+impl PgSnapshotStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn initialise(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"CREATE TABLE IF NOT EXISTS profile_snapshots (
+                player_id int8 not null,
+                taken_at timestamptz not null,
+                name text not null,
+                rank text not null,
+                level int2 not null,
+                age int4 not null,
+                faction_id int8,
+                state text not null,
+                last_action timestamptz not null,
+                primary key (player_id, taken_at)
+            )"#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"CREATE TABLE IF NOT EXISTS personal_stats_snapshots (
+                player_id int8 not null,
+                taken_at timestamptz not null,
+                attacks_won int4 not null,
+                attacks_lost int4 not null,
+                defends_won int4 not null,
+                defends_lost int4 not null,
+                stat_enhancers_used int4 not null,
+                refills int4 not null,
+                drugs_used int4 not null,
+                xanax_taken int4 not null,
+                lsd_taken int4 not null,
+                net_worth int8 not null,
+                cans_used int4 not null,
+                boosters_used int4 not null,
+                awards int2 not null,
+                elo int2 not null,
+                days_been_donator int2 not null,
+                best_damage int4 not null,
+                primary key (player_id, taken_at)
+            )"#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"CREATE TABLE IF NOT EXISTS attacks (
+                id int8 primary key,
+                code text not null,
+                timestamp_started timestamptz not null,
+                timestamp_ended timestamptz not null,
+                attacker_id int8,
+                attacker_faction int8,
+                defender_id int8 not null,
+                defender_faction int8,
+                result text not null,
+                stealthed bool not null,
+                respect float8 not null
+            )"#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "faction")]
+        {
+            sqlx::query(
+                r#"CREATE TABLE IF NOT EXISTS chain_reports (
+                    faction_id int8 not null,
+                    started timestamptz not null,
+                    ended timestamptz not null,
+                    length int4 not null,
+                    respect float8 not null,
+                    targets int4 not null,
+                    war_hits int4 not null,
+                    best_hit float8 not null,
+                    retaliations int4 not null,
+                    primary key (faction_id, started)
+                )"#,
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                r#"CREATE TABLE IF NOT EXISTS chain_report_members (
+                    faction_id int8 not null,
+                    started timestamptz not null,
+                    user_id int8 not null,
+                    respect float8 not null,
+                    attacks int4 not null,
+                    leaves int4 not null,
+                    mugs int4 not null,
+                    hospitalizations int4 not null,
+                    overseas int4 not null,
+                    war_hits int4 not null,
+                    retaliations int4 not null,
+                    assists int4 not null,
+                    bonus_hits int4 not null,
+                    best_hit float8 not null,
+                    primary key (faction_id, started, user_id),
+                    foreign key (faction_id, started) references chain_reports
+                )"#,
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn store_profile(
+        &self,
+        profile: &ProfileOwned,
+        taken_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO profile_snapshots
+                (player_id, taken_at, name, rank, level, age, faction_id, state, last_action)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT DO NOTHING"#,
+        )
+        .bind(profile.player_id.get())
+        .bind(taken_at)
+        .bind(&profile.name)
+        .bind(&profile.rank)
+        .bind(profile.level)
+        .bind(profile.age)
+        .bind(profile.faction.as_ref().map(|f| f.faction_id.get()))
+        .bind(profile.status.state.as_str())
+        .bind(profile.last_action.timestamp)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn store_personal_stats(
+        &self,
+        player_id: crate::UserId,
+        stats: &PersonalStats,
+        taken_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO personal_stats_snapshots (
+                player_id, taken_at, attacks_won, attacks_lost, defends_won, defends_lost,
+                stat_enhancers_used, refills, drugs_used, xanax_taken, lsd_taken, net_worth,
+                cans_used, boosters_used, awards, elo, days_been_donator, best_damage
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT DO NOTHING"#,
+        )
+        .bind(player_id.get())
+        .bind(taken_at)
+        .bind(stats.attacks_won)
+        .bind(stats.attacks_lost)
+        .bind(stats.defends_won)
+        .bind(stats.defends_lost)
+        .bind(stats.stat_enhancers_used)
+        .bind(stats.refills)
+        .bind(stats.drugs_used)
+        .bind(stats.xanax_taken)
+        .bind(stats.lsd_taken)
+        .bind(stats.net_worth)
+        .bind(stats.cans_used)
+        .bind(stats.boosters_used)
+        .bind(stats.awards)
+        .bind(stats.elo)
+        .bind(stats.days_been_donator)
+        .bind(stats.best_damage)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store attacks which aren't stored yet. Returns the number of new attacks.
+    pub async fn store_attacks(
+        &self,
+        attacks: &BTreeMap<i64, AttackFullOwned>,
+    ) -> Result<u64, sqlx::Error> {
+        const COLUMNS: usize = 11;
+
+        let attacks: Vec<_> = attacks.iter().collect();
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+
+        for chunk in attacks.chunks(BIND_LIMIT / COLUMNS) {
+            let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO attacks (id, code, timestamp_started, timestamp_ended, attacker_id, \
+                attacker_faction, defender_id, defender_faction, result, stealthed, respect) ",
+            );
+            qb.push_values(chunk, |mut row, (id, attack)| {
+                row.push_bind(**id)
+                    .push_bind(attack.code.clone())
+                    .push_bind(attack.timestamp_started)
+                    .push_bind(attack.timestamp_ended)
+                    .push_bind(attack.attacker_id.map(|id| id.get()))
+                    .push_bind(attack.attacker_faction.map(|id| id.get()))
+                    .push_bind(attack.defender_id.get())
+                    .push_bind(attack.defender_faction.map(|id| id.get()))
+                    .push_bind(attack.result.as_str().to_owned())
+                    .push_bind(attack.stealthed)
+                    .push_bind(respect(attack.respect));
+            });
+            qb.push(" ON CONFLICT DO NOTHING");
+
+            inserted += qb.build().execute(&mut *tx).await?.rows_affected();
+        }
+
+        tx.commit().await?;
+
+        Ok(inserted)
+    }
+
+    /// Store a chain report together with its members, unless it's stored already. Returns
+    /// whether the report was new.
+    #[cfg(feature = "faction")]
+    pub async fn store_chain_report(
+        &self,
+        report: &crate::faction::ChainReport,
+    ) -> Result<bool, sqlx::Error> {
+        const COLUMNS: usize = 14;
+
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            r#"INSERT INTO chain_reports (
+                faction_id, started, ended, length, respect, targets, war_hits, best_hit,
+                retaliations
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT DO NOTHING"#,
+        )
+        .bind(report.faction_id.get())
+        .bind(report.start)
+        .bind(report.end)
+        .bind(report.length)
+        .bind(report.respect)
+        .bind(report.targets)
+        .bind(report.war_hits)
+        .bind(report.best_hit)
+        .bind(report.retaliations)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            == 1;
+
+        if inserted {
+            let members: Vec<_> = report.members.values().collect();
+            for chunk in members.chunks(BIND_LIMIT / COLUMNS) {
+                let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+                    "INSERT INTO chain_report_members (faction_id, started, user_id, respect, \
+                    attacks, leaves, mugs, hospitalizations, overseas, war_hits, retaliations, \
+                    assists, bonus_hits, best_hit) ",
+                );
+                qb.push_values(chunk, |mut row, member| {
+                    row.push_bind(report.faction_id.get())
+                        .push_bind(report.start)
+                        .push_bind(member.user_id.get())
+                        .push_bind(member.respect)
+                        .push_bind(member.attacks)
+                        .push_bind(member.leaves)
+                        .push_bind(member.mugs)
+                        .push_bind(member.hospitalizations)
+                        .push_bind(member.overseas)
+                        .push_bind(member.war_hits)
+                        .push_bind(member.retaliations)
+                        .push_bind(member.assists)
+                        .push_bind(member.bonus_hits)
+                        .push_bind(member.best_hit);
+                });
+                qb.build().execute(&mut *tx).await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(inserted)
+    }
+
+    /// Id of the newest stored attack, e.g. to resume an [`crate::attack_sync::AttackSync`].
+    pub async fn latest_attack(&self) -> Result<Option<(i64, DateTime<Utc>)>, sqlx::Error> {
+        sqlx::query_as("SELECT id::int8, timestamp_started FROM attacks ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{common::AttackFull, IntoOwned};
+
+    #[tokio::test]
+    async fn attack_snapshots() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        sqlx::query("DROP TABLE IF EXISTS attacks")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let storage = PgSnapshotStorage::new(pool);
+        storage.initialise().await.unwrap();
+
+        let value = serde_json::json!({
+            "7": {
+                "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                "timestamp_started": 1_650_000_000,
+                "timestamp_ended": 1_650_000_100,
+                "attacker_id": "",
+                "attacker_name": "",
+                "attacker_faction": "",
+                "attacker_factionname": "",
+                "defender_id": 28,
+                "defender_name": "Chedburn",
+                "defender_faction": "",
+                "defender_factionname": "",
+                "result": "Lost",
+                "stealthed": 1,
+                "raid": 0,
+                "ranked_war": 0,
+                "respect": 0.0,
+                "respect_loss": 0.0,
+                "modifiers": {
+                    "fair_fight": 1.0,
+                    "war": 1,
+                    "retaliation": 1,
+                    "group_attack": 1,
+                    "overseas": 1,
+                    "chain_bonus": 1,
+                },
+            }
+        });
+        let attacks = BTreeMap::<i64, AttackFull>::deserialize(&value).unwrap();
+        let attacks = attacks.into_owned();
+
+        assert_eq!(storage.store_attacks(&attacks).await.unwrap(), 1);
+        assert_eq!(storage.store_attacks(&attacks).await.unwrap(), 0);
+
+        let (id, started) = storage.latest_attack().await.unwrap().unwrap();
+        assert_eq!(id, 7);
+        assert_eq!(started, attacks[&7].timestamp_started);
+
+        let result: String = sqlx::query_scalar("SELECT result FROM attacks WHERE id = 7")
+            .fetch_one(&storage.pool)
+            .await
+            .unwrap();
+        assert_eq!(result, "Lost");
+    }
+    #[cfg(feature = "faction")]
+    #[tokio::test]
+    async fn chain_report_snapshots() {
+        use crate::faction::ChainReport;
+
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        sqlx::query("DROP TABLE IF EXISTS chain_report_members, chain_reports")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let storage = PgSnapshotStorage::new(pool.clone());
+        storage.initialise().await.unwrap();
+
+        let report: ChainReport = serde_json::from_value(serde_json::json!({
+            "factionID": 9,
+            "chain": 100,
+            "start": 1_650_000_000,
+            "end": 1_650_003_600,
+            "respect": "412.5",
+            "targets": 80,
+            "warhits": 0,
+            "besthit": "25.2",
+            "retaliations": 3,
+            "members": {
+                "28": {
+                    "userID": 28, "respect": "412.5", "attacks": 100, "leave": 100, "mug": 0,
+                    "hospitalize": 0, "overseas": 2, "war": 0, "retaliation": 3, "assist": 0,
+                    "bonus": 1, "besthit": "25.2",
+                },
+            },
+            "bonuses": [[28, 100, 25.2]],
+        }))
+        .unwrap();
+
+        assert!(storage.store_chain_report(&report).await.unwrap());
+        assert!(!storage.store_chain_report(&report).await.unwrap());
+
+        let (members,): (i64,) = sqlx::query_as("SELECT count(*) FROM chain_report_members")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(members, 1);
+    }
+}
@@ -1,12 +1,79 @@
 use async_trait::async_trait;
 
-use crate::send::ApiClient;
+use crate::{bulk::BufferPool, send::ApiClient, RawResponse};
 
 #[async_trait]
 impl ApiClient for reqwest::Client {
     type Error = reqwest::Error;
 
-    async fn request(&self, url: String) -> Result<serde_json::Value, Self::Error> {
-        self.get(url).send().await?.json().await
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let response = self.get(url).send().await?;
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+        let body = response.bytes().await?.to_vec();
+
+        Ok(RawResponse {
+            status,
+            content_type,
+            body,
+        })
+    }
+
+    async fn request_buffered(
+        &self,
+        url: String,
+        buffers: &BufferPool,
+    ) -> Result<RawResponse, Self::Error> {
+        let mut response = self.get(url).send().await?;
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+
+        let mut body = buffers.take();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(RawResponse {
+            status,
+            content_type,
+            body,
+        })
+    }
+}
+
+#[cfg(feature = "yata")]
+#[async_trait]
+impl crate::yata::send::PostClient for reqwest::Client {
+    async fn post_json(&self, url: String, body: Vec<u8>) -> Result<RawResponse, Self::Error> {
+        let response = self
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+        let body = response.bytes().await?.to_vec();
+
+        Ok(RawResponse {
+            status,
+            content_type,
+            body,
+        })
     }
 }
@@ -0,0 +1,296 @@
+use std::marker::PhantomData;
+
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ApiSelection;
+
+#[derive(Clone, Copy)]
+pub struct SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    bits: u64,
+    phantom: PhantomData<A>,
+}
+
+impl<A> Default for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    pub const fn new() -> Self {
+        Self {
+            bits: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub(crate) fn from_bits(bits: u64) -> Self {
+        Self {
+            bits,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn all() -> Self {
+        A::all().iter().copied().collect()
+    }
+
+    fn bit(selection: A) -> u64 {
+        1 << selection.index()
+    }
+
+    pub fn insert(&mut self, selection: A) -> bool {
+        let inserted = !self.contains(selection);
+        self.bits |= Self::bit(selection);
+        inserted
+    }
+
+    pub fn remove(&mut self, selection: A) -> bool {
+        let removed = self.contains(selection);
+        self.bits &= !Self::bit(selection);
+        removed
+    }
+
+    #[must_use]
+    pub fn with(mut self, selection: A) -> Self {
+        self.insert(selection);
+        self
+    }
+
+    pub fn contains(&self, selection: A) -> bool {
+        self.bits & Self::bit(selection) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+            phantom: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        Self {
+            bits: self.bits & other.bits,
+            phantom: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn difference(self, other: Self) -> Self {
+        Self {
+            bits: self.bits & !other.bits,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = A> + '_ {
+        A::all().iter().copied().filter(|s| self.contains(*s))
+    }
+
+    /// The value of the `selections` query parameter.
+    pub fn query_value(&self) -> String {
+        let mut value = String::new();
+        for (idx, selection) in self.iter().enumerate() {
+            if idx != 0 {
+                value.push(',');
+            }
+            value.push_str(selection.raw_value());
+        }
+        value
+    }
+}
+
+impl<A> PartialEq for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<A> Eq for SelectionSet<A> where A: ApiSelection {}
+
+impl<A> std::hash::Hash for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
+impl<A> std::fmt::Debug for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|s| s.raw_value()))
+            .finish()
+    }
+}
+
+impl<A> std::fmt::Display for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.query_value())
+    }
+}
+
+impl<A> From<A> for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn from(selection: A) -> Self {
+        Self::new().with(selection)
+    }
+}
+
+impl<A> FromIterator<A> for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn from_iter<T: IntoIterator<Item = A>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<A> Extend<A> for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn extend<T: IntoIterator<Item = A>>(&mut self, iter: T) {
+        for selection in iter {
+            self.insert(selection);
+        }
+    }
+}
+
+impl<A> IntoIterator for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    type Item = A;
+    type IntoIter = std::vec::IntoIter<A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter().collect::<Vec<_>>().into_iter()
+    }
+}
+
+impl<A> std::ops::BitOr for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl<A> std::ops::BitAnd for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl<A> Serialize for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter().map(|s| s.raw_value()))
+    }
+}
+
+impl<'de, A> Deserialize<'de> for SelectionSet<A>
+where
+    A: ApiSelection,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw: Vec<String> = Deserialize::deserialize(deserializer)?;
+        raw.iter()
+            .map(|r| {
+                A::all()
+                    .iter()
+                    .copied()
+                    .find(|s| s.raw_value() == *r)
+                    .ok_or_else(|| {
+                        D::Error::custom(format!("unknown {} selection '{}'", A::category(), r))
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "user"))]
+mod tests {
+    use super::*;
+    use crate::user::Selection;
+
+    #[test]
+    fn set_operations() {
+        let a: SelectionSet<_> = [Selection::Profile, Selection::Basic, Selection::Profile]
+            .into_iter()
+            .collect();
+        let b = SelectionSet::from(Selection::Basic).with(Selection::Discord);
+
+        assert_eq!(a.len(), 2);
+        assert_eq!((a & b).query_value(), "basic");
+        assert_eq!((a | b).len(), 3);
+        assert!((a & b).is_subset(&a));
+        assert_eq!(a.query_value(), "basic,profile");
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"["basic","profile"]"#);
+        assert_eq!(
+            serde_json::from_str::<SelectionSet<Selection>>(&json).unwrap(),
+            a
+        );
+        assert!(serde_json::from_str::<SelectionSet<Selection>>(r#"["nope"]"#).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use rkyv::{
+    de::deserializers::SharedDeserializeMap, ser::serializers::AllocSerializer,
+    validation::validators::DefaultValidator, AlignedVec, Archive, CheckBytes, Deserialize,
+    Serialize,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Failed to encode snapshot: {0}")]
+    Encode(String),
+
+    #[error("Invalid snapshot: {0}")]
+    Invalid(String),
+}
+
+/// Encode an owned model, e.g. a `BTreeMap<i64, AttackFullOwned>`, for storing it in a cache.
+pub fn encode<T>(value: &T) -> Result<AlignedVec, SnapshotError>
+where
+    T: Serialize<AllocSerializer<1024>>,
+{
+    rkyv::to_bytes::<_, 1024>(value).map_err(|why| SnapshotError::Encode(why.to_string()))
+}
+
+/// Validate and decode a snapshot created by [`encode`]. Bytes which aren't suitably aligned,
+/// as they usually aren't after being read from disk or redis, are copied first.
+pub fn decode<T>(bytes: &[u8]) -> Result<T, SnapshotError>
+where
+    T: Archive,
+    T::Archived: for<'a> CheckBytes<DefaultValidator<'a>> + Deserialize<T, SharedDeserializeMap>,
+{
+    let result = if (bytes.as_ptr() as usize).is_multiple_of(AlignedVec::ALIGNMENT) {
+        rkyv::from_bytes(bytes)
+    } else {
+        let mut aligned = AlignedVec::with_capacity(bytes.len());
+        aligned.extend_from_slice(bytes);
+        rkyv::from_bytes(&aligned)
+    };
+
+    result.map_err(|why| SnapshotError::Invalid(why.to_string()))
+}
+
+#[cfg(all(test, feature = "__common"))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        common::{AttackFull, AttackFullOwned, AttackResult},
+        IntoOwned, UserId,
+    };
+
+    #[test]
+    fn attacks_round_trip() {
+        let value = serde_json::json!({
+            "1": {
+                "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                "timestamp_started": 1_650_000_000,
+                "timestamp_ended": 1_650_000_100,
+                "attacker_id": 2111649,
+                "attacker_name": "Pyrit",
+                "attacker_faction": "",
+                "attacker_factionname": "",
+                "defender_id": 28,
+                "defender_name": "Chedburn",
+                "defender_faction": "",
+                "defender_factionname": "",
+                "result": "Hospitalized",
+                "stealthed": 0,
+                "raid": 0,
+                "ranked_war": 0,
+                "respect": 4.2,
+                "respect_loss": 0.0,
+                "modifiers": {
+                    "fair_fight": 3.0,
+                    "war": 1,
+                    "retaliation": 1,
+                    "group_attack": 1,
+                    "overseas": 1,
+                    "chain_bonus": 1,
+                },
+            }
+        });
+        let attacks = BTreeMap::<i64, AttackFull>::deserialize(&value).unwrap();
+        let attacks = attacks.into_owned();
+
+        let bytes = encode(&attacks).unwrap();
+        let decoded: BTreeMap<i64, AttackFullOwned> = decode(&bytes).unwrap();
+        let attack = &decoded[&1];
+        assert_eq!(attack.code, attacks[&1].code);
+        assert_eq!(attack.attacker_id, Some(UserId(2111649)));
+        assert_eq!(attack.defender_name, "Chedburn");
+        assert_eq!(attack.result, AttackResult::Hospitalized);
+        assert_eq!(attack.timestamp_ended, attacks[&1].timestamp_ended);
+        assert_eq!(attack.modifiers.fair_fight, 3.0);
+
+        // misaligned input is copied before validation
+        let mut shifted = vec![0];
+        shifted.extend_from_slice(&bytes);
+        decode::<BTreeMap<i64, AttackFullOwned>>(&shifted[1..]).unwrap();
+
+        // a corrupted root
+        let mut corrupted = bytes.to_vec();
+        let len = corrupted.len();
+        corrupted[len - 8..].fill(0xff);
+        assert!(decode::<BTreeMap<i64, AttackFullOwned>>(&corrupted).is_err());
+    }
+}
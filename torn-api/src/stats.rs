@@ -0,0 +1,189 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BattleStat {
+    Strength,
+    Defense,
+    Speed,
+    Dexterity,
+}
+
+impl BattleStat {
+    pub fn all() -> [Self; 4] {
+        [Self::Strength, Self::Defense, Self::Speed, Self::Dexterity]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Strength => "Strength",
+            Self::Defense => "Defense",
+            Self::Speed => "Speed",
+            Self::Dexterity => "Dexterity",
+        }
+    }
+
+    // stat specific constants of the gym gain formula
+    fn gain_constants(self) -> (f64, f64) {
+        match self {
+            Self::Strength => (1600.0, 1700.0),
+            Self::Defense => (2100.0, -600.0),
+            Self::Speed => (1600.0, 2000.0),
+            Self::Dexterity => (1800.0, 1500.0),
+        }
+    }
+}
+
+impl std::fmt::Display for BattleStat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct BattleStats {
+    pub strength: f64,
+    pub defense: f64,
+    pub speed: f64,
+    pub dexterity: f64,
+}
+
+impl BattleStats {
+    pub fn total(&self) -> f64 {
+        self.strength + self.defense + self.speed + self.dexterity
+    }
+
+    pub fn get(&self, stat: BattleStat) -> f64 {
+        match stat {
+            BattleStat::Strength => self.strength,
+            BattleStat::Defense => self.defense,
+            BattleStat::Speed => self.speed,
+            BattleStat::Dexterity => self.dexterity,
+        }
+    }
+
+    pub fn get_mut(&mut self, stat: BattleStat) -> &mut f64 {
+        match stat {
+            BattleStat::Strength => &mut self.strength,
+            BattleStat::Defense => &mut self.defense,
+            BattleStat::Speed => &mut self.speed,
+            BattleStat::Dexterity => &mut self.dexterity,
+        }
+    }
+
+    /// Share of the total made up by `stat`, between 0 and 1.
+    pub fn ratio(&self, stat: BattleStat) -> f64 {
+        let total = self.total();
+        if total == 0.0 {
+            0.0
+        } else {
+            self.get(stat) / total
+        }
+    }
+}
+
+/// Above this value gym gains only grow logarithmically with the trained stat.
+pub const STAT_SOFT_CAP: f64 = 50_000_000.0;
+
+/// The value of a stat as used by the gym gain formula.
+pub fn effective_stat(value: f64) -> f64 {
+    if value > STAT_SOFT_CAP {
+        (value - STAT_SOFT_CAP) / (8.77635 * value.ln()) + STAT_SOFT_CAP
+    } else {
+        value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Train {
+    pub happy: f64,
+    /// Gym dots for the trained stat as shown in game, e.g. `7.3`.
+    pub gym_dots: f64,
+    pub energy_per_train: f64,
+    /// Combined gym gain multiplier from perks, e.g. `1.1` for +10%.
+    pub modifier: f64,
+}
+
+impl Train {
+    pub fn new(happy: f64, gym_dots: f64, energy_per_train: f64) -> Self {
+        Self {
+            happy,
+            gym_dots,
+            energy_per_train,
+            modifier: 1.0,
+        }
+    }
+
+    #[must_use]
+    pub fn modifier(mut self, modifier: f64) -> Self {
+        self.modifier = modifier;
+        self
+    }
+
+    /// Estimated gain of a single train of `stat` at its current `value`.
+    pub fn gain(&self, stat: BattleStat, value: f64) -> f64 {
+        fn round4(value: f64) -> f64 {
+            (value * 10_000.0).round() / 10_000.0
+        }
+
+        let (a, b) = stat.gain_constants();
+        let happy = self.happy.max(0.0);
+        let happy_mult = round4(1.0 + 0.07 * round4((1.0 + happy / 250.0).ln()));
+
+        (effective_stat(value) * happy_mult
+            + 8.0 * happy.powf(1.05)
+            + (1.0 - (happy / 99_999.0).powi(2)) * a
+            + b)
+            / 200_000.0
+            * self.gym_dots
+            * self.energy_per_train
+            * self.modifier
+    }
+
+    /// Estimated total gain of spending `energy` on `stat`, accounting for the happy lost and
+    /// the stat gained with every train.
+    pub fn gains(&self, stat: BattleStat, value: f64, energy: f64) -> f64 {
+        let trains = (energy / self.energy_per_train).floor() as u64;
+        let mut train = *self;
+        let mut current = value;
+        for _ in 0..trains {
+            current += train.gain(stat, current);
+            // each train costs on average half the energy spent in happy
+            train.happy = (train.happy - train.energy_per_train / 2.0).max(0.0);
+        }
+        current - value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn battle_stats() {
+        let stats = BattleStats {
+            strength: 100.0,
+            defense: 100.0,
+            speed: 150.0,
+            dexterity: 50.0,
+        };
+
+        assert_eq!(stats.total(), 400.0);
+        assert_eq!(stats.ratio(BattleStat::Speed), 0.375);
+    }
+
+    #[test]
+    fn gym_gains() {
+        let train = Train::new(5000.0, 7.3, 10.0);
+
+        let gain = train.gain(BattleStat::Strength, 1_000_000.0);
+        assert!((gain - 466.34).abs() < 0.01, "{gain}");
+        assert!(train.modifier(1.1).gain(BattleStat::Strength, 1_000_000.0) > gain);
+        assert!(Train::new(250.0, 7.3, 10.0).gain(BattleStat::Strength, 1_000_000.0) < gain);
+
+        assert_eq!(effective_stat(1_000_000.0), 1_000_000.0);
+        assert!(effective_stat(100_000_000.0) < 51_000_000.0);
+
+        let gains = train.gains(BattleStat::Strength, 1_000_000.0, 150.0);
+        assert!(gains > 14.5 * gain && gains < 15.5 * gain, "{gains}");
+    }
+}
@@ -0,0 +1,329 @@
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    common::Stock, send::ApiClient, ApiResponse, ApiVersion, ResponseError, SelectionError,
+};
+
+const BASE_URL: &str = "https://api.torn.com/v2/torn";
+
+#[derive(Error, Debug)]
+pub enum StockError<C>
+where
+    C: std::error::Error,
+{
+    #[error(transparent)]
+    Client(C),
+
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+}
+
+/// The span covered by a single point of the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StockInterval {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl StockInterval {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StockHistoryRequest {
+    pub stock: Stock,
+    pub interval: StockInterval,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl StockHistoryRequest {
+    pub fn new(stock: Stock, interval: StockInterval) -> Self {
+        Self {
+            stock,
+            interval,
+            from: None,
+            to: None,
+        }
+    }
+
+    #[must_use]
+    pub fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    #[must_use]
+    pub fn to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn url(&self, key: &str) -> String {
+        let mut url = format!(
+            "{BASE_URL}/{}/stocks?interval={}",
+            self.stock.id(),
+            self.interval.as_str()
+        );
+        if let Some(from) = self.from {
+            url.push_str(&format!("&from={}", from.timestamp()));
+        }
+        if let Some(to) = self.to {
+            url.push_str(&format!("&to={}", to.timestamp()));
+        }
+        url.push_str(&format!("&key={key}"));
+        url
+    }
+}
+
+/// Prices of a stock during one interval.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StockPoint {
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl StockPoint {
+    /// Relative change over the interval, e.g. `0.05` for a rise of five percent.
+    pub fn change(&self) -> f64 {
+        if self.open == 0.0 {
+            0.0
+        } else {
+            (self.close - self.open) / self.open
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BenefitType {
+    Active,
+    Passive,
+    #[serde(other)]
+    Unknown,
+}
+
+/// What a benefit pays out, parsed from its description.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+pub enum BenefitReward {
+    Money(i64),
+    Points(i64),
+    Item {
+        quantity: i32,
+        name: String,
+    },
+    /// Anything else, e.g. a discount, by its description.
+    Other(String),
+}
+
+fn parse_amount(s: &str) -> Option<i64> {
+    s.replace(',', "").parse().ok()
+}
+
+impl BenefitReward {
+    pub fn parse(description: &str) -> Self {
+        let description = description.trim();
+
+        if let Some(money) = description.strip_prefix('$').and_then(parse_amount) {
+            return Self::Money(money);
+        }
+
+        if let Some(points) = description
+            .strip_suffix(" Points")
+            .or_else(|| description.strip_suffix(" points"))
+            .and_then(parse_amount)
+        {
+            return Self::Points(points);
+        }
+
+        if let Some((quantity, name)) = description.split_once("x ") {
+            if let Ok(quantity) = quantity.parse() {
+                return Self::Item {
+                    quantity,
+                    name: name.to_owned(),
+                };
+            }
+        }
+
+        Self::Other(description.to_owned())
+    }
+}
+
+impl From<String> for BenefitReward {
+    fn from(description: String) -> Self {
+        Self::parse(&description)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StockBenefit {
+    #[serde(rename = "type")]
+    pub benefit_type: BenefitType,
+    /// Days between payouts of an active benefit.
+    pub frequency: i16,
+    /// Shares needed for the benefit.
+    pub requirement: i64,
+    #[serde(rename = "description")]
+    pub reward: BenefitReward,
+}
+
+/// A stock as listed in the torn `stocks` selection.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StockListing {
+    #[serde(rename = "stock_id")]
+    pub stock: Stock,
+    pub name: String,
+    pub acronym: String,
+    pub current_price: f64,
+    pub market_cap: i64,
+    pub total_shares: i64,
+    pub investors: i32,
+    pub benefit: StockBenefit,
+}
+
+impl StockListing {
+    /// The price of the shares needed for the benefit at the current price.
+    pub fn benefit_cost(&self) -> f64 {
+        self.benefit.requirement as f64 * self.current_price
+    }
+}
+
+/// A client for the price history of stocks, which is only available in v2 of the API.
+pub struct StockProvider<'a, C>
+where
+    C: ApiClient,
+{
+    client: &'a C,
+    key: String,
+}
+
+impl<'a, C> StockProvider<'a, C>
+where
+    C: ApiClient,
+{
+    pub fn new<S>(client: &'a C, key: S) -> Self
+    where
+        S: ToString,
+    {
+        Self {
+            client,
+            key: key.to_string(),
+        }
+    }
+
+    /// The points of the requested range, oldest first.
+    pub async fn history(
+        &self,
+        request: StockHistoryRequest,
+    ) -> Result<Vec<StockPoint>, StockError<C::Error>> {
+        let raw = self
+            .client
+            .request(request.url(&self.key))
+            .await
+            .map_err(StockError::Client)?;
+        let response = ApiResponse::from_raw(raw)?;
+
+        let mut points: Vec<StockPoint> =
+            response.decode_versioned("stocks", "Vec<StockPoint>", "history", ApiVersion::V2)?;
+        points.sort_by_key(|p| p.timestamp);
+
+        Ok(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn benefit_rewards() {
+        assert_eq!(
+            BenefitReward::parse("$50,000,000"),
+            BenefitReward::Money(50_000_000)
+        );
+        assert_eq!(
+            BenefitReward::parse("1,000 Points"),
+            BenefitReward::Points(1000)
+        );
+        assert_eq!(
+            BenefitReward::parse("1x Box of Grenades"),
+            BenefitReward::Item {
+                quantity: 1,
+                name: "Box of Grenades".to_owned()
+            }
+        );
+        assert_eq!(
+            BenefitReward::parse("10% Bank Interest Bonus"),
+            BenefitReward::Other("10% Bank Interest Bonus".to_owned())
+        );
+
+        let stock: StockListing = serde_json::from_value(serde_json::json!({
+            "stock_id": 1,
+            "name": "Torn & Shanghai Banking",
+            "acronym": "TSB",
+            "current_price": 1000.0,
+            "market_cap": 1_000_000_000_000_i64,
+            "total_shares": 1_000_000_000,
+            "investors": 10000,
+            "benefit": {
+                "type": "active",
+                "frequency": 31,
+                "requirement": 3_000_000,
+                "description": "$50,000,000",
+            },
+        }))
+        .unwrap();
+        assert_eq!(stock.stock, Stock::Tsb);
+        assert_eq!(stock.benefit.benefit_type, BenefitType::Active);
+        assert_eq!(stock.benefit_cost(), 3_000_000_000.0);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn history() {
+        use chrono::TimeZone;
+
+        let request = StockHistoryRequest::new(Stock::Tci, StockInterval::Day)
+            .from(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+        assert_eq!(
+            request.url("key"),
+            "https://api.torn.com/v2/torn/2/stocks?interval=day&from=1700000000&key=key"
+        );
+
+        let client = crate::mock::MockClient::new().respond_url(
+            "https://api.torn.com/v2/torn/2/stocks",
+            serde_json::json!({ "history": [
+                { "timestamp": 1700086400, "open": 500.0, "high": 530.5, "low": 498.0, "close": 525.0 },
+                { "timestamp": 1700000000, "open": 510.0, "high": 512.0, "low": 490.0, "close": 500.0 },
+            ]}),
+        );
+        let points = StockProvider::new(&client, "key")
+            .history(request)
+            .await
+            .unwrap();
+
+        assert_eq!(points.len(), 2);
+        assert!(points[0].timestamp < points[1].timestamp);
+        assert_eq!(points[1].change(), 0.05);
+    }
+}
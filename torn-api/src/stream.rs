@@ -0,0 +1,426 @@
+use futures::{Stream, StreamExt};
+use thiserror::Error;
+
+use crate::{
+    common::{AttackFullOwned, AttackOwned},
+    DeserializeError, IntoOwned, ResponseError, SelectionError,
+};
+
+#[derive(Error, Debug)]
+pub enum StreamError<E>
+where
+    E: std::error::Error,
+{
+    #[error(transparent)]
+    Client(E),
+
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+}
+
+enum Step {
+    NeedMore,
+    Entry {
+        key: std::ops::Range<usize>,
+        value: std::ops::Range<usize>,
+    },
+    Missing,
+    Done,
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Start,
+    Top,
+    Field,
+    Done,
+}
+
+fn syntax(msg: &str) -> ResponseError {
+    <serde_json::Error as serde::de::Error>::custom(msg).into()
+}
+
+fn skip_ws(buf: &[u8], from: usize) -> Option<usize> {
+    (from..buf.len()).find(|i| !buf[*i].is_ascii_whitespace())
+}
+
+fn skip_separators(buf: &[u8], from: usize) -> Option<usize> {
+    (from..buf.len()).find(|i| !buf[*i].is_ascii_whitespace() && buf[*i] != b',')
+}
+
+// index of the quote closing the string opened at `start`
+fn string_end(buf: &[u8], start: usize) -> Option<usize> {
+    let mut escape = false;
+    for (i, c) in buf.iter().enumerate().skip(start + 1) {
+        match c {
+            _ if escape => escape = false,
+            b'\\' => escape = true,
+            b'"' => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn value_end(buf: &[u8], start: usize) -> Option<usize> {
+    match buf[start] {
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut i = start;
+            while i < buf.len() {
+                match buf[i] {
+                    b'"' => i = string_end(buf, i)?,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(i + 1);
+                        }
+                    }
+                    _ => (),
+                }
+                i += 1;
+            }
+            None
+        }
+        b'"' => string_end(buf, start).map(|end| end + 1),
+        _ => (start..buf.len())
+            .find(|i| matches!(buf[*i], b',' | b'}' | b']') || buf[*i].is_ascii_whitespace()),
+    }
+}
+
+/// Incrementally extracts the entries of one object valued top level field from a response body.
+struct EntryParser {
+    buf: Vec<u8>,
+    pos: usize,
+    state: State,
+    field: &'static str,
+}
+
+impl EntryParser {
+    fn new(field: &'static str) -> Self {
+        Self {
+            buf: Vec::new(),
+            pos: 0,
+            state: State::Start,
+            field,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        self.buf.extend_from_slice(chunk);
+    }
+
+    // returns the range of the key and the position after the colon
+    fn key(&self, start: usize) -> Result<Option<(std::ops::Range<usize>, usize)>, ResponseError> {
+        if self.buf[start] != b'"' {
+            return Err(syntax("expected object key"));
+        }
+        let Some(end) = string_end(&self.buf, start) else {
+            return Ok(None);
+        };
+        let Some(colon) = skip_ws(&self.buf, end + 1) else {
+            return Ok(None);
+        };
+        if self.buf[colon] != b':' {
+            return Err(syntax("expected ':'"));
+        }
+        Ok(Some((start + 1..end, colon + 1)))
+    }
+
+    fn next(&mut self) -> Result<Step, ResponseError> {
+        #[derive(serde::Deserialize)]
+        struct ApiErrorDto {
+            code: u8,
+            #[serde(rename = "error")]
+            reason: String,
+        }
+
+        loop {
+            match self.state {
+                State::Start => {
+                    let Some(start) = skip_ws(&self.buf, self.pos) else {
+                        return Ok(Step::NeedMore);
+                    };
+                    if self.buf[start] != b'{' {
+                        return Err(syntax("expected response object"));
+                    }
+                    self.pos = start + 1;
+                    self.state = State::Top;
+                }
+                State::Top => {
+                    let Some(start) = skip_separators(&self.buf, self.pos) else {
+                        return Ok(Step::NeedMore);
+                    };
+                    if self.buf[start] == b'}' {
+                        self.state = State::Done;
+                        return Ok(Step::Missing);
+                    }
+                    let Some((key, after)) = self.key(start)? else {
+                        return Ok(Step::NeedMore);
+                    };
+                    let Some(value) = skip_ws(&self.buf, after) else {
+                        return Ok(Step::NeedMore);
+                    };
+
+                    if &self.buf[key.clone()] == self.field.as_bytes() && self.buf[value] == b'{' {
+                        self.pos = value + 1;
+                        self.state = State::Field;
+                        continue;
+                    }
+
+                    let Some(end) = value_end(&self.buf, value) else {
+                        return Ok(Step::NeedMore);
+                    };
+                    if &self.buf[key.clone()] == b"error" {
+                        let dto: ApiErrorDto = serde_json::from_slice(&self.buf[value..end])?;
+                        return Err(ResponseError::from_code(dto.code.into(), dto.reason));
+                    }
+                    if &self.buf[key] == self.field.as_bytes() {
+                        // null or an empty array instead of an empty object
+                        self.state = State::Done;
+                        return Ok(Step::Done);
+                    }
+                    self.pos = end;
+                }
+                State::Field => {
+                    let Some(start) = skip_separators(&self.buf, self.pos) else {
+                        return Ok(Step::NeedMore);
+                    };
+                    if self.buf[start] == b'}' {
+                        self.state = State::Done;
+                        return Ok(Step::Done);
+                    }
+                    let Some((key, after)) = self.key(start)? else {
+                        return Ok(Step::NeedMore);
+                    };
+                    let Some(value) = skip_ws(&self.buf, after) else {
+                        return Ok(Step::NeedMore);
+                    };
+                    let Some(end) = value_end(&self.buf, value) else {
+                        return Ok(Step::NeedMore);
+                    };
+                    self.pos = end;
+                    return Ok(Step::Entry {
+                        key,
+                        value: value..end,
+                    });
+                }
+                State::Done => return Ok(Step::Done),
+            }
+        }
+    }
+}
+
+/// Decode the entries of the object in `field` as they arrive in `body`, keeping only the entry
+/// currently being decoded in memory instead of the whole response.
+pub fn entries<S, B, E, K, V>(
+    body: S,
+    field: &'static str,
+    type_name: &'static str,
+    decode: fn(&[u8]) -> serde_json::Result<V>,
+) -> impl Stream<Item = Result<(K, V), StreamError<E>>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::error::Error,
+    K: std::str::FromStr,
+{
+    let malformed = move |raw: &[u8], source| -> StreamError<E> {
+        let raw = String::from_utf8_lossy(raw).into_owned();
+        SelectionError::from(DeserializeError::with_raw(
+            field,
+            type_name,
+            Some(raw),
+            source,
+        ))
+        .into()
+    };
+
+    let state = (Box::pin(body), EntryParser::new(field));
+    futures::stream::unfold(Some(state), move |state| async move {
+        let (mut body, mut parser) = state?;
+        loop {
+            match parser.next() {
+                Err(why) => return Some((Err(why.into()), None)),
+                Ok(Step::Entry { key, value }) => {
+                    let key_raw = &parser.buf[key];
+                    let value_raw = &parser.buf[value];
+                    let item = match std::str::from_utf8(key_raw)
+                        .ok()
+                        .and_then(|k| k.parse().ok())
+                    {
+                        None => Err(malformed(
+                            key_raw,
+                            <serde_json::Error as serde::de::Error>::custom("invalid key"),
+                        )),
+                        Some(key) => decode(value_raw)
+                            .map(|value| (key, value))
+                            .map_err(|source| malformed(value_raw, source)),
+                    };
+                    return Some((item, Some((body, parser))));
+                }
+                Ok(Step::Missing) => {
+                    let missing = SelectionError::Missing { selection: field };
+                    return Some((Err(missing.into()), None));
+                }
+                Ok(Step::Done) => return None,
+                Ok(Step::NeedMore) => match body.next().await {
+                    Some(Ok(chunk)) => parser.feed(chunk.as_ref()),
+                    Some(Err(why)) => return Some((Err(StreamError::Client(why)), None)),
+                    None => return Some((Err(syntax("unexpected end of body").into()), None)),
+                },
+            }
+        }
+    })
+}
+
+/// Entries of the `attacks` selection of the user and faction categories.
+pub fn attacks<S, B, E>(
+    body: S,
+) -> impl Stream<Item = Result<(i64, AttackFullOwned), StreamError<E>>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::error::Error,
+{
+    entries(body, "attacks", "AttackFull", |raw| {
+        serde_json::from_slice::<crate::common::AttackFull>(raw).map(IntoOwned::into_owned)
+    })
+}
+
+/// Entries of the `attacksfull` selection of the user and faction categories.
+pub fn attacks_full<S, B, E>(
+    body: S,
+) -> impl Stream<Item = Result<(i64, AttackOwned), StreamError<E>>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::error::Error,
+{
+    entries(body, "attacks", "Attack", |raw| {
+        serde_json::from_slice::<crate::common::Attack>(raw).map(IntoOwned::into_owned)
+    })
+}
+
+/// Send the request described by `builder` and return its body as a stream of chunks, to be
+/// decoded with e.g. [`attacks`]. Responses which aren't JSON, like error pages, are classified
+/// the same way as by [`crate::ApiResponse::from_raw`].
+#[cfg(feature = "reqwest")]
+pub async fn request<A>(
+    client: &reqwest::Client,
+    key: &str,
+    builder: crate::ApiRequestBuilder<A>,
+) -> Result<
+    impl Stream<Item = Result<impl AsRef<[u8]>, reqwest::Error>>,
+    crate::ApiClientError<reqwest::Error>,
+>
+where
+    A: crate::ApiSelection,
+{
+    builder.request.validate(builder.id.is_some())?;
+
+    let url = builder.request.url(key, builder.id.as_deref());
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(crate::ApiClientError::Client)?;
+
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .map(ToOwned::to_owned);
+    let mut body = response.bytes_stream();
+
+    // wait for the start of the body, so that it can be told apart from e.g. an html page
+    let mut head = Vec::new();
+    while head.iter().all(u8::is_ascii_whitespace) {
+        match body.next().await {
+            Some(chunk) => head.extend_from_slice(&chunk.map_err(crate::ApiClientError::Client)?),
+            None => break,
+        }
+    }
+
+    let raw = crate::RawResponse {
+        status,
+        content_type,
+        body: head,
+    };
+    crate::ApiResponse::check_json(&raw)?;
+
+    Ok(futures::stream::once(async move { Ok(raw.body.into()) }).chain(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::UserId;
+
+    fn chunked(body: &str, size: usize) -> impl Stream<Item = Result<Vec<u8>, Infallible>> + '_ {
+        futures::stream::iter(body.as_bytes().chunks(size).map(|c| Ok(c.to_vec())))
+    }
+
+    #[test]
+    fn streamed_attacks() {
+        let attack = |defender: i32| {
+            serde_json::json!({
+                "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                "timestamp_started": 1_650_000_000,
+                "timestamp_ended": 1_650_000_100,
+                "attacker_id": "",
+                "attacker_faction": "",
+                "defender_id": defender,
+                "defender_faction": "",
+                "result": "Lost",
+                "stealthed": 1,
+                "respect": 0.0,
+            })
+        };
+        let body = serde_json::json!({
+            "chain": { "current": 0, "note": "{ \"braces\" in strings }" },
+            "attacks": { "1": attack(28), "2": attack(4) },
+        })
+        .to_string();
+
+        for size in [1, 7, 64, body.len()] {
+            let attacks: Vec<_> = block_on(attacks_full(chunked(&body, size)).collect());
+            let attacks: Vec<_> = attacks.into_iter().map(Result::unwrap).collect();
+            assert_eq!(attacks.len(), 2);
+            assert_eq!(attacks[0].0, 1);
+            assert_eq!(attacks[1].1.defender_id, UserId(4));
+        }
+
+        let empty: Vec<_> = block_on(attacks(chunked(r#"{"attacks":[]}"#, 3)).collect());
+        assert!(empty.is_empty());
+
+        let error: Vec<_> = block_on(
+            attacks(chunked(
+                r#"{"error":{"code":2,"error":"Incorrect key"}}"#,
+                5,
+            ))
+            .collect(),
+        );
+        assert!(matches!(
+            &error[..],
+            [Err(StreamError::Response(ResponseError::Key { .. }))]
+        ));
+
+        let missing: Vec<_> = block_on(attacks(chunked(r#"{"chain":{}}"#, 5)).collect());
+        assert!(matches!(
+            &missing[..],
+            [Err(StreamError::Selection(SelectionError::Missing { .. }))]
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, HashMap};
+
+use thiserror::Error;
+
+use crate::{
+    common::Territory,
+    faction::{self, FactionTerritoryWarOwned},
+    send::{ApiClient, ApiProvider, RequestExecutor},
+    torn::{self, Racket, TerritoryWar},
+    FactionId, IntoOwned, SelectionError,
+};
+
+#[derive(Error, Debug)]
+pub enum TerritoryError<E>
+where
+    E: std::error::Error,
+{
+    #[error(transparent)]
+    Api(E),
+
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+}
+
+/// A territory held by the faction.
+#[derive(Debug, Clone)]
+pub struct HeldTerritory {
+    pub territory: Territory,
+    pub racket: Option<Racket>,
+    /// The war on the territory, if it's being assaulted.
+    pub war: Option<TerritoryWar>,
+    /// The score of the assault, if the war is listed in the faction's wars.
+    pub score: Option<FactionTerritoryWarOwned>,
+}
+
+impl HeldTerritory {
+    pub fn is_under_assault(&self) -> bool {
+        self.war.is_some()
+    }
+}
+
+/// A territory the faction is assaulting.
+#[derive(Debug, Clone)]
+pub struct Assault {
+    pub war: FactionTerritoryWarOwned,
+    pub racket: Option<Racket>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TerritoryOverview {
+    pub faction_id: FactionId,
+    /// The faction's territories by their name.
+    pub held: BTreeMap<String, HeldTerritory>,
+    /// The territories the faction is assaulting by their name.
+    pub assaults: BTreeMap<String, Assault>,
+}
+
+impl TerritoryOverview {
+    /// Join the faction's basic and territory selections with the torn `territorywars` and
+    /// `rackets` selections.
+    pub fn new(
+        basic: &faction::Basic,
+        territory: &HashMap<String, Territory>,
+        wars: &HashMap<String, TerritoryWar>,
+        rackets: &HashMap<String, Racket>,
+    ) -> Self {
+        let faction_wars: HashMap<_, _> = basic
+            .territory_wars
+            .iter()
+            .map(|w| (w.territory_war_id, w))
+            .collect();
+
+        let held = territory
+            .iter()
+            .map(|(name, territory)| {
+                let war = wars
+                    .get(name)
+                    .filter(|w| w.defending_faction == basic.id)
+                    .cloned();
+                let score = war
+                    .as_ref()
+                    .and_then(|w| faction_wars.get(&w.territory_war_id))
+                    .map(|w| (*w).clone().into_owned());
+                let held = HeldTerritory {
+                    territory: territory.clone(),
+                    racket: rackets.get(name).cloned(),
+                    war,
+                    score,
+                };
+                (name.clone(), held)
+            })
+            .collect();
+
+        let assaults = basic
+            .territory_wars
+            .iter()
+            .filter(|w| w.assaulting_faction == basic.id)
+            .map(|w| {
+                let assault = Assault {
+                    war: w.clone().into_owned(),
+                    racket: rackets.get(w.territory).cloned(),
+                };
+                (w.territory.to_owned(), assault)
+            })
+            .collect();
+
+        Self {
+            faction_id: basic.id,
+            held,
+            assaults,
+        }
+    }
+
+    /// The held territories which are being assaulted.
+    pub fn under_assault(&self) -> impl Iterator<Item = (&str, &HeldTerritory)> {
+        self.held
+            .iter()
+            .filter(|(_, t)| t.is_under_assault())
+            .map(|(name, t)| (name.as_str(), t))
+    }
+
+    /// Request everything the overview needs in two concurrent requests. Without an id, the key
+    /// owner's faction is used.
+    pub async fn fetch<C, E>(
+        provider: &ApiProvider<'_, C, E>,
+        faction_id: Option<FactionId>,
+    ) -> Result<Self, TerritoryError<E::Error>>
+    where
+        C: ApiClient,
+        E: RequestExecutor<C>,
+    {
+        let (faction, torn) = futures::join!(
+            provider.faction(|b| {
+                let b = b.selections([faction::Selection::Basic, faction::Selection::Territory]);
+                match faction_id {
+                    Some(id) => b.id(id),
+                    None => b,
+                }
+            }),
+            provider.torn(|b| {
+                b.selections([torn::Selection::TerritoryWars, torn::Selection::Rackets])
+            })
+        );
+        let faction = faction.map_err(TerritoryError::Api)?;
+        let torn = torn.map_err(TerritoryError::Api)?;
+
+        Ok(Self::new(
+            &faction.basic()?,
+            &faction.territory()?,
+            &torn.territory_wars()?,
+            &torn.rackets()?,
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+
+    #[tokio::test]
+    async fn overview() {
+        let territory = |faction: i32| {
+            serde_json::json!({
+                "sector": 1,
+                "size": 10,
+                "density": 5,
+                "slots": 3,
+                "daily_respect": 50,
+                "faction": faction,
+                "coordinate_x": "1.0",
+                "coordinate_y": "2.0",
+            })
+        };
+        let war = |id: i32, territory: &str, assaulting: i32, defending: i32| {
+            serde_json::json!({
+                "territory_war_id": id,
+                "territory": territory,
+                "assaulting_faction": assaulting,
+                "defending_faction": defending,
+                "score": 100,
+                "required_score": 1000,
+                "start_time": 1_700_000_000,
+                "end_time": 1_700_100_000,
+            })
+        };
+        let client = MockClient::new()
+            .respond(
+                None::<i32>,
+                [faction::Selection::Basic, faction::Selection::Territory],
+                serde_json::json!({
+                    "ID": 7, "name": "Faction", "leader": 1, "co-leader": 0, "respect": 1000,
+                    "age": 100, "capacity": 100, "best_chain": 10, "tag_image": "",
+                    "members": {}, "peace": {},
+                    "territory_wars": [war(1, "ABC", 8, 7), war(2, "XYZ", 7, 9)],
+                    "territory": { "ABC": territory(7), "DEF": territory(7) },
+                }),
+            )
+            .respond(
+                None::<i32>,
+                [torn::Selection::TerritoryWars, torn::Selection::Rackets],
+                serde_json::json!({
+                    "territorywars": {
+                        "ABC": {
+                            "territory_war_id": 1, "assaulting_faction": 8,
+                            "defending_faction": 7, "started": 1_700_000_000,
+                            "ends": 1_700_100_000,
+                        },
+                    },
+                    "rackets": {
+                        "XYZ": {
+                            "name": "Gambling Den", "level": 2, "reward": "$1,000 daily",
+                            "created": 1_600_000_000, "changed": 1_650_000_000, "faction": 9,
+                        },
+                    },
+                }),
+            );
+
+        let overview = TerritoryOverview::fetch(&client.torn_api("key"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(overview.faction_id, FactionId(7));
+        assert_eq!(overview.held.len(), 2);
+        let assaulted: Vec<_> = overview.under_assault().collect();
+        assert_eq!(assaulted.len(), 1);
+        assert_eq!(assaulted[0].0, "ABC");
+        assert_eq!(assaulted[0].1.score.as_ref().unwrap().required_score, 1000);
+
+        let assault = &overview.assaults["XYZ"];
+        assert_eq!(assault.war.defending_faction, FactionId(9));
+        assert_eq!(assault.racket.as_ref().unwrap().level, 2);
+    }
+}
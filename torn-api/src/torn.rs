@@ -3,12 +3,22 @@ use std::collections::{BTreeMap, HashMap};
 use chrono::{DateTime, Utc};
 use serde::{
     de::{self, MapAccess, Visitor},
-    Deserialize, Deserializer,
+    Deserialize,
 };
 
-use torn_api_macros::ApiCategory;
+use torn_api_macros::{ApiCategory, IntoOwned};
 
-use crate::{de_util, user};
+use crate::{
+    de_util::{self, null_is_empty_btree_map, null_is_empty_dict},
+    user, CourseId, FactionId, ItemId, UserId,
+};
+
+pub use crate::common::{
+    Course, PropertyStaff, PropertyType, PropertyUpgrade, RankedWar, RankedWarFaction,
+    RankedWarInfo, Stock,
+};
+
+pub use crate::stocks::{BenefitReward, BenefitType, StockBenefit, StockListing};
 
 #[derive(Debug, Clone, Copy, ApiCategory)]
 #[api(category = "torn")]
@@ -23,7 +33,7 @@ pub enum TornSelection {
 
     #[api(
         type = "HashMap<String, TerritoryWar>",
-        with = "decode_territory_wars",
+        with = "null_is_empty_dict",
         field = "territorywars"
     )]
     TerritoryWars,
@@ -33,16 +43,103 @@ pub enum TornSelection {
 
     #[api(
         type = "HashMap<String, Territory>",
-        with = "decode_territory",
+        with = "null_is_empty_dict",
         field = "territory"
     )]
     Territory,
 
-    #[api(type = "TerritoryWarReport", field = "territorywarreport")]
+    #[api(type = "TerritoryWarReport", field = "territorywarreport", requires_id)]
     TerritoryWarReport,
 
-    #[api(type = "BTreeMap<i32, Item>", field = "items")]
+    #[api(
+        type = "BTreeMap<ItemId, Item<'a>>",
+        field = "items",
+        with = "null_is_empty_btree_map",
+        parallel
+    )]
     Items,
+
+    #[api(
+        type = "BTreeMap<Stock, StockListing>",
+        field = "stocks",
+        with = "null_is_empty_btree_map"
+    )]
+    Stocks,
+
+    #[api(
+        type = "BTreeMap<CourseId, Course>",
+        field = "education",
+        with = "null_is_empty_btree_map"
+    )]
+    Education,
+
+    #[api(
+        type = "BTreeMap<user::Honor, Honor>",
+        field = "honors",
+        with = "null_is_empty_btree_map"
+    )]
+    Honors,
+
+    #[api(
+        type = "BTreeMap<user::Medal, Medal>",
+        field = "medals",
+        with = "null_is_empty_btree_map"
+    )]
+    Medals,
+
+    #[api(
+        type = "BTreeMap<i16, Gym>",
+        field = "gyms",
+        with = "null_is_empty_btree_map"
+    )]
+    Gyms,
+
+    #[api(
+        type = "BTreeMap<PropertyType, Property>",
+        field = "properties",
+        with = "null_is_empty_btree_map"
+    )]
+    Properties,
+
+    #[api(
+        type = "BTreeMap<i32, RankedWar<'a>>",
+        field = "rankedwars",
+        with = "null_is_empty_btree_map"
+    )]
+    RankedWars,
+
+    #[api(type = "RankedWarReport", field = "rankedwarreport", requires_id)]
+    RankedWarReport,
+
+    #[api(
+        type = "BTreeMap<i32, Raid>",
+        field = "raids",
+        with = "null_is_empty_btree_map"
+    )]
+    Raids,
+
+    #[api(type = "BankRates", field = "bank")]
+    Bank,
+
+    #[api(
+        type = "BTreeMap<i16, PokerTable>",
+        field = "pokertables",
+        with = "null_is_empty_btree_map"
+    )]
+    PokerTables,
+
+    #[api(
+        type = "BTreeMap<i16, CityShop>",
+        field = "cityshops",
+        with = "null_is_empty_btree_map"
+    )]
+    CityShops,
+
+    #[api(type = "Calendar", field = "calendar")]
+    Calendar,
+
+    #[api(type = "ItemDetails<'a>", field = "itemdetails", requires_id)]
+    ItemDetails,
 }
 
 pub type Selection = TornSelection;
@@ -64,15 +161,6 @@ pub enum Competition {
     Unkown(String),
 }
 
-fn decode_territory_wars<'de, D>(deserializer: D) -> Result<HashMap<String, TerritoryWar>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let map: Option<_> = serde::Deserialize::deserialize(deserializer)?;
-
-    Ok(map.unwrap_or_default())
-}
-
 fn decode_competition<'de, D>(deserializer: D) -> Result<Option<Competition>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -134,11 +222,177 @@ where
     deserializer.deserialize_option(CompetitionVisitor)
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalendarEvent {
+    pub title: String,
+    pub description: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end: DateTime<Utc>,
+}
+
+impl CalendarEvent {
+    pub fn is_running(&self, at: DateTime<Utc>) -> bool {
+        self.start <= at && at < self.end
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Calendar {
+    pub competitions: Vec<CalendarEvent>,
+    pub events: Vec<CalendarEvent>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl Calendar {
+    /// The competition running at `at`, e.g. to decide whether to request
+    /// [`TornSelection::Competition`].
+    pub fn competition_at(&self, at: DateTime<Utc>) -> Option<&CalendarEvent> {
+        self.competitions.iter().find(|c| c.is_running(at))
+    }
+
+    /// Competitions and events which haven't started at `at`, soonest first.
+    pub fn upcoming(&self, at: DateTime<Utc>) -> Vec<&CalendarEvent> {
+        let mut upcoming: Vec<_> = self
+            .competitions
+            .iter()
+            .chain(&self.events)
+            .filter(|e| e.start > at)
+            .collect();
+        upcoming.sort_by_key(|e| e.start);
+        upcoming
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Honor {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub honor_type: i16,
+    #[serde(default)]
+    pub circulation: i32,
+    #[serde(default)]
+    pub rarity: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Medal {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub medal_type: String,
+    #[serde(default)]
+    pub circulation: i32,
+    #[serde(default)]
+    pub rarity: Option<String>,
+}
+
+/// Interest rates of bank investments by their duration, in percent.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct BankRates {
+    #[serde(rename = "1w", deserialize_with = "de_util::string_f64")]
+    pub one_week: f64,
+    #[serde(rename = "2w", deserialize_with = "de_util::string_f64")]
+    pub two_weeks: f64,
+    #[serde(rename = "1m", deserialize_with = "de_util::string_f64")]
+    pub one_month: f64,
+    #[serde(rename = "2m", deserialize_with = "de_util::string_f64")]
+    pub two_months: f64,
+    #[serde(rename = "3m", deserialize_with = "de_util::string_f64")]
+    pub three_months: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PokerTable {
+    pub name: String,
+    pub big_blind: i64,
+    pub small_blind: i64,
+    /// Seconds per turn.
+    pub speed: i16,
+    pub current_players: i16,
+    pub maximum_players: i16,
+}
+
+impl PokerTable {
+    pub fn free_seats(&self) -> i16 {
+        (self.maximum_players - self.current_players).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShopItem {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub price: i64,
+    pub in_stock: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CityShop {
+    pub name: String,
+    #[serde(default, deserialize_with = "de_util::empty_array_is_empty_btree_map")]
+    pub inventory: BTreeMap<ItemId, ShopItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GymStat {
+    Strength,
+    Speed,
+    Defense,
+    Dexterity,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Gym {
+    pub name: String,
+    pub stage: i16,
+    pub cost: i64,
+    /// Energy used per train.
+    pub energy: i16,
+    /// The gains of the stats in tenths, zero if the gym doesn't train the stat.
+    pub strength: i16,
+    pub speed: i16,
+    pub defense: i16,
+    pub dexterity: i16,
+    #[serde(default)]
+    pub note: String,
+}
+
+impl Gym {
+    /// The gym's multiplier for the stat, e.g. `2.0`, or `None` if it can't be trained here.
+    pub fn gain(&self, stat: GymStat) -> Option<f64> {
+        let tenths = match stat {
+            GymStat::Strength => self.strength,
+            GymStat::Speed => self.speed,
+            GymStat::Defense => self.defense,
+            GymStat::Dexterity => self.dexterity,
+        };
+        (tenths > 0).then(|| tenths as f64 / 10.0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Property {
+    pub name: String,
+    pub cost: i64,
+    pub happy: i32,
+    pub upkeep: i64,
+    #[serde(default, deserialize_with = "de_util::null_is_empty_vec")]
+    pub upgrades_available: Vec<PropertyUpgrade>,
+    #[serde(default, deserialize_with = "de_util::null_is_empty_vec")]
+    pub staff_available: Vec<PropertyStaff>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct TerritoryWar {
     pub territory_war_id: i32,
-    pub assaulting_faction: i32,
-    pub defending_faction: i32,
+    pub assaulting_faction: FactionId,
+    pub defending_faction: FactionId,
 
     #[serde(with = "chrono::serde::ts_seconds")]
     pub started: DateTime<Utc>,
@@ -157,8 +411,19 @@ pub struct Racket {
     #[serde(with = "chrono::serde::ts_seconds")]
     pub changed: DateTime<Utc>,
 
-    #[serde(rename = "faction")]
-    pub faction_id: Option<i32>,
+    #[serde(
+        rename = "faction",
+        default,
+        deserialize_with = "de_util::zero_is_none"
+    )]
+    pub faction_id: Option<FactionId>,
+}
+
+impl Racket {
+    /// The reward paid out each day, e.g. `10x Xanax daily` as an item reward.
+    pub fn daily_reward(&self) -> BenefitReward {
+        BenefitReward::parse(self.reward.trim_end().trim_end_matches(" daily"))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -167,20 +432,23 @@ pub struct Territory {
     pub size: i16,
     pub slots: i16,
     pub daily_respect: i16,
-    pub faction: i32,
+    #[serde(deserialize_with = "de_util::zero_is_none")]
+    pub faction: Option<FactionId>,
+
+    #[cfg(feature = "decimal")]
+    #[serde(deserialize_with = "de_util::string_decimal")]
+    pub coordinate_x: rust_decimal::Decimal,
+
+    #[cfg(feature = "decimal")]
+    #[serde(deserialize_with = "de_util::string_decimal")]
+    pub coordinate_y: rust_decimal::Decimal,
 
+    #[serde(default, deserialize_with = "de_util::null_is_empty_vec")]
     pub neighbors: Vec<String>,
     pub war: Option<TerritoryWar>,
     pub racket: Option<Racket>,
 }
 
-fn decode_territory<'de, D>(deserializer: D) -> Result<HashMap<String, Territory>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
-}
-
 #[derive(Clone, Debug, Deserialize)]
 pub struct TerritoryWarReportTerritory {
     pub name: String,
@@ -226,10 +494,84 @@ pub struct TerritoryWarReportFaction {
 pub struct TerritoryWarReport {
     pub territory: TerritoryWarReportTerritory,
     pub war: TerritoryWarReportWar,
-    pub factions: HashMap<i32, TerritoryWarReportFaction>,
+    #[serde(deserialize_with = "de_util::id_keyed_map")]
+    pub factions: HashMap<FactionId, TerritoryWarReportFaction>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RankedWarRewardItem {
+    pub name: String,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RankedWarRewards {
+    pub respect: i32,
+    pub points: i32,
+    #[serde(default, deserialize_with = "de_util::id_keyed_map")]
+    pub items: BTreeMap<ItemId, RankedWarRewardItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RankedWarReportMember {
+    pub name: String,
+    pub level: i16,
+    pub attacks: i32,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RankedWarReportFaction {
+    pub name: String,
+    pub score: i32,
+    pub attacks: i32,
+    pub rewards: RankedWarRewards,
+    #[serde(default, deserialize_with = "de_util::id_keyed_map")]
+    pub members: BTreeMap<UserId, RankedWarReportMember>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RankedWarReportWar {
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end: DateTime<Utc>,
+    pub winner: FactionId,
+    #[serde(default)]
+    pub forfeit: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RankedWarReport {
+    #[serde(deserialize_with = "de_util::id_keyed_map")]
+    pub factions: BTreeMap<FactionId, RankedWarReportFaction>,
+    pub war: RankedWarReportWar,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl RankedWarReport {
+    pub fn winner(&self) -> Option<&RankedWarReportFaction> {
+        self.factions.get(&self.war.winner)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Raid {
+    pub assaulting_faction: FactionId,
+    pub defending_faction: FactionId,
+    pub assaulting_score: f64,
+    pub defending_score: f64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub started: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 pub enum ItemType {
     Primary,
@@ -260,6 +602,7 @@ pub enum ItemType {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 //Missing hand to hand because it is not possible as a weapon
 pub enum WeaponType {
@@ -277,7 +620,7 @@ pub enum WeaponType {
     Shotgun,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
 pub struct Item<'a> {
     pub name: String,
     pub description: String,
@@ -297,6 +640,120 @@ pub struct Item<'a> {
     #[serde(deserialize_with = "de_util::zero_is_none")]
     pub circulation: Option<u32>,
     pub image: String,
+    /// Coverage of the body parts by name, only sent for defensive items.
+    #[serde(default, deserialize_with = "de_util::empty_array_is_empty_btree_map")]
+    pub coverage: BTreeMap<String, f64>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl<'a> Item<'a> {
+    pub fn is_weapon(&self) -> bool {
+        self.weapon_type.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ItemBonus {
+    #[serde(rename = "bonus")]
+    pub name: String,
+    pub description: String,
+    pub value: f64,
+}
+
+fn item_bonuses<'de, D>(deserializer: D) -> Result<Vec<ItemBonus>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    // bonuses are sent as an object with numeric keys, or as an empty array
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bonuses {
+        List(Vec<ItemBonus>),
+        Map(BTreeMap<String, ItemBonus>),
+    }
+
+    Ok(match Option::<Bonuses>::deserialize(deserializer)? {
+        Some(Bonuses::List(bonuses)) => bonuses,
+        Some(Bonuses::Map(bonuses)) => bonuses.into_values().collect(),
+        None => Vec::new(),
+    })
+}
+
+/// The glow of a unique weapon or armour piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ItemRarity {
+    #[serde(rename = "None")]
+    Plain,
+    Yellow,
+    Orange,
+    Red,
+    #[serde(other)]
+    Unknown,
+}
+
+/// A unique instance of an item, looked up by its UID.
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct ItemDetails<'a> {
+    #[serde(rename = "ID")]
+    pub item_id: ItemId,
+    #[serde(rename = "UID")]
+    pub uid: i64,
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub item_type: ItemType,
+    pub damage: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub armor: Option<f64>,
+    /// The quality of the item in percent.
+    #[serde(default)]
+    pub quality: Option<f64>,
+    #[serde(default)]
+    pub rarity: Option<ItemRarity>,
+    #[serde(default, deserialize_with = "item_bonuses")]
+    pub bonuses: Vec<ItemBonus>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
+}
+
+/// The details of a unique item joined with its entry in the items catalogue.
+#[derive(Debug, Clone)]
+pub struct ItemInstance {
+    pub uid: i64,
+    pub item_id: ItemId,
+    pub item: ItemOwned,
+    pub damage: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub armor: Option<f64>,
+    pub quality: Option<f64>,
+    pub rarity: Option<ItemRarity>,
+    pub bonuses: Vec<ItemBonus>,
+}
+
+impl ItemInstance {
+    pub fn new(details: ItemDetails<'_>, item: ItemOwned) -> Self {
+        Self {
+            uid: details.uid,
+            item_id: details.item_id,
+            item,
+            damage: details.damage,
+            accuracy: details.accuracy,
+            armor: details.armor,
+            quality: details.quality,
+            rarity: details.rarity,
+            bonuses: details.bonuses,
+        }
+    }
+
+    /// Look up the item of `details` in `catalogue`, as returned by [`TornSelection::Items`].
+    pub fn join(details: ItemDetails<'_>, catalogue: &BTreeMap<ItemId, ItemOwned>) -> Option<Self> {
+        let item = catalogue.get(&details.item_id)?.clone();
+        Some(Self::new(details, item))
+    }
 }
 
 #[cfg(test)]
@@ -304,6 +761,73 @@ mod tests {
     use super::*;
     use crate::tests::{async_test, setup, Client, ClientTrait};
 
+    #[test]
+    fn item_instance() {
+        use crate::IntoOwned;
+
+        let value = serde_json::json!({
+            "ID": 1,
+            "UID": 123456,
+            "name": "Hammer",
+            "type": "Melee",
+            "damage": 12.5,
+            "accuracy": 50.1,
+            "armor": null,
+            "quality": 87.5,
+            "rarity": "Orange",
+            "bonuses": { "0": { "bonus": "Stun", "description": "15% chance", "value": 15 } },
+        });
+        let details = ItemDetails::deserialize(&value).unwrap();
+        assert_eq!(details.bonuses[0].name, "Stun");
+
+        let item = Item::deserialize(&serde_json::json!({
+            "name": "Hammer",
+            "description": "A small, lightweight tool.",
+            "effect": "",
+            "requirement": "",
+            "type": "Melee",
+            "weapon_type": "Clubbing",
+            "buy_price": 75,
+            "sell_price": 50,
+            "market_value": 60,
+            "circulation": 1000,
+            "image": "https://www.torn.com/images/items/1/large.png",
+        }))
+        .unwrap()
+        .into_owned();
+        let catalogue = BTreeMap::from([(ItemId(1), item)]);
+
+        let instance = ItemInstance::join(details, &catalogue).unwrap();
+        assert_eq!(instance.uid, 123456);
+        assert_eq!(instance.item.weapon_type, Some(WeaponType::Clubbing));
+        assert_eq!(instance.bonuses.len(), 1);
+        assert_eq!(instance.quality, Some(87.5));
+        assert_eq!(instance.rarity, Some(ItemRarity::Orange));
+    }
+
+    #[test]
+    fn armor_coverage() {
+        let value = serde_json::json!({
+            "name": "Riot Helmet",
+            "description": "A helmet.",
+            "effect": "",
+            "requirement": "",
+            "type": "Defensive",
+            "weapon_type": null,
+            "buy_price": 0,
+            "sell_price": 0,
+            "market_value": 12000,
+            "circulation": 50000,
+            "image": "https://www.torn.com/images/items/651/large.png",
+            "coverage": { "Full Body Coverage": 11.71, "Head Coverage": 100.0 },
+        });
+        let item = Item::deserialize(&value).unwrap();
+
+        assert!(!item.is_weapon());
+        assert_eq!(item.buy_price, None);
+        assert_eq!(item.coverage["Head Coverage"], 100.0);
+    }
+
     #[async_test]
     async fn competition() {
         let key = setup();
@@ -325,6 +849,21 @@ mod tests {
         response.rackets().unwrap();
     }
 
+    #[async_test]
+    async fn calendar() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .torn(|b| b.selections([Selection::Calendar]))
+            .await
+            .unwrap();
+
+        let calendar = response.calendar().unwrap();
+        assert!(!calendar.competitions.is_empty());
+        calendar.upcoming(Utc::now());
+    }
+
     #[async_test]
     async fn territory() {
         let key = setup();
@@ -407,11 +946,325 @@ mod tests {
 
         let response = Client::default()
             .torn_api(key)
-            .torn(|b| b.selections([Selection::Items]).id(837))
+            .torn(|b| b.selections([Selection::Items]).id(ItemId(837)))
             .await
             .unwrap();
 
         let item_list = response.items().unwrap();
-        assert!(item_list.contains_key(&837));
+        assert!(item_list.contains_key(&ItemId(837)));
+    }
+
+    #[async_test]
+    async fn stocks() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .torn(|b| b.selections([Selection::Stocks]))
+            .await
+            .unwrap();
+
+        let stocks = response.stocks().unwrap();
+        assert!(stocks.values().any(|s| s.acronym == "TSB"));
+    }
+
+    #[async_test]
+    async fn awards() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .torn(|b| b.selections([Selection::Education, Selection::Honors, Selection::Medals]))
+            .await
+            .unwrap();
+
+        let education = response.education().unwrap();
+        assert!(education.values().any(|c| !c.prerequisites.is_empty()));
+        assert!(!response.honors().unwrap().is_empty());
+        assert!(!response.medals().unwrap().is_empty());
+    }
+
+    #[async_test]
+    async fn gyms_and_properties() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .torn(|b| b.selections([Selection::Gyms, Selection::Properties]))
+            .await
+            .unwrap();
+
+        assert!(!response.gyms().unwrap().is_empty());
+        assert!(!response.properties().unwrap().is_empty());
+    }
+
+    #[async_test]
+    async fn wars() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .torn(|b| b.selections([Selection::RankedWars, Selection::Raids]))
+            .await
+            .unwrap();
+
+        response.ranked_wars().unwrap();
+        response.raids().unwrap();
+    }
+
+    #[test]
+    fn honor_names() {
+        use crate::ApiCategoryResponse;
+
+        let response = Response::from_response(
+            crate::ApiResponse::from_value(serde_json::json!({
+                "honors": {
+                    "2": {
+                        "name": "Finisher",
+                        "description": "Achieve a finishing hit",
+                        "type": 1,
+                        "circulation": 100,
+                        "rarity": "Uncommon"
+                    }
+                },
+                "medals": null
+            }))
+            .unwrap(),
+        );
+
+        let honors = response.honors().unwrap();
+        assert_eq!(
+            user::Honor::by_name(&honors, "Finisher"),
+            Some(user::Honor(2))
+        );
+        assert_eq!(user::Honor::by_name(&honors, "Missing"), None);
+        assert!(response.medals().unwrap().is_empty());
+    }
+
+    #[test]
+    fn ranked_war_report() {
+        let report: RankedWarReport = serde_json::from_value(serde_json::json!({
+            "factions": {
+                "7": {
+                    "name": "Seven",
+                    "score": 3500,
+                    "attacks": 400,
+                    "rewards": {
+                        "respect": 5000,
+                        "points": 100,
+                        "items": { "370": { "name": "Box of Medical Supplies", "quantity": 5 } },
+                    },
+                    "members": {
+                        "1": { "name": "Chedburn", "level": 100, "attacks": 40, "score": 350.5 },
+                    },
+                },
+                "9": {
+                    "name": "Nine",
+                    "score": 500,
+                    "attacks": 60,
+                    "rewards": { "respect": 100, "points": 0, "items": [] },
+                    "members": [],
+                },
+            },
+            "war": { "start": 1_700_000_000, "end": 1_700_100_000, "winner": 7, "forfeit": false },
+        }))
+        .unwrap();
+
+        let winner = report.winner().unwrap();
+        assert_eq!(winner.name, "Seven");
+        assert_eq!(winner.rewards.items[&ItemId(370)].quantity, 5);
+        assert_eq!(winner.members[&UserId(1)].score, 350.5);
+        assert!(report.factions[&FactionId(9)].rewards.items.is_empty());
+    }
+
+    #[async_test]
+    async fn economy() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .torn(|b| {
+                b.selections([
+                    Selection::Bank,
+                    Selection::PokerTables,
+                    Selection::CityShops,
+                ])
+            })
+            .await
+            .unwrap();
+
+        response.bank().unwrap();
+        response.poker_tables().unwrap();
+        assert!(!response.city_shops().unwrap().is_empty());
+    }
+
+    #[test]
+    fn economy_offline() {
+        let rates: BankRates = serde_json::from_value(serde_json::json!({
+            "1w": "0.64", "2w": "0.71", "1m": 0.82, "2m": "0.91", "3m": "1.02",
+        }))
+        .unwrap();
+        assert_eq!(rates.three_months, 1.02);
+
+        let shops: BTreeMap<i16, CityShop> = serde_json::from_value(serde_json::json!({
+            "1": {
+                "name": "Big Al's Gun Shop",
+                "inventory": {
+                    "1": { "name": "Hammer", "type": "Melee", "price": 75, "in_stock": 1000 },
+                },
+            },
+            "2": { "name": "Closed Shop", "inventory": [] },
+        }))
+        .unwrap();
+        assert_eq!(shops[&1].inventory[&ItemId(1)].price, 75);
+        assert!(shops[&2].inventory.is_empty());
+    }
+
+    #[test]
+    fn racket_reward() {
+        let racket = |reward: &str| {
+            serde_json::from_value::<Racket>(serde_json::json!({
+                "name": "Gambling Den",
+                "level": 3,
+                "reward": reward,
+                "created": 1_600_000_000,
+                "changed": 1_650_000_000,
+            }))
+            .unwrap()
+        };
+
+        let xanax = racket("10x Xanax daily");
+        assert_eq!(xanax.faction_id, None);
+        assert_eq!(
+            xanax.daily_reward(),
+            BenefitReward::Item {
+                quantity: 10,
+                name: "Xanax".to_owned()
+            }
+        );
+        assert_eq!(
+            racket("$5,000,000 daily").daily_reward(),
+            BenefitReward::Money(5_000_000)
+        );
+    }
+
+    #[test]
+    fn gym_gains() {
+        let gym: Gym = serde_json::from_value(serde_json::json!({
+            "name": "Premier Fitness",
+            "stage": 1,
+            "cost": 10,
+            "energy": 5,
+            "strength": 20,
+            "speed": 20,
+            "defense": 20,
+            "dexterity": 0,
+            "note": "",
+        }))
+        .unwrap();
+
+        assert_eq!(gym.gain(GymStat::Strength), Some(2.0));
+        assert_eq!(gym.gain(GymStat::Dexterity), None);
+    }
+
+    #[test]
+    fn course() {
+        let course: Course = serde_json::from_value(serde_json::json!({
+            "name": "Introduction to Business",
+            "code": "BUS1100",
+            "description": "The basics of business.",
+            "money": 5000,
+            "tier": 1,
+            "duration": 604_800,
+            "results": { "perk": ["+ 10% company training"] },
+            "prerequisites": [],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            course.duration,
+            std::time::Duration::from_secs(7 * 24 * 3600)
+        );
+        assert_eq!(course.results["perk"].len(), 1);
+        assert!(course.prerequisites.is_empty());
+    }
+
+    #[test]
+    fn course_branches() {
+        let course = |name: &str, prerequisites: &[i64]| {
+            serde_json::json!({
+                "name": name,
+                "code": "",
+                "description": "",
+                "money": 0,
+                "tier": 1,
+                "duration": 604_800,
+                "results": [],
+                "prerequisites": prerequisites,
+            })
+        };
+        let courses: BTreeMap<CourseId, Course> = serde_json::from_value(serde_json::json!({
+            "1": course("Introduction to Law", &[]),
+            "2": course("Criminal Law", &[1]),
+            "3": course("Bachelor of Law", &[1, 2]),
+            "4": course("Introduction to History", &[]),
+        }))
+        .unwrap();
+
+        assert_eq!(
+            crate::common::EducationBranch::of(CourseId(2), &courses),
+            Some(crate::common::EducationBranch::Law)
+        );
+        assert_eq!(
+            crate::common::EducationBranch::of(CourseId(4), &courses),
+            None
+        );
+        assert_eq!(
+            crate::common::EducationBranch::Law
+                .courses(&courses)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [CourseId(1), CourseId(2), CourseId(3)]
+        );
+
+        let education: user::Education = serde_json::from_value(serde_json::json!({
+            "education_current": 2,
+            "education_timeleft": 3600,
+            "education_completed": [1],
+        }))
+        .unwrap();
+        assert_eq!(education.current, Some(CourseId(2)));
+        let remaining: Vec<_> = education.remaining(&courses).map(|(id, _)| id).collect();
+        assert_eq!(remaining, [CourseId(2), CourseId(3), CourseId(4)]);
+    }
+
+    #[test]
+    fn properties_selection() {
+        let properties: BTreeMap<PropertyType, Property> =
+            serde_json::from_value(serde_json::json!({
+                "13": {
+                    "name": "Castle",
+                    "cost": 1_000_000_000,
+                    "happy": 4225,
+                    "upkeep": 50_000,
+                    "upgrades_available": ["Superior interior", "Large vault", "Airstrip"],
+                    "staff_available": ["Maid", "Pilot"],
+                },
+            }))
+            .unwrap();
+
+        let castle = &properties[&PropertyType::Castle];
+        assert_eq!(
+            castle.upgrades_available,
+            [
+                PropertyUpgrade::Interior("Superior interior".to_owned()),
+                PropertyUpgrade::Vault("Large vault".to_owned()),
+                PropertyUpgrade::Airstrip,
+            ]
+        );
+        assert_eq!(
+            castle.staff_available,
+            [PropertyStaff::Maid, PropertyStaff::Pilot]
+        );
     }
 }
@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use thiserror::Error;
+
+use crate::{ApiResponse, FactionId, RawResponse, ResponseError, UserId};
+
+const BASE_URL: &str = "https://www.tornstats.com/api/v2";
+
+#[derive(Error, Debug)]
+pub enum TornStatsError<C>
+where
+    C: std::error::Error,
+{
+    #[error(transparent)]
+    Client(C),
+
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+
+    #[error("TornStats error: {0}")]
+    Api(String),
+}
+
+// TornStats reports stats which weren't spied as "N/A"
+fn optional_stat<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(serde_json::Value::deserialize(deserializer)?.as_i64())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Spy {
+    pub player_name: String,
+    pub player_id: UserId,
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, deserialize_with = "optional_stat")]
+    pub strength: Option<i64>,
+    #[serde(default, deserialize_with = "optional_stat")]
+    pub defense: Option<i64>,
+    #[serde(default, deserialize_with = "optional_stat")]
+    pub speed: Option<i64>,
+    #[serde(default, deserialize_with = "optional_stat")]
+    pub dexterity: Option<i64>,
+    #[serde(default, deserialize_with = "optional_stat")]
+    pub total: Option<i64>,
+    /// Battle stat score of the target, estimated from the spy.
+    pub target_score: Option<f64>,
+    pub your_score: Option<f64>,
+    pub fair_fight_bonus: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpyEntry {
+    Found(Spy),
+    Missing {},
+}
+
+impl From<SpyEntry> for Option<Spy> {
+    fn from(entry: SpyEntry) -> Self {
+        match entry {
+            SpyEntry::Found(spy) => Some(spy),
+            SpyEntry::Missing {} => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FactionMember {
+    pub name: String,
+    pub level: i16,
+    #[serde(default, deserialize_with = "deserialize_spy")]
+    pub spy: Option<Spy>,
+}
+
+fn deserialize_spy<'de, D>(deserializer: D) -> Result<Option<Spy>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<SpyEntry>::deserialize(deserializer)?.and_then(Into::into))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FactionSpies {
+    pub name: String,
+    pub members: BTreeMap<UserId, FactionMember>,
+}
+
+fn url(key: &str, path: &str) -> String {
+    format!("{BASE_URL}/{key}/{path}")
+}
+
+fn decode<T, E>(raw: &RawResponse) -> Result<T, TornStatsError<E>>
+where
+    T: DeserializeOwned,
+    E: std::error::Error,
+{
+    #[derive(Deserialize)]
+    struct Status {
+        status: bool,
+        #[serde(default)]
+        message: String,
+    }
+
+    ApiResponse::check_json(raw)?;
+
+    let status: Status = serde_json::from_slice(&raw.body).map_err(ResponseError::from)?;
+    if !status.status {
+        return Err(TornStatsError::Api(status.message));
+    }
+
+    Ok(serde_json::from_slice(&raw.body).map_err(ResponseError::from)?)
+}
+
+#[derive(Deserialize)]
+struct SpyResponse {
+    spy: SpyEntry,
+}
+
+#[derive(Deserialize)]
+struct FactionSpiesResponse {
+    faction: FactionSpies,
+}
+
+pub mod send {
+    use super::*;
+    use crate::send::ApiClient;
+
+    /// A client for the [TornStats](https://www.tornstats.com) API, using the same backends as
+    /// the Torn API.
+    pub struct TornStatsProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        client: &'a C,
+        key: String,
+    }
+
+    impl<'a, C> TornStatsProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        pub fn new<S>(client: &'a C, key: S) -> Self
+        where
+            S: ToString,
+        {
+            Self {
+                client,
+                key: key.to_string(),
+            }
+        }
+
+        async fn get<T>(&self, path: String) -> Result<T, TornStatsError<C::Error>>
+        where
+            T: DeserializeOwned,
+        {
+            let raw = self
+                .client
+                .request(url(&self.key, &path))
+                .await
+                .map_err(TornStatsError::Client)?;
+            decode(&raw)
+        }
+
+        /// The latest spy on `user` shared with the key owner, if there is one.
+        pub async fn spy(&self, user: UserId) -> Result<Option<Spy>, TornStatsError<C::Error>> {
+            let response: SpyResponse = self.get(format!("spy/user/{user}")).await?;
+            Ok(response.spy.into())
+        }
+
+        /// The members of `faction` along with the latest spies shared with the key owner.
+        pub async fn faction_spies(
+            &self,
+            faction: FactionId,
+        ) -> Result<FactionSpies, TornStatsError<C::Error>> {
+            let response: FactionSpiesResponse = self.get(format!("spy/faction/{faction}")).await?;
+            Ok(response.faction)
+        }
+    }
+}
+
+pub mod local {
+    use super::*;
+    use crate::local::ApiClient;
+
+    /// A client for the [TornStats](https://www.tornstats.com) API, using the same backends as
+    /// the Torn API.
+    pub struct TornStatsProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        client: &'a C,
+        key: String,
+    }
+
+    impl<'a, C> TornStatsProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        pub fn new<S>(client: &'a C, key: S) -> Self
+        where
+            S: ToString,
+        {
+            Self {
+                client,
+                key: key.to_string(),
+            }
+        }
+
+        async fn get<T>(&self, path: String) -> Result<T, TornStatsError<C::Error>>
+        where
+            T: DeserializeOwned,
+        {
+            let raw = self
+                .client
+                .request(url(&self.key, &path))
+                .await
+                .map_err(TornStatsError::Client)?;
+            decode(&raw)
+        }
+
+        /// The latest spy on `user` shared with the key owner, if there is one.
+        pub async fn spy(&self, user: UserId) -> Result<Option<Spy>, TornStatsError<C::Error>> {
+            let response: SpyResponse = self.get(format!("spy/user/{user}")).await?;
+            Ok(response.spy.into())
+        }
+
+        /// The members of `faction` along with the latest spies shared with the key owner.
+        pub async fn faction_spies(
+            &self,
+            faction: FactionId,
+        ) -> Result<FactionSpies, TornStatsError<C::Error>> {
+            let response: FactionSpiesResponse = self.get(format!("spy/faction/{faction}")).await?;
+            Ok(response.faction)
+        }
+    }
+}
+
+pub use send::TornStatsProvider;
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+
+    #[tokio::test]
+    async fn spies() {
+        let client = MockClient::new().respond_url(
+            "https://www.tornstats.com/api/v2/key/spy/user/1",
+            serde_json::json!({
+                "status": true,
+                "message": "Spy found.",
+                "spy": {
+                    "status": true,
+                    "player_name": "Chedburn",
+                    "player_id": 1,
+                    "timestamp": 1650000000,
+                    "strength": 1000,
+                    "defense": "N/A",
+                    "speed": 500,
+                    "dexterity": 250,
+                    "total": 1750,
+                    "target_score": 12.5,
+                    "your_score": 10.0,
+                    "fair_fight_bonus": 2.1,
+                },
+            }),
+        );
+        let spy = TornStatsProvider::new(&client, "key")
+            .spy(UserId(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(spy.player_id, UserId(1));
+        assert_eq!(spy.strength, Some(1000));
+        assert_eq!(spy.defense, None);
+
+        let client = MockClient::new().respond_url(
+            "https://www.tornstats.com/api/v2/key/spy/user/1",
+            serde_json::json!({ "status": true, "message": "", "spy": { "status": false } }),
+        );
+        let spy = local::TornStatsProvider::new(&client, "key")
+            .spy(UserId(1))
+            .await
+            .unwrap();
+        assert!(spy.is_none());
+
+        let client = MockClient::new().respond_url(
+            "https://www.tornstats.com/api/v2/key/spy/faction/1",
+            serde_json::json!({ "status": false, "message": "User not found." }),
+        );
+        let err = TornStatsProvider::new(&client, "key")
+            .faction_spies(FactionId(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TornStatsError::Api(message) if message == "User not found."));
+    }
+}
@@ -2,21 +2,34 @@ use serde::{
     de::{self, MapAccess, Visitor},
     Deserialize, Deserializer,
 };
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
 
-use torn_api_macros::{ApiCategory, IntoOwned};
+use chrono::{serde::ts_seconds, DateTime, Utc};
 
-use crate::de_util;
+use torn_api_macros::ApiCategory;
 
-pub use crate::common::{Attack, AttackFull, LastAction, Status};
+use crate::{
+    de_util::{self, empty_array_is_empty_btree_map, null_is_empty_vec},
+    into_owned::IntoOwned,
+    CompanyId, CourseId, FactionId, ItemId, UserId,
+};
+
+pub use crate::common::{
+    Attack, AttackFull, Country, Course, EducationBranch, LastAction, Position, Status,
+    TravelMethod,
+};
+pub use crate::stats::{BattleStat, BattleStats};
 
 #[derive(Debug, Clone, Copy, ApiCategory)]
 #[api(category = "user")]
 #[non_exhaustive]
 pub enum UserSelection {
-    #[api(type = "Basic", flatten)]
+    #[api(type = "Basic<'a>", flatten)]
     Basic,
-    #[api(type = "Profile", flatten)]
+    #[api(type = "Profile<'a>", flatten)]
     Profile,
     #[api(type = "Discord", field = "discord")]
     Discord,
@@ -24,118 +37,154 @@ pub enum UserSelection {
     PersonalStats,
     #[api(type = "CriminalRecord", field = "criminalrecord")]
     Crimes,
-    #[api(type = "BTreeMap<i32, Attack>", field = "attacks")]
+    #[api(
+        type = "BTreeMap<i64, Attack<'a>>",
+        field = "attacks",
+        with = "empty_array_is_empty_btree_map",
+        parallel
+    )]
     AttacksFull,
-    #[api(type = "BTreeMap<i32, AttackFull>", field = "attacks")]
+    #[api(
+        type = "BTreeMap<i64, AttackFull<'a>>",
+        field = "attacks",
+        with = "empty_array_is_empty_btree_map",
+        parallel
+    )]
     Attacks,
-    #[api(type = "HashMap<Icon, &str>", field = "icons")]
+    #[api(type = "HashMap<Icon, &'a str>", field = "icons")]
     Icons,
+    #[api(type = "Cooldowns", field = "cooldowns")]
+    Cooldowns,
+    #[api(type = "Education", flatten)]
+    Education,
+    #[api(type = "Travel", field = "travel")]
+    Travel,
+    #[api(
+        type = "Vec<Honor>",
+        field = "honors_awarded",
+        with = "null_is_empty_vec"
+    )]
+    Honors,
+    #[api(
+        type = "Vec<Medal>",
+        field = "medals_awarded",
+        with = "null_is_empty_vec"
+    )]
+    Medals,
+    #[api(type = "BattleStats", flatten)]
+    BattleStats,
+    #[api(
+        type = "Vec<Equipment<'a>>",
+        field = "equipment",
+        with = "null_is_empty_vec"
+    )]
+    Equipment,
+    #[api(
+        type = "BTreeMap<String, Event>",
+        field = "events",
+        with = "empty_array_is_empty_btree_map"
+    )]
+    Events,
+    /// Only the unseen events. Requesting this selection marks them as seen.
+    #[api(
+        type = "BTreeMap<String, Event>",
+        field = "events",
+        with = "empty_array_is_empty_btree_map"
+    )]
+    NewEvents,
+    #[api(
+        type = "BTreeMap<i64, Message>",
+        field = "messages",
+        with = "empty_array_is_empty_btree_map"
+    )]
+    Messages,
+    /// Only the unseen messages. Requesting this selection marks them as seen, though not as
+    /// read.
+    #[api(
+        type = "BTreeMap<i64, Message>",
+        field = "messages",
+        with = "empty_array_is_empty_btree_map"
+    )]
+    NewMessages,
 }
 
 pub type Selection = UserSelection;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub enum Gender {
     Male,
     Female,
     Enby,
+    Other(String),
+}
+
+impl<'de> Deserialize<'de> for Gender {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+
+        Ok(match raw.as_ref() {
+            "Male" => Self::Male,
+            "Female" => Self::Female,
+            "Enby" => Self::Enby,
+            other => {
+                de_util::fallback::<D::Error>(other, "gender")?;
+                Self::Other(raw.into_owned())
+            }
+        })
+    }
 }
 
 #[derive(Debug, IntoOwned)]
 pub struct Faction<'a> {
-    pub faction_id: i32,
+    pub faction_id: FactionId,
     pub faction_name: &'a str,
     pub days_in_faction: i16,
-    pub position: &'a str,
+    pub position: Position<&'a str>,
     pub faction_tag: Option<&'a str>,
 }
 
+impl<'a> Faction<'a> {
+    pub fn tag_or_name(&self) -> &'a str {
+        self.faction_tag
+            .filter(|tag| !tag.is_empty())
+            .unwrap_or(self.faction_name)
+    }
+}
+
 fn deserialize_faction<'de, D>(deserializer: D) -> Result<Option<Faction<'de>>, D::Error>
 where
     D: Deserializer<'de>,
 {
     #[derive(Deserialize)]
-    #[serde(rename_all = "snake_case")]
-    enum Field {
-        FactionId,
-        FactionName,
-        DaysInFaction,
-        Position,
-        FactionTag,
+    struct FactionDto<'a> {
+        #[serde(deserialize_with = "de_util::zero_is_none")]
+        faction_id: Option<FactionId>,
+        faction_name: &'a str,
+        days_in_faction: i16,
+        position: Position<&'a str>,
+        #[serde(borrow)]
+        faction_tag: Option<&'a str>,
     }
 
-    struct FactionVisitor;
-
-    impl<'de> Visitor<'de> for FactionVisitor {
-        type Value = Option<Faction<'de>>;
+    let dto = FactionDto::deserialize(deserializer)?;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("struct Faction")
-        }
-
-        fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
-        where
-            V: MapAccess<'de>,
-        {
-            let mut faction_id = None;
-            let mut faction_name = None;
-            let mut days_in_faction = None;
-            let mut position = None;
-            let mut faction_tag = None;
-
-            while let Some(key) = map.next_key()? {
-                match key {
-                    Field::FactionId => {
-                        faction_id = Some(map.next_value()?);
-                    }
-                    Field::FactionName => {
-                        faction_name = Some(map.next_value()?);
-                    }
-                    Field::DaysInFaction => {
-                        days_in_faction = Some(map.next_value()?);
-                    }
-                    Field::Position => {
-                        position = Some(map.next_value()?);
-                    }
-                    Field::FactionTag => {
-                        faction_tag = map.next_value()?;
-                    }
-                }
-            }
-            let faction_id = faction_id.ok_or_else(|| de::Error::missing_field("faction_id"))?;
-            let faction_name =
-                faction_name.ok_or_else(|| de::Error::missing_field("faction_name"))?;
-            let days_in_faction =
-                days_in_faction.ok_or_else(|| de::Error::missing_field("days_in_faction"))?;
-            let position = position.ok_or_else(|| de::Error::missing_field("position"))?;
-
-            if faction_id == 0 {
-                Ok(None)
-            } else {
-                Ok(Some(Faction {
-                    faction_id,
-                    faction_name,
-                    days_in_faction,
-                    position,
-                    faction_tag,
-                }))
-            }
-        }
-    }
-
-    const FIELDS: &[&str] = &[
-        "faction_id",
-        "faction_name",
-        "days_in_faction",
-        "position",
-        "faction_tag",
-    ];
-    deserializer.deserialize_struct("Faction", FIELDS, FactionVisitor)
+    Ok(dto.faction_id.map(|faction_id| Faction {
+        faction_id,
+        faction_name: dto.faction_name,
+        days_in_faction: dto.days_in_faction,
+        position: dto.position,
+        faction_tag: dto.faction_tag,
+    }))
 }
 
 #[derive(Debug, IntoOwned, Deserialize)]
 pub struct Basic<'a> {
-    pub player_id: i32,
+    pub player_id: UserId,
     pub name: &'a str,
     pub level: i16,
     pub gender: Gender,
@@ -143,22 +192,192 @@ pub struct Basic<'a> {
 }
 
 #[derive(Debug, Clone, IntoOwned, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[into_owned(identity)]
 pub struct Discord {
     #[serde(
         rename = "userID",
         deserialize_with = "de_util::empty_string_int_option"
     )]
-    pub user_id: Option<i32>,
+    pub user_id: Option<UserId>,
     #[serde(rename = "discordID", deserialize_with = "de_util::string_is_long")]
     pub discord_id: Option<i64>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LifeBar {
     pub current: i16,
     pub maximum: i16,
     pub increment: i16,
+    #[serde(deserialize_with = "de_util::seconds_duration")]
+    pub interval: Duration,
+    #[serde(rename = "ticktime", deserialize_with = "de_util::seconds_duration")]
+    pub tick_time: Duration,
+    #[serde(rename = "fulltime", deserialize_with = "de_util::seconds_duration")]
+    pub full_time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(from = "i16")]
+pub enum EquipmentSlot {
+    Primary,
+    Secondary,
+    Melee,
+    Body,
+    Temporary,
+    Head,
+    Legs,
+    Feet,
+    Hands,
+    Other(i16),
+}
+
+impl From<i16> for EquipmentSlot {
+    fn from(slot: i16) -> Self {
+        match slot {
+            1 => Self::Primary,
+            2 => Self::Secondary,
+            3 => Self::Melee,
+            4 => Self::Body,
+            5 => Self::Temporary,
+            6 => Self::Head,
+            7 => Self::Legs,
+            8 => Self::Feet,
+            9 => Self::Hands,
+            other => Self::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+pub struct Equipment<'a> {
+    #[serde(rename = "ID")]
+    pub item_id: ItemId,
+    #[serde(rename = "UID")]
+    pub uid: i64,
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub item_type: &'a str,
+    #[serde(rename = "equipped")]
+    pub slot: EquipmentSlot,
+    pub market_price: i64,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Event {
+    #[serde(with = "ts_seconds")]
+    #[cfg_attr(feature = "schemars", schemars(with = "i64"))]
+    pub timestamp: DateTime<Utc>,
+    /// The event's text, which contains html markup.
+    pub event: String,
+    #[serde(deserialize_with = "de_util::int_is_bool")]
+    pub seen: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Message {
+    #[serde(with = "ts_seconds")]
+    #[cfg_attr(feature = "schemars", schemars(with = "i64"))]
+    pub timestamp: DateTime<Utc>,
+    /// The sender, or `None` for messages sent by the game.
+    #[serde(rename = "ID", deserialize_with = "de_util::zero_is_none")]
+    pub sender_id: Option<UserId>,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    #[serde(deserialize_with = "de_util::int_is_bool")]
+    pub seen: bool,
+    #[serde(deserialize_with = "de_util::int_is_bool")]
+    pub read: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Cooldowns {
+    #[serde(deserialize_with = "de_util::seconds_duration")]
+    pub drug: Duration,
+    #[serde(deserialize_with = "de_util::seconds_duration")]
+    pub medical: Duration,
+    #[serde(deserialize_with = "de_util::seconds_duration")]
+    pub booster: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Education {
+    #[serde(
+        rename = "education_current",
+        deserialize_with = "de_util::zero_is_none"
+    )]
+    pub current: Option<CourseId>,
+    #[serde(
+        rename = "education_timeleft",
+        deserialize_with = "de_util::seconds_duration"
+    )]
+    pub time_left: Duration,
+    #[serde(
+        rename = "education_completed",
+        default,
+        deserialize_with = "de_util::null_is_empty_vec"
+    )]
+    pub completed: Vec<CourseId>,
+}
+
+impl Education {
+    pub fn has_completed(&self, course: CourseId) -> bool {
+        self.completed.contains(&course)
+    }
+
+    /// The courses of the torn `education` selection which weren't completed yet.
+    pub fn remaining<'c>(
+        &'c self,
+        courses: &'c BTreeMap<CourseId, Course>,
+    ) -> impl Iterator<Item = (CourseId, &'c Course)> {
+        courses
+            .iter()
+            .filter(|(id, _)| !self.has_completed(**id))
+            .map(|(id, course)| (*id, course))
+    }
+}
+
+fn travel_method<'de, D>(deserializer: D) -> Result<Option<TravelMethod>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(name) = Option::<std::borrow::Cow<'de, str>>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    match TravelMethod::from_name(&name) {
+        Some(method) => Ok(Some(method)),
+        None => {
+            de_util::fallback::<D::Error>(&name, "travel method")?;
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Debug, Clone, IntoOwned, Deserialize)]
+#[into_owned(identity)]
+pub struct Travel {
+    pub destination: Country,
+    #[serde(default, deserialize_with = "travel_method")]
+    pub method: Option<TravelMethod>,
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    pub departed: DateTime<Utc>,
+    #[serde(deserialize_with = "de_util::seconds_duration")]
+    pub time_left: Duration,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
@@ -180,6 +399,7 @@ pub enum EliminationTeam2022 {
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum EliminationTeam {
     Backstabbers,
@@ -192,7 +412,17 @@ pub enum EliminationTeam {
     CapsLockCrew,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Hand {
+    Rock,
+    Paper,
+    Scissors,
+}
+
 #[derive(Debug, Clone, IntoOwned)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[into_owned(identity)]
 pub enum Competition {
     Elimination {
@@ -204,7 +434,20 @@ pub enum Competition {
         score: i32,
         position: Option<i32>,
     },
-    Unknown,
+    EasterEggHunt {
+        eggs: i32,
+    },
+    Halloween {
+        treats: i32,
+    },
+    RockPaperScissors {
+        /// `None` until a side was chosen.
+        hand: Option<Hand>,
+    },
+    Other {
+        name: String,
+        data: serde_json::Value,
+    },
 }
 
 fn deserialize_comp<'de, D>(deserializer: D) -> Result<Option<Competition>, D::Error>
@@ -212,129 +455,108 @@ where
     D: Deserializer<'de>,
 {
     #[derive(Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    enum Field {
-        Name,
-        Score,
-        Team,
-        Attacks,
-        TeamName,
-        Position,
-        #[serde(other)]
-        Ignore,
+    struct EliminationDto<'a> {
+        score: i32,
+        attacks: i16,
+        team: &'a str,
     }
 
     #[derive(Deserialize)]
-    enum CompetitionName {
-        Elimination,
-        #[serde(rename = "Dog Tags")]
-        DogTags,
-        #[serde(other)]
-        Unknown,
+    struct DogTagsDto {
+        score: i32,
+        position: Option<i32>,
     }
 
-    struct CompetitionVisitor;
-
-    impl<'de> Visitor<'de> for CompetitionVisitor {
-        type Value = Option<Competition>;
-
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("struct Competition")
-        }
+    #[derive(Deserialize)]
+    struct EasterEggHuntDto {
+        eggs: i32,
+    }
 
-        fn visit_none<E>(self) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(None)
-        }
+    #[derive(Deserialize)]
+    struct HalloweenDto {
+        treats_collected_total: i32,
+    }
 
-        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
-        where
-            D: Deserializer<'de>,
-        {
-            deserializer.deserialize_map(self)
-        }
+    #[derive(Deserialize)]
+    struct RockPaperScissorsDto<'a> {
+        #[serde(default, borrow, deserialize_with = "de_util::empty_string_is_none")]
+        status: Option<&'a str>,
+    }
 
-        fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
-        where
-            V: MapAccess<'de>,
-        {
-            let mut team = None;
-            let mut score = None;
-            let mut attacks = None;
-            let mut name = None;
-            let mut position = None;
-
-            while let Some(key) = map.next_key()? {
-                match key {
-                    Field::Name => {
-                        name = Some(map.next_value()?);
-                    }
-                    Field::Score => {
-                        score = Some(map.next_value()?);
-                    }
-                    Field::Attacks => {
-                        attacks = Some(map.next_value()?);
-                    }
-                    Field::Position => {
-                        position = Some(map.next_value()?);
-                    }
-                    Field::Team => {
-                        let team_raw: &str = map.next_value()?;
-                        team = if team_raw.is_empty() {
-                            None
-                        } else {
-                            Some(match team_raw {
-                                "backstabbers" => EliminationTeam::Backstabbers,
-                                "cheese" => EliminationTeam::Cheese,
-                                "deaths-door" => EliminationTeam::DeathsDoor,
-                                "regular-human-people" => EliminationTeam::RegularHumanPeople,
-                                "flower-rangers" => EliminationTeam::FlowerRangers,
-                                "religious-extremists" => EliminationTeam::ReligiousExtremists,
-                                "hivemind" => EliminationTeam::Hivemind,
-                                "caps-lock-crew" => EliminationTeam::CapsLockCrew,
-                                _ => Err(de::Error::unknown_variant(team_raw, &[]))?,
-                            })
-                        }
-                    }
-                    _ => (),
-                }
+    let Some(data) = Option::<serde_json::Value>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    let name = data
+        .get("name")
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| de::Error::missing_field("name"))?
+        .to_owned();
+
+    match name.as_str() {
+        "Elimination" => {
+            let dto = EliminationDto::deserialize(&data).map_err(de::Error::custom)?;
+            if dto.team.is_empty() {
+                return Ok(None);
             }
 
-            let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
-
-            match name {
-                CompetitionName::Elimination => {
-                    if let Some(team) = team {
-                        let score = score.ok_or_else(|| de::Error::missing_field("score"))?;
-                        let attacks = attacks.ok_or_else(|| de::Error::missing_field("attacks"))?;
-                        Ok(Some(Competition::Elimination {
-                            team,
-                            score,
-                            attacks,
-                        }))
-                    } else {
-                        Ok(None)
-                    }
+            // teams change every year, so an unknown one shouldn't make the profile unusable
+            match EliminationTeam::deserialize(
+                de::value::BorrowedStrDeserializer::<de::value::Error>::new(dto.team),
+            ) {
+                Ok(team) => Ok(Some(Competition::Elimination {
+                    score: dto.score,
+                    attacks: dto.attacks,
+                    team,
+                })),
+                Err(_) => {
+                    de_util::fallback::<D::Error>(dto.team, "elimination team")?;
+                    Ok(Some(Competition::Other { name, data }))
                 }
-                CompetitionName::DogTags => {
-                    let score = score.ok_or_else(|| de::Error::missing_field("score"))?;
-                    let position = position.ok_or_else(|| de::Error::missing_field("position"))?;
-
-                    Ok(Some(Competition::DogTags { score, position }))
+            }
+        }
+        "Dog Tags" => {
+            let dto = DogTagsDto::deserialize(&data).map_err(de::Error::custom)?;
+            Ok(Some(Competition::DogTags {
+                score: dto.score,
+                position: dto.position,
+            }))
+        }
+        "Easter Egg Hunt" => {
+            let dto = EasterEggHuntDto::deserialize(&data).map_err(de::Error::custom)?;
+            Ok(Some(Competition::EasterEggHunt { eggs: dto.eggs }))
+        }
+        "Halloween" => {
+            let dto = HalloweenDto::deserialize(&data).map_err(de::Error::custom)?;
+            Ok(Some(Competition::Halloween {
+                treats: dto.treats_collected_total,
+            }))
+        }
+        "Rock, Paper, Scissors" => {
+            let dto = RockPaperScissorsDto::deserialize(&data).map_err(de::Error::custom)?;
+            let Some(status) = dto.status else {
+                return Ok(Some(Competition::RockPaperScissors { hand: None }));
+            };
+            match Hand::deserialize(de::value::BorrowedStrDeserializer::<de::value::Error>::new(
+                status,
+            )) {
+                Ok(hand) => Ok(Some(Competition::RockPaperScissors { hand: Some(hand) })),
+                Err(_) => {
+                    de_util::fallback::<D::Error>(status, "rock, paper or scissors")?;
+                    Ok(Some(Competition::Other { name, data }))
                 }
-                CompetitionName::Unknown => Ok(Some(Competition::Unknown)),
             }
         }
+        _ => {
+            de_util::fallback::<D::Error>(&name, "competition")?;
+            Ok(Some(Competition::Other { name, data }))
+        }
     }
-
-    deserializer.deserialize_option(CompetitionVisitor)
 }
 
 #[derive(Debug, IntoOwned, Deserialize)]
 pub struct Profile<'a> {
-    pub player_id: i32,
+    pub player_id: UserId,
     pub name: &'a str,
     pub rank: &'a str,
     pub level: i16,
@@ -355,6 +577,36 @@ pub struct Profile<'a> {
     pub revivable: bool,
 }
 
+impl Profile<'_> {
+    pub fn is_okay(&self) -> bool {
+        self.status.is_okay()
+    }
+
+    pub fn is_in_hospital(&self) -> bool {
+        self.status.is_in_hospital()
+    }
+
+    pub fn hospital_time_remaining(&self) -> Option<Duration> {
+        self.status.hospital_time_remaining()
+    }
+
+    pub fn is_in_jail(&self) -> bool {
+        self.status.is_in_jail()
+    }
+
+    pub fn is_travelling(&self) -> bool {
+        self.status.is_travelling()
+    }
+
+    pub fn is_abroad(&self) -> bool {
+        self.status.is_abroad()
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.last_action.is_online()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct PersonalStats {
     #[serde(rename = "attackswon")]
@@ -386,9 +638,58 @@ pub struct PersonalStats {
     pub days_been_donator: i16,
     #[serde(rename = "bestdamage")]
     pub best_damage: i32,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub unknown_fields: BTreeMap<String, serde_json::Value>,
 }
 
-#[derive(Deserialize)]
+impl PersonalStats {
+    /// Gains since `earlier`, e.g. a snapshot requested with [`crate::ApiRequestBuilder::at`].
+    pub fn diff(&self, earlier: &PersonalStats) -> PersonalStatsDelta {
+        PersonalStatsDelta {
+            attacks_won: i64::from(self.attacks_won) - i64::from(earlier.attacks_won),
+            attacks_lost: i64::from(self.attacks_lost) - i64::from(earlier.attacks_lost),
+            defends_won: i64::from(self.defends_won) - i64::from(earlier.defends_won),
+            defends_lost: i64::from(self.defends_lost) - i64::from(earlier.defends_lost),
+            stat_enhancers_used: i64::from(self.stat_enhancers_used)
+                - i64::from(earlier.stat_enhancers_used),
+            refills: i64::from(self.refills) - i64::from(earlier.refills),
+            drugs_used: i64::from(self.drugs_used) - i64::from(earlier.drugs_used),
+            xanax_taken: i64::from(self.xanax_taken) - i64::from(earlier.xanax_taken),
+            lsd_taken: i64::from(self.lsd_taken) - i64::from(earlier.lsd_taken),
+            net_worth: self.net_worth - earlier.net_worth,
+            cans_used: i64::from(self.cans_used) - i64::from(earlier.cans_used),
+            boosters_used: i64::from(self.boosters_used) - i64::from(earlier.boosters_used),
+            awards: i64::from(self.awards) - i64::from(earlier.awards),
+            elo: i64::from(self.elo) - i64::from(earlier.elo),
+            days_been_donator: i64::from(self.days_been_donator)
+                - i64::from(earlier.days_been_donator),
+            best_damage: i64::from(self.best_damage) - i64::from(earlier.best_damage),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PersonalStatsDelta {
+    pub attacks_won: i64,
+    pub attacks_lost: i64,
+    pub defends_won: i64,
+    pub defends_lost: i64,
+    pub stat_enhancers_used: i64,
+    pub refills: i64,
+    pub drugs_used: i64,
+    pub xanax_taken: i64,
+    pub lsd_taken: i64,
+    pub net_worth: i64,
+    pub cans_used: i64,
+    pub boosters_used: i64,
+    pub awards: i64,
+    pub elo: i64,
+    pub days_been_donator: i64,
+    pub best_damage: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Crimes1 {
     pub selling_illegal_products: i32,
     pub theft: i32,
@@ -401,7 +702,7 @@ pub struct Crimes1 {
     pub total: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Crimes2 {
     pub vandalism: i32,
     pub theft: i32,
@@ -417,17 +718,73 @@ pub struct Crimes2 {
     pub total: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum CriminalRecord {
     Crimes1(Crimes1),
     Crimes2(Crimes2),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(transparent)]
+pub struct Honor(pub i32);
+
+impl Honor {
+    pub fn id(self) -> i32 {
+        self.0
+    }
+
+    /// Find an honor by its name in the torn `honors` selection, so award trackers can refer to
+    /// honors by name rather than by a hard coded id.
+    #[cfg(feature = "torn")]
+    pub fn by_name(honors: &BTreeMap<Self, crate::torn::Honor>, name: &str) -> Option<Self> {
+        honors
+            .iter()
+            .find_map(|(id, honor)| (honor.name == name).then_some(*id))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(transparent)]
+pub struct Medal(pub i32);
+
+impl Medal {
+    pub fn id(self) -> i32 {
+        self.0
+    }
+
+    /// Find a medal by its name in the torn `medals` selection, see [`Honor::by_name`].
+    #[cfg(feature = "torn")]
+    pub fn by_name(medals: &BTreeMap<Self, crate::torn::Medal>, name: &str) -> Option<Self> {
+        medals
+            .iter()
+            .find_map(|(id, medal)| (medal.name == name).then_some(*id))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconCategory {
+    Status,
+    Profile,
+    Faction,
+    Job,
+    Finance,
+    Cooldown,
+    Activity,
+}
+
+/// An icon of the status line of a profile.
+///
+/// The catalogue is limited to icons whose id is confirmed: only those have a constant, a
+/// [`name`](Icon::name) and a [`category`](Icon::category). Every other icon still deserializes,
+/// can be built with [`Icon::from_id`] and is displayed as `Icon <id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Icon(i16);
 
 impl Icon {
+    pub const ONLINE: Self = Self(1);
+    pub const OFFLINE: Self = Self(2);
+    pub const DONATOR: Self = Self(3);
     pub const SUBSCRIBER: Self = Self(4);
     pub const LEVEL_100: Self = Self(5);
     pub const GENDER_MALE: Self = Self(6);
@@ -437,6 +794,10 @@ impl Icon {
     pub const PLAYER_COMMITTEE: Self = Self(10);
     pub const STAFF: Self = Self(11);
 
+    pub const HOSPITAL: Self = Self(15);
+    pub const JAIL: Self = Self(16);
+    pub const RACING: Self = Self(17);
+
     pub const COMPANY: Self = Self(27);
     pub const BANK_INVESTMENT: Self = Self(29);
     pub const PROPERTY_VAULT: Self = Self(32);
@@ -444,6 +805,8 @@ impl Icon {
 
     pub const DRUG_COOLDOWN: Self = Self(53);
 
+    pub const IDLE: Self = Self(62);
+
     pub const FEDDED: Self = Self(70);
     pub const TRAVELLING: Self = Self(71);
     pub const FACTION_LEADER: Self = Self(74);
@@ -451,6 +814,97 @@ impl Icon {
 
     pub const FACTION_RECRUIT: Self = Self(81);
     pub const STOCK_MARKET: Self = Self(84);
+
+    const KNOWN: &'static [(Self, &'static str, IconCategory)] = &[
+        (Self::ONLINE, "Online", IconCategory::Status),
+        (Self::OFFLINE, "Offline", IconCategory::Status),
+        (Self::DONATOR, "Donator", IconCategory::Profile),
+        (Self::SUBSCRIBER, "Subscriber", IconCategory::Profile),
+        (Self::LEVEL_100, "Level 100", IconCategory::Profile),
+        (Self::GENDER_MALE, "Male", IconCategory::Profile),
+        (Self::GENDER_FEMALE, "Female", IconCategory::Profile),
+        (
+            Self::MARITAL_STATUS,
+            "Marital status",
+            IconCategory::Profile,
+        ),
+        (
+            Self::FACTION_MEMBER,
+            "Faction member",
+            IconCategory::Faction,
+        ),
+        (
+            Self::PLAYER_COMMITTEE,
+            "Player committee",
+            IconCategory::Profile,
+        ),
+        (Self::STAFF, "Staff", IconCategory::Profile),
+        (Self::HOSPITAL, "Hospital", IconCategory::Status),
+        (Self::JAIL, "Jail", IconCategory::Status),
+        (Self::RACING, "Racing", IconCategory::Activity),
+        (Self::COMPANY, "Company", IconCategory::Job),
+        (
+            Self::BANK_INVESTMENT,
+            "Bank investment",
+            IconCategory::Finance,
+        ),
+        (
+            Self::PROPERTY_VAULT,
+            "Property vault",
+            IconCategory::Finance,
+        ),
+        (Self::DUKE_LOAN, "Duke loan", IconCategory::Finance),
+        (Self::DRUG_COOLDOWN, "Drug cooldown", IconCategory::Cooldown),
+        (Self::IDLE, "Idle", IconCategory::Status),
+        (Self::FEDDED, "Federal jail", IconCategory::Status),
+        (Self::TRAVELLING, "Traveling", IconCategory::Status),
+        (
+            Self::FACTION_LEADER,
+            "Faction leader",
+            IconCategory::Faction,
+        ),
+        (Self::TERRITORY_WAR, "Territory war", IconCategory::Faction),
+        (
+            Self::FACTION_RECRUIT,
+            "Faction recruit",
+            IconCategory::Faction,
+        ),
+        (Self::STOCK_MARKET, "Stock market", IconCategory::Finance),
+    ];
+
+    pub const fn from_id(id: i16) -> Self {
+        Self(id)
+    }
+
+    pub fn id(self) -> i16 {
+        self.0
+    }
+
+    fn lookup(self) -> Option<&'static (Self, &'static str, IconCategory)> {
+        Self::KNOWN.iter().find(|(icon, ..)| *icon == self)
+    }
+
+    pub fn name(self) -> Option<&'static str> {
+        self.lookup().map(|(_, name, _)| *name)
+    }
+
+    pub fn category(self) -> Option<IconCategory> {
+        self.lookup().map(|(.., category)| *category)
+    }
+
+    /// The catalogued icons, see [`Icon`].
+    pub fn known() -> impl Iterator<Item = Self> {
+        Self::KNOWN.iter().map(|(icon, ..)| *icon)
+    }
+}
+
+impl std::fmt::Display for Icon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "Icon {}", self.0),
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for Icon {
@@ -488,7 +942,8 @@ impl<'de> Deserialize<'de> for Icon {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 pub enum Job {
     Director,
@@ -499,15 +954,39 @@ pub enum Job {
     Casino,
     Medical,
     Grocer,
-    #[serde(other)]
     Other,
 }
 
+impl<'de> Deserialize<'de> for Job {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+
+        Ok(match raw.as_ref() {
+            "Director" => Self::Director,
+            "Employee" => Self::Employee,
+            "Education" => Self::Education,
+            "Army" => Self::Army,
+            "Law" => Self::Law,
+            "Casino" => Self::Casino,
+            "Medical" => Self::Medical,
+            "Grocer" => Self::Grocer,
+            _ => {
+                de_util::fallback::<D::Error>(&raw, "job")?;
+                Self::Other
+            }
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Company {
     PlayerRun {
         name: String,
-        id: i32,
+        id: CompanyId,
         company_type: u8,
     },
     CityJob,
@@ -550,7 +1029,7 @@ impl<'de> Deserialize<'de> for Company {
                     match key {
                         Field::CompanyId => {
                             id = Some(map.next_value()?);
-                            if id == Some(0) {
+                            if id == Some(CompanyId(0)) {
                                 return Ok(Company::CityJob);
                             }
                         }
@@ -580,6 +1059,7 @@ impl<'de> Deserialize<'de> for Company {
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct EmploymentStatus {
     pub job: Job,
     #[serde(flatten)]
@@ -605,6 +1085,13 @@ mod tests {
                     Selection::PersonalStats,
                     Selection::Crimes,
                     Selection::Attacks,
+                    Selection::Cooldowns,
+                    Selection::Education,
+                    Selection::Travel,
+                    Selection::Honors,
+                    Selection::Medals,
+                    Selection::BattleStats,
+                    Selection::Equipment,
                 ])
             })
             .await
@@ -617,6 +1104,101 @@ mod tests {
         response.crimes().unwrap();
         response.attacks().unwrap();
         response.attacks_full().unwrap();
+        response.cooldowns().unwrap();
+        response.education().unwrap();
+        response.travel().unwrap();
+        response.honors().unwrap();
+        response.medals().unwrap();
+        response.battle_stats().unwrap();
+        response.equipment().unwrap();
+    }
+
+    #[async_test]
+    async fn template() {
+        let key = setup();
+
+        let template = crate::ApiRequestBuilder::new().selections([Selection::Basic]);
+        let client = Client::default();
+        let provider = client.torn_api(key);
+
+        let single: Response = provider.fetch(template.with_id(28)).await.unwrap();
+        assert_eq!(single.basic().unwrap().player_id, UserId(28));
+
+        let bulk = provider
+            .fetch_many::<Response, _, _>([1, 28], template.clone())
+            .await;
+        assert_eq!(bulk.len(), 2);
+    }
+
+    #[async_test]
+    async fn generic_bulk() {
+        let key = setup();
+
+        let response = Client::default()
+            .torn_api(key)
+            .bulk::<Response, _, _, _>([1, 28], |b| b.selections([Selection::Basic]))
+            .await;
+
+        let basic = response
+            .get(&28)
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .basic()
+            .unwrap();
+        assert_eq!(basic.player_id, UserId(28));
+        response.get(&1).unwrap().as_ref().unwrap();
+    }
+
+    #[cfg(feature = "unknown-fields")]
+    #[test]
+    fn unknown_fields() {
+        let discord: Discord =
+            serde_json::from_str(r#"{"userID":1,"discordID":"123","verified":true}"#).unwrap();
+
+        assert_eq!(discord.unknown_fields.len(), 1);
+        assert_eq!(
+            discord.unknown_fields.get("verified"),
+            Some(&serde_json::Value::Bool(true))
+        );
+
+        let faction: crate::faction::Basic =
+            serde_json::from_str(include_str!("../fixtures/faction/basic.json")).unwrap();
+        let member = faction.members.values().next().unwrap();
+        assert!(!member.unknown_fields.contains_key("name"));
+    }
+
+    #[test]
+    fn owned_response() {
+        use crate::ApiCategoryResponse;
+
+        let response = Response::from_response(
+            crate::ApiResponse::from_value(serde_json::json!({
+                "discord": { "userID": 1, "discordID": "123" },
+                "cooldowns": { "drug": 0, "medical": 120, "booster": 0 },
+            }))
+            .unwrap(),
+        );
+
+        fn assert_static<T: Send + 'static>(_: &T) {}
+
+        let owned = response.into_owned().unwrap();
+        assert_static(&owned);
+        assert!(owned.discord.is_some());
+        assert!(owned.cooldowns.is_some());
+        assert!(owned.travel.is_none());
+        let basic: Option<OwnedBasic> = owned.basic;
+        assert!(basic.is_none());
+
+        // a requested flattened selection which fails to decode is an error, not an absence
+        let response = Response::from_response(
+            crate::ApiResponse::from_value(serde_json::json!({
+                "discord": { "userID": 1, "discordID": "123" },
+            }))
+            .unwrap()
+            .with_selections([Selection::Basic, Selection::Discord].into_iter().collect()),
+        );
+        assert!(response.into_owned().is_err());
     }
 
     #[async_test]
@@ -655,11 +1237,14 @@ mod tests {
 
         let response = Client::default()
             .torn_api(key)
-            .user(|b| b.id(374272176892674048i64).selections([Selection::Basic]))
+            .user(|b| {
+                // a discord id in place of the user id
+                b.id(374272176892674048i64).selections([Selection::Basic])
+            })
             .await
             .unwrap();
 
-        assert_eq!(response.basic().unwrap().player_id, 2111649);
+        assert_eq!(response.basic().unwrap().player_id, UserId(2111649));
     }
 
     #[async_test]
@@ -676,4 +1261,181 @@ mod tests {
 
         assert!(icons.contains_key(&Icon::FEDDED))
     }
+
+    #[async_test]
+    async fn events() {
+        let key = setup();
+
+        // the `new` variants would mark the key owner's events and messages as seen
+        let response = Client::default()
+            .torn_api(key)
+            .user(|b| b.selections([Selection::Events, Selection::Messages]))
+            .await
+            .unwrap();
+
+        response.events().unwrap();
+        response.messages().unwrap();
+    }
+
+    #[test]
+    fn unknown_competition() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            #[serde(deserialize_with = "deserialize_comp")]
+            competition: Option<Competition>,
+        }
+
+        let wrapper: Wrapper = serde_json::from_value(serde_json::json!({
+            "competition": { "name": "Mr & Ms Torn", "votes": 12 }
+        }))
+        .unwrap();
+
+        match wrapper.competition {
+            Some(Competition::Other { name, data }) => {
+                assert_eq!(name, "Mr & Ms Torn");
+                assert_eq!(data["votes"], 12);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let wrapper: Wrapper = serde_json::from_value(serde_json::json!({
+            "competition": { "name": "Rock, Paper, Scissors", "status": "rock" }
+        }))
+        .unwrap();
+        assert!(matches!(
+            wrapper.competition,
+            Some(Competition::RockPaperScissors {
+                hand: Some(Hand::Rock)
+            })
+        ));
+    }
+
+    #[test]
+    fn corpus() {
+        use crate::ApiCategoryResponse;
+
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/corpus/user");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let value: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            let response = Response::from_response(crate::ApiResponse::from_value(value).unwrap());
+
+            let profile = response
+                .profile()
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            response.basic().unwrap();
+            response.crimes().unwrap();
+
+            match profile.player_id.get() {
+                1001 => assert!(profile.faction.is_none()),
+                1002 => assert!(matches!(
+                    profile.status.kind,
+                    crate::common::StatusKind::Federal { .. }
+                )),
+                1007 => assert!(profile.competition.is_none()),
+                1008 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::Other { .. })
+                )),
+                1010 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::EasterEggHunt { eggs: 7 })
+                )),
+                1014 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::DogTags {
+                        position: Some(12),
+                        ..
+                    })
+                )),
+                1015 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::Halloween { treats: 1250 })
+                )),
+                1016 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::RockPaperScissors {
+                        hand: Some(Hand::Scissors)
+                    })
+                )),
+                1017 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::RockPaperScissors { hand: None })
+                )),
+                1018 => assert!(matches!(
+                    profile.competition,
+                    Some(Competition::Other { .. })
+                )),
+                1012 => assert_eq!(profile.job.company, Company::CityJob),
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn personal_stats_diff() {
+        fn stats(attacks_won: i32, net_worth: i64) -> PersonalStats {
+            serde_json::from_value(serde_json::json!({
+                "attackswon": attacks_won,
+                "attackslost": 3,
+                "defendswon": 0,
+                "defendslost": 0,
+                "statenhancersused": 0,
+                "refills": 10,
+                "drugsused": 0,
+                "xantaken": 0,
+                "lsdtaken": 0,
+                "networth": net_worth,
+                "energydrinkused": 0,
+                "boostersused": 0,
+                "awards": 20,
+                "elo": 1000,
+                "daysbeendonator": 5,
+                "bestdamage": 1200,
+            }))
+            .unwrap()
+        }
+
+        let delta = stats(150, 1_000_000).diff(&stats(100, 3_000_000));
+        assert_eq!(delta.attacks_won, 50);
+        assert_eq!(delta.net_worth, -2_000_000);
+        assert_eq!(delta.refills, 0);
+    }
+
+    #[test]
+    fn unknown_gender() {
+        let gender: Gender = serde_json::from_str(r#""Agender""#).unwrap();
+        assert_eq!(gender, Gender::Other("Agender".to_owned()));
+
+        // owned deserializers work as well
+        let gender = Gender::deserialize(serde_json::json!("Female")).unwrap();
+        assert_eq!(gender, Gender::Female);
+    }
+
+    #[test]
+    fn icon_catalogue() {
+        assert_eq!(Icon::FEDDED.to_string(), "Federal jail");
+        assert_eq!(Icon::FEDDED.category(), Some(IconCategory::Status));
+        assert_eq!(Icon::from_id(200).to_string(), "Icon 200");
+        assert!(Icon::from_id(200).name().is_none());
+
+        let known: std::collections::BTreeSet<_> = Icon::known().collect();
+        assert_eq!(known.len(), Icon::known().count());
+    }
+
+    #[test]
+    fn travel() {
+        let travel: Travel = serde_json::from_value(serde_json::json!({
+            "destination": "Atlantis",
+            "method": "Airstrip",
+            "timestamp": 1700000600,
+            "departed": 1700000000,
+            "time_left": 600,
+        }))
+        .unwrap();
+
+        assert_eq!(travel.destination, Country::Other);
+        assert_eq!(travel.method, Some(TravelMethod::Airstrip));
+    }
 }
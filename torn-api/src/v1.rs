@@ -0,0 +1,10 @@
+//! Models of the selections whose shape differs between the versions of the API, as returned
+//! by v1. The category modules use these models, see [`crate::v2`] for their v2 counterparts.
+
+pub use crate::common::{Attack, AttackFull};
+
+#[cfg(feature = "faction")]
+pub use crate::faction::Member;
+
+#[cfg(feature = "user")]
+pub use crate::user::PersonalStats;
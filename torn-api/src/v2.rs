@@ -0,0 +1,197 @@
+//! Models of the selections whose shape differs between the versions of the API, as returned
+//! by v2. Decoding a v1 response with these, or a v2 response with the models in [`crate::v1`],
+//! fails with [`SelectionError::Version`].
+
+use std::collections::BTreeMap;
+
+use chrono::{serde::ts_seconds, serde::ts_seconds_option, DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    common::{AttackResult, LastAction, State},
+    ApiResponse, ApiVersion, FactionId, SelectionError, UserId,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackFaction {
+    pub id: FactionId,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackParticipant {
+    pub id: UserId,
+    pub name: String,
+    pub level: i16,
+    pub faction: Option<AttackFaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Attack {
+    pub id: i64,
+    pub code: String,
+    #[serde(with = "ts_seconds")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    pub ended: DateTime<Utc>,
+    /// `None` for stealthed attacks.
+    pub attacker: Option<AttackParticipant>,
+    pub defender: AttackParticipant,
+    pub result: AttackResult,
+    pub respect_gain: f64,
+    pub respect_loss: f64,
+    pub chain: i32,
+    pub is_interrupted: bool,
+    pub is_stealthed: bool,
+    pub is_raid: bool,
+    pub is_ranked_war: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemberStatus {
+    pub description: String,
+    pub details: Option<String>,
+    pub state: State,
+    #[serde(with = "ts_seconds_option")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Member {
+    pub id: UserId,
+    pub name: String,
+    pub level: i16,
+    pub days_in_faction: i16,
+    pub position: String,
+    pub last_action: LastAction,
+    pub status: MemberStatus,
+    pub is_revivable: bool,
+    pub is_on_wall: bool,
+    pub is_in_oc: bool,
+}
+
+/// The personal stats, which v2 groups into nested categories, e.g. `attacking.attacks.won`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct PersonalStats(pub BTreeMap<String, serde_json::Value>);
+
+impl PersonalStats {
+    /// Look up a numeric stat by its dotted path, e.g. `"attacking.attacks.won"`.
+    pub fn get(&self, path: &str) -> Option<i64> {
+        let mut parts = path.split('.');
+        let mut value = self.0.get(parts.next()?)?;
+        for part in parts {
+            value = value.get(part)?;
+        }
+        value.as_i64()
+    }
+}
+
+impl ApiResponse {
+    pub fn v2_attacks(&self) -> Result<Vec<Attack>, SelectionError> {
+        self.decode_versioned("attacks", "Vec<v2::Attack>", "attacks", ApiVersion::V2)
+    }
+
+    pub fn v2_members(&self) -> Result<Vec<Member>, SelectionError> {
+        self.decode_versioned("members", "Vec<v2::Member>", "members", ApiVersion::V2)
+    }
+
+    pub fn v2_personal_stats(&self) -> Result<PersonalStats, SelectionError> {
+        self.decode_versioned(
+            "personalstats",
+            "v2::PersonalStats",
+            "personalstats",
+            ApiVersion::V2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attacks() {
+        let response = ApiResponse::from_value(serde_json::json!({
+            "attacks": [{
+                "id": 1,
+                "code": "3f0d4e5c6b7a8f9e0d1c2b3a4f5e6d7c",
+                "started": 1_700_000_000,
+                "ended": 1_700_000_100,
+                "attacker": null,
+                "defender": {
+                    "id": 28,
+                    "name": "Chedburn",
+                    "level": 15,
+                    "faction": { "id": 7, "name": "Faction" },
+                },
+                "result": "Lost",
+                "respect_gain": 0.0,
+                "respect_loss": 0.0,
+                "chain": 0,
+                "is_interrupted": false,
+                "is_stealthed": true,
+                "is_raid": false,
+                "is_ranked_war": false,
+            }],
+            "_metadata": { "links": { "prev": null, "next": null } },
+        }))
+        .unwrap();
+
+        assert_eq!(response.field_version("attacks"), Some(ApiVersion::V2));
+        let attacks = response.v2_attacks().unwrap();
+        assert_eq!(
+            attacks[0].defender.faction.as_ref().unwrap().id,
+            FactionId(7)
+        );
+
+        let v1 = ApiResponse::from_value(serde_json::json!({ "attacks": {} })).unwrap();
+        assert!(matches!(
+            v1.v2_attacks(),
+            Err(SelectionError::Version {
+                found: ApiVersion::V1,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(all(feature = "user", feature = "faction"))]
+    #[test]
+    fn empty_v1_attacks() {
+        use crate::ApiCategoryResponse;
+
+        let response = || ApiResponse::from_value(serde_json::json!({ "attacks": [] })).unwrap();
+        assert_eq!(response().field_version("attacks"), None);
+
+        let faction = crate::faction::Response::from_response(response());
+        assert!(faction.attacks_full().unwrap().is_empty());
+        assert!(faction.attacks().unwrap().is_empty());
+
+        let user = crate::user::Response::from_response(response());
+        assert!(user.attacks_full().unwrap().is_empty());
+        assert!(user.attacks().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "user")]
+    #[test]
+    fn wrong_model() {
+        use crate::ApiCategoryResponse;
+
+        let response = crate::user::Response::from_response(
+            ApiResponse::from_value(serde_json::json!({
+                "personalstats": { "attacking": { "attacks": { "won": 10 } } },
+            }))
+            .unwrap(),
+        );
+
+        let err = response.personal_stats().unwrap_err();
+        assert!(matches!(
+            err,
+            SelectionError::Version {
+                expected: ApiVersion::V1,
+                found: ApiVersion::V2,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use wiremock::{matchers::method, Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::{send::ApiClient, ApiErrorCode, RawResponse};
+
+// (category, selection, partial response)
+const FIXTURES: &[(&str, &str, &str)] = &[
+    ("user", "basic", include_str!("../fixtures/user/basic.json")),
+    (
+        "user",
+        "profile",
+        include_str!("../fixtures/user/profile.json"),
+    ),
+    (
+        "user",
+        "discord",
+        include_str!("../fixtures/user/discord.json"),
+    ),
+    (
+        "user",
+        "cooldowns",
+        include_str!("../fixtures/user/cooldowns.json"),
+    ),
+    (
+        "user",
+        "personalstats",
+        include_str!("../fixtures/user/personalstats.json"),
+    ),
+    (
+        "user",
+        "battlestats",
+        include_str!("../fixtures/user/battlestats.json"),
+    ),
+    (
+        "faction",
+        "basic",
+        include_str!("../fixtures/faction/basic.json"),
+    ),
+];
+
+fn error(code: ApiErrorCode, reason: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "error": { "code": code.code(), "error": reason }
+    }))
+}
+
+struct FixtureResponder;
+
+impl Respond for FixtureResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let Some(category) = request
+            .url
+            .path_segments()
+            .and_then(|mut segments| segments.next())
+        else {
+            return error(ApiErrorCode::IncorrectCategory, "Incorrect category");
+        };
+
+        if !request.url.query_pairs().any(|(name, _)| name == "key") {
+            return error(ApiErrorCode::KeyEmpty, "Key is empty");
+        }
+
+        let selections = request
+            .url
+            .query_pairs()
+            .find(|(name, _)| name == "selections")
+            .map(|(_, value)| value.into_owned())
+            .unwrap_or_default();
+
+        let mut body = serde_json::Map::new();
+        for selection in selections.split(',').filter(|s| !s.is_empty()) {
+            let Some((_, _, fixture)) = FIXTURES
+                .iter()
+                .find(|(c, s, _)| *c == category && *s == selection)
+            else {
+                return error(ApiErrorCode::WrongFields, "Wrong fields");
+            };
+
+            let serde_json::Value::Object(fields) = serde_json::from_str(fixture).unwrap() else {
+                unreachable!("fixtures are json objects");
+            };
+            body.extend(fields);
+        }
+
+        ResponseTemplate::new(200).set_body_json(body)
+    }
+}
+
+/// A local server answering Torn API requests with the bundled fixtures. Requesting a selection
+/// without a fixture results in the usual "Wrong fields" API error.
+pub struct TornMockServer {
+    server: MockServer,
+}
+
+impl TornMockServer {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(FixtureResponder)
+            .with_priority(u8::MAX)
+            .mount(&server)
+            .await;
+
+        Self { server }
+    }
+
+    /// The underlying server, e.g. for mounting additional mocks which take precedence over the
+    /// bundled fixtures.
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    pub fn client(&self) -> MockServerClient {
+        MockServerClient {
+            client: reqwest::Client::new(),
+            base: self.uri(),
+        }
+    }
+}
+
+/// A reqwest based client sending all requests to a [`TornMockServer`].
+pub struct MockServerClient {
+    client: reqwest::Client,
+    base: String,
+}
+
+#[async_trait]
+impl ApiClient for MockServerClient {
+    type Error = reqwest::Error;
+
+    async fn request(&self, url: String) -> Result<RawResponse, Self::Error> {
+        let url = match url.strip_prefix("https://api.torn.com") {
+            Some(path) => format!("{}{}", self.base, path),
+            None => url,
+        };
+
+        ApiClient::request(&self.client, url).await
+    }
+}
+
+#[cfg(all(test, feature = "user", feature = "faction"))]
+mod tests {
+    use super::*;
+    use crate::{faction, user, UserId};
+
+    #[tokio::test]
+    async fn bundled_fixtures() {
+        let server = TornMockServer::start().await;
+        let client = server.client();
+
+        let response = client
+            .torn_api("key")
+            .user(|b| {
+                b.id(4).selections([
+                    user::Selection::Basic,
+                    user::Selection::Profile,
+                    user::Selection::Discord,
+                    user::Selection::Cooldowns,
+                    user::Selection::PersonalStats,
+                    user::Selection::BattleStats,
+                ])
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.profile().unwrap().player_id, UserId(4));
+        response.discord().unwrap();
+        response.cooldowns().unwrap();
+        response.personal_stats().unwrap();
+        response.battle_stats().unwrap();
+
+        let faction = client
+            .torn_api("key")
+            .faction(|b| b.selections([faction::Selection::Basic]))
+            .await
+            .unwrap();
+        assert_eq!(faction.basic().unwrap().leader, UserId(4));
+
+        let err = client
+            .torn_api("key")
+            .user(|b| b.selections([user::Selection::Icons]))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.api_code(), Some(ApiErrorCode::WrongFields));
+    }
+}
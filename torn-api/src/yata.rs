@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::{common::Country, de_util, ApiResponse, ItemId, RawResponse, ResponseError, UserId};
+
+const BASE_URL: &str = "https://yata.yt/api/v1";
+
+#[derive(Error, Debug)]
+pub enum YataError<C>
+where
+    C: std::error::Error,
+{
+    #[error(transparent)]
+    Client(C),
+
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+
+    #[error("YATA error {code}: {reason}")]
+    Api { code: i32, reason: String },
+}
+
+fn country_codes<'de, D>(deserializer: D) -> Result<HashMap<Country, CountryStocks>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let by_code = HashMap::<String, CountryStocks>::deserialize(deserializer)?;
+    let mut stocks = HashMap::with_capacity(by_code.len());
+    for (code, country_stocks) in by_code {
+        match Country::from_code(&code) {
+            Some(country) => {
+                stocks.insert(country, country_stocks);
+            }
+            None => de_util::fallback::<D::Error>(&code, "country code")?,
+        }
+    }
+    Ok(stocks)
+}
+
+fn country_code<S>(country: &Country, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match country.code() {
+        Some(code) => serializer.serialize_str(code),
+        None => Err(serde::ser::Error::custom("Torn has no travel stocks")),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stock {
+    pub id: ItemId,
+    pub name: String,
+    pub quantity: i64,
+    pub cost: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CountryStocks {
+    #[serde(with = "ts_seconds")]
+    pub update: DateTime<Utc>,
+    pub stocks: Vec<Stock>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TravelStocks {
+    #[serde(deserialize_with = "country_codes")]
+    pub stocks: HashMap<Country, CountryStocks>,
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportItem {
+    pub id: ItemId,
+    pub quantity: i64,
+    pub cost: i64,
+}
+
+/// The items on sale in a country, as reported by a traveller who is there.
+#[derive(Debug, Clone, Serialize)]
+pub struct TravelImport {
+    /// Name of the reporting application.
+    pub client: String,
+    pub version: String,
+    pub author_name: String,
+    pub author_id: UserId,
+    #[serde(serialize_with = "country_code")]
+    pub country: Country,
+    pub items: Vec<ImportItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BazaarListing {
+    pub player_id: UserId,
+    pub player_name: String,
+    pub price: i64,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BazaarListings {
+    pub item_id: ItemId,
+    /// The known listings, cheapest first.
+    pub listings: Vec<BazaarListing>,
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+}
+
+fn travel_export_url() -> String {
+    format!("{BASE_URL}/travel/export/")
+}
+
+fn travel_import_url() -> String {
+    format!("{BASE_URL}/travel/import/")
+}
+
+fn bazaar_url(item: ItemId) -> String {
+    format!("{BASE_URL}/bazaar/{item}/")
+}
+
+fn decode<T, E>(raw: &RawResponse) -> Result<T, YataError<E>>
+where
+    T: DeserializeOwned,
+    E: std::error::Error,
+{
+    #[derive(Deserialize)]
+    struct ErrorDto {
+        code: i32,
+        error: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Response<T> {
+        Error { error: ErrorDto },
+        Ok(T),
+    }
+
+    ApiResponse::check_json(raw)?;
+
+    match serde_json::from_slice(&raw.body).map_err(ResponseError::from)? {
+        Response::Error { error } => Err(YataError::Api {
+            code: error.code,
+            reason: error.error,
+        }),
+        Response::Ok(value) => Ok(value),
+    }
+}
+
+#[derive(Deserialize)]
+struct ImportResponse {
+    message: String,
+}
+
+fn encode_import(import: &TravelImport) -> Result<Vec<u8>, ResponseError> {
+    Ok(serde_json::to_vec(import)?)
+}
+
+pub mod send {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::send::ApiClient;
+
+    /// Clients which can also send the POST requests needed to submit data to YATA.
+    #[async_trait]
+    pub trait PostClient: ApiClient {
+        async fn post_json(&self, url: String, body: Vec<u8>) -> Result<RawResponse, Self::Error>;
+    }
+
+    /// A client for the public endpoints of [YATA](https://yata.yt), using the same backends as
+    /// the Torn API.
+    pub struct YataProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        client: &'a C,
+    }
+
+    impl<'a, C> YataProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        pub fn new(client: &'a C) -> Self {
+            Self { client }
+        }
+
+        async fn get<T>(&self, url: String) -> Result<T, YataError<C::Error>>
+        where
+            T: DeserializeOwned,
+        {
+            let raw = self.client.request(url).await.map_err(YataError::Client)?;
+            decode(&raw)
+        }
+
+        /// The items on sale abroad, as last reported by travellers.
+        pub async fn travel_stocks(&self) -> Result<TravelStocks, YataError<C::Error>> {
+            self.get(travel_export_url()).await
+        }
+
+        /// The bazaar listings of `item` YATA knows of.
+        pub async fn bazaar(&self, item: ItemId) -> Result<BazaarListings, YataError<C::Error>> {
+            self.get(bazaar_url(item)).await
+        }
+    }
+
+    impl<C> YataProvider<'_, C>
+    where
+        C: PostClient,
+    {
+        /// Report the items on sale in a country, returning YATA's confirmation message.
+        pub async fn import(&self, import: &TravelImport) -> Result<String, YataError<C::Error>> {
+            let raw = self
+                .client
+                .post_json(travel_import_url(), encode_import(import)?)
+                .await
+                .map_err(YataError::Client)?;
+            decode::<ImportResponse, _>(&raw).map(|r| r.message)
+        }
+    }
+}
+
+pub mod local {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::local::ApiClient;
+
+    /// Clients which can also send the POST requests needed to submit data to YATA.
+    #[async_trait(?Send)]
+    pub trait PostClient: ApiClient {
+        async fn post_json(&self, url: String, body: Vec<u8>) -> Result<RawResponse, Self::Error>;
+    }
+
+    /// A client for the public endpoints of [YATA](https://yata.yt), using the same backends as
+    /// the Torn API.
+    pub struct YataProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        client: &'a C,
+    }
+
+    impl<'a, C> YataProvider<'a, C>
+    where
+        C: ApiClient,
+    {
+        pub fn new(client: &'a C) -> Self {
+            Self { client }
+        }
+
+        async fn get<T>(&self, url: String) -> Result<T, YataError<C::Error>>
+        where
+            T: DeserializeOwned,
+        {
+            let raw = self.client.request(url).await.map_err(YataError::Client)?;
+            decode(&raw)
+        }
+
+        /// The items on sale abroad, as last reported by travellers.
+        pub async fn travel_stocks(&self) -> Result<TravelStocks, YataError<C::Error>> {
+            self.get(travel_export_url()).await
+        }
+
+        /// The bazaar listings of `item` YATA knows of.
+        pub async fn bazaar(&self, item: ItemId) -> Result<BazaarListings, YataError<C::Error>> {
+            self.get(bazaar_url(item)).await
+        }
+    }
+
+    impl<C> YataProvider<'_, C>
+    where
+        C: PostClient,
+    {
+        /// Report the items on sale in a country, returning YATA's confirmation message.
+        pub async fn import(&self, import: &TravelImport) -> Result<String, YataError<C::Error>> {
+            let raw = self
+                .client
+                .post_json(travel_import_url(), encode_import(import)?)
+                .await
+                .map_err(YataError::Client)?;
+            decode::<ImportResponse, _>(&raw).map(|r| r.message)
+        }
+    }
+}
+
+pub use send::{PostClient, YataProvider};
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+
+    #[tokio::test]
+    async fn travel_stocks() {
+        let client = MockClient::new().respond_url(
+            "https://yata.yt/api/v1/travel/export/",
+            serde_json::json!({
+                "stocks": { "mex": { "update": 1650000000, "stocks": [
+                    { "id": 258, "name": "Jaguar Plushie", "quantity": 1200, "cost": 10000 },
+                ]}},
+                "timestamp": 1650000060,
+            }),
+        );
+        let stocks = YataProvider::new(&client).travel_stocks().await.unwrap();
+        let mexico = &stocks.stocks[&Country::Mexico];
+        assert_eq!(mexico.stocks[0].id, ItemId(258));
+        assert_eq!(mexico.stocks[0].quantity, 1200);
+
+        let client = MockClient::new().respond_url(
+            "https://yata.yt/api/v1/travel/export/",
+            serde_json::json!({ "error": { "code": 1, "error": "Server error" } }),
+        );
+        let err = YataProvider::new(&client)
+            .travel_stocks()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, YataError::Api { code: 1, .. }));
+    }
+
+    #[tokio::test]
+    async fn bazaar_and_import() {
+        let client = MockClient::new()
+            .respond_url(
+                "https://yata.yt/api/v1/bazaar/206/",
+                serde_json::json!({
+                    "item_id": 206,
+                    "listings": [
+                        { "player_id": 4, "player_name": "Duke", "price": 850000, "quantity": 2 },
+                    ],
+                    "timestamp": 1650000060,
+                }),
+            )
+            .respond_url(
+                "https://yata.yt/api/v1/travel/import/",
+                serde_json::json!({ "message": "Stocks imported" }),
+            );
+
+        let provider = local::YataProvider::new(&client);
+        let bazaar = provider.bazaar(ItemId(206)).await.unwrap();
+        assert_eq!(bazaar.listings[0].player_id, UserId(4));
+
+        let import = TravelImport {
+            client: "torn-api".to_owned(),
+            version: "0.7".to_owned(),
+            author_name: "Chedburn".to_owned(),
+            author_id: UserId(1),
+            country: Country::Mexico,
+            items: vec![ImportItem {
+                id: ItemId(258),
+                quantity: 1200,
+                cost: 10000,
+            }],
+        };
+        assert_eq!(provider.import(&import).await.unwrap(), "Stocks imported");
+        assert_eq!(
+            serde_json::to_value(&import).unwrap()["country"],
+            serde_json::json!("mex")
+        );
+    }
+}
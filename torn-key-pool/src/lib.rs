@@ -3,15 +3,27 @@
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
+#[cfg(feature = "memory")]
+pub mod memory;
+
 pub mod local;
 pub mod send;
 
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use thiserror::Error;
 
-use torn_api::ResponseError;
+use torn_api::{
+    bulk::{BufferPool, ClassifyFailure, FailureCause},
+    ApiErrorCode, ApiRequest, ApiResponse, ApiSelection, DeserializeHook, RawResponse,
+    RequestValidationError, ResponseError, Strictness,
+};
 
 #[derive(Debug, Error)]
 pub enum KeyPoolError<S, C>
@@ -27,6 +39,9 @@ where
 
     #[error(transparent)]
     Response(ResponseError),
+
+    #[error(transparent)]
+    Validation(RequestValidationError),
 }
 
 impl<S, C> KeyPoolError<S, C>
@@ -35,12 +50,36 @@ where
     C: std::error::Error,
 {
     #[inline(always)]
-    pub fn api_code(&self) -> Option<u8> {
+    pub fn api_code(&self) -> Option<ApiErrorCode> {
         match self {
             Self::Response(why) => why.api_code(),
             _ => None,
         }
     }
+
+    pub fn is_key_error(&self) -> bool {
+        matches!(self, Self::Response(why) if why.is_key_error())
+    }
+
+    pub fn is_request_error(&self) -> bool {
+        matches!(self, Self::Response(why) if why.is_request_error())
+            || matches!(self, Self::Validation(_))
+    }
+}
+
+impl<S, C> ClassifyFailure for KeyPoolError<S, C>
+where
+    S: std::error::Error,
+    C: std::error::Error,
+{
+    fn cause(&self) -> FailureCause {
+        match self {
+            Self::Storage(_) => FailureCause::Storage,
+            Self::Client(_) => FailureCause::Client,
+            Self::Response(why) => why.cause(),
+            Self::Validation(_) => FailureCause::Validation,
+        }
+    }
 }
 
 pub trait ApiKey: Sync + Send + std::fmt::Debug + Clone {
@@ -55,6 +94,35 @@ pub trait KeyDomain: Clone + std::fmt::Debug + Send + Sync {
     fn fallback(&self) -> Option<Self> {
         None
     }
+
+    /// A narrower domain for keys which can serve `request`, e.g. one requiring faction API
+    /// access when the request is for the faction's own selections. The narrower domain should
+    /// fall back to this one, so other keys are still tried if no such key is available.
+    fn for_request(&self, _request: &RequestHint<'_>) -> Option<Self> {
+        None
+    }
+}
+
+/// What a key is needed for, see [`KeyDomain::for_request`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestHint<'a> {
+    pub category: &'static str,
+    pub selections: Vec<&'static str>,
+    /// The requested entity. Bulk requests share their keys between ids, so they have none.
+    pub id: Option<&'a str>,
+}
+
+impl<'a> RequestHint<'a> {
+    pub fn new<A>(request: &ApiRequest<A>, id: Option<&'a str>) -> Self
+    where
+        A: ApiSelection,
+    {
+        Self {
+            category: A::category(),
+            selections: request.selections.iter().map(|s| s.raw_value()).collect(),
+            id,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +143,19 @@ where
     K: ApiKey,
     D: KeyDomain,
 {
+    pub(crate) fn for_request(&self, request: &RequestHint<'_>) -> Self {
+        let narrow = |domain: &D| {
+            domain
+                .for_request(request)
+                .unwrap_or_else(|| domain.clone())
+        };
+        match self {
+            Self::Has(domain) => Self::Has(narrow(domain)),
+            Self::OneOf(domains) => Self::OneOf(domains.iter().map(narrow).collect()),
+            other => other.clone(),
+        }
+    }
+
     pub(crate) fn fallback(&self) -> Option<Self> {
         match self {
             Self::Key(_) | Self::UserId(_) | Self::Id(_) => None,
@@ -137,7 +218,20 @@ pub trait KeyPoolStorage {
     where
         S: IntoSelector<Self::Key, Self::Domain>;
 
-    async fn flag_key(&self, key: Self::Key, code: u8) -> Result<bool, Self::Error>;
+    /// Number of calls the selected keys have left in the current rate limit window, not
+    /// counting keys which are cooling down. Fallback domains aren't included.
+    ///
+    /// Storages which don't keep track of the uses of their keys can't tell, and report
+    /// `i64::MAX`.
+    async fn capacity<S>(&self, selector: S) -> Result<i64, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        _ = selector;
+        Ok(i64::MAX)
+    }
+
+    async fn flag_key(&self, key: Self::Key, code: ApiErrorCode) -> Result<bool, Self::Error>;
 
     async fn store_key(
         &self,
@@ -191,6 +285,10 @@ where
     storage: &'a S,
     comment: Option<&'a str>,
     selector: KeySelector<S::Key, S::Domain>,
+    pacing: Option<Duration>,
+    hook: Option<Arc<dyn DeserializeHook>>,
+    strictness: Strictness,
+    buffers: Arc<BufferPool>,
     _marker: std::marker::PhantomData<C>,
 }
 
@@ -207,9 +305,154 @@ where
             storage,
             selector,
             comment,
+            pacing: None,
+            hook: None,
+            strictness: Strictness::default(),
+            buffers: Default::default(),
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// Let bulk requests which need more calls than the selected keys have left wait for the
+    /// next rate limit `window` instead of failing the remaining ids. Each window only uses the
+    /// calls the keys have left in it.
+    #[cfg(any(feature = "tokio-runtime", feature = "actix-runtime"))]
+    #[must_use]
+    pub fn pacing(mut self, window: Duration) -> Self {
+        self.pacing = Some(window);
+        self
+    }
+
+    /// Deserialise the selections of all responses using `hook`.
+    #[must_use]
+    pub fn deserialize_hook(mut self, hook: Arc<dyn DeserializeHook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// Deserialise the selections of all responses with `strictness`.
+    #[must_use]
+    pub fn strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    fn parse(&self, raw: RawResponse) -> Result<ApiResponse, ResponseError> {
+        let response =
+            ApiResponse::from_raw_pooled(raw, &self.buffers)?.with_strictness(self.strictness);
+        Ok(match &self.hook {
+            Some(hook) => response.with_hook(hook.clone()),
+            None => response,
+        })
+    }
+
+    /// Runs a bulk request, handing `run` a key for as many ids as the selected keys have calls
+    /// left for in the current rate limit window. With [`Self::pacing`] the remaining ids wait
+    /// for the next window, otherwise `run` gets no key and has to acquire one itself.
+    async fn run_batched<I, E, F, Fut>(
+        &self,
+        selector: &KeySelector<S::Key, S::Domain>,
+        ids: Vec<I>,
+        run: F,
+    ) -> HashMap<I, Result<ApiResponse, KeyPoolError<S::Error, E>>>
+    where
+        S: Sync,
+        I: std::hash::Hash + Eq,
+        E: std::error::Error,
+        F: Fn(I, Option<S::Key>) -> Fut,
+        Fut: Future<Output = (I, Result<ApiResponse, KeyPoolError<S::Error, E>>)>,
+    {
+        let mut results = HashMap::with_capacity(ids.len());
+        let mut pending = ids;
+        // ids still lacking a key after waiting for a new window fail
+        let mut waited = false;
+        loop {
+            let started = Instant::now();
+            let keys = self.acquire_batch(selector, pending.len()).await;
+
+            match (keys, self.pacing) {
+                (Ok(keys), Some(window)) if !keys.is_empty() => {
+                    let rest = pending.split_off(keys.len().min(pending.len()));
+                    let batch = std::iter::zip(pending, keys).map(|(id, key)| run(id, Some(key)));
+                    results.extend(futures::future::join_all(batch).await);
+                    if rest.is_empty() {
+                        return results;
+                    }
+
+                    pending = rest;
+                    sleep(window.saturating_sub(started.elapsed())).await;
+                    waited = true;
+                }
+                (Ok(keys), Some(window)) if keys.is_empty() && !waited => {
+                    sleep(window).await;
+                    waited = true;
+                }
+                (Ok(keys), _) => {
+                    // ids left without a key compete for one on their own
+                    let keys = keys.into_iter().map(Some).chain(std::iter::repeat(None));
+                    let batch = std::iter::zip(pending, keys).map(|(id, key)| run(id, key));
+                    results.extend(futures::future::join_all(batch).await);
+                    return results;
+                }
+                (Err(why), _) => {
+                    let shared = Arc::new(why);
+                    results.extend(
+                        pending
+                            .into_iter()
+                            .map(|i| (i, Err(KeyPoolError::Storage(shared.clone())))),
+                    );
+                    return results;
+                }
+            }
+        }
+    }
+
+    /// Keys for up to `pending` requests, but never more than the selected keys have calls left
+    /// in the current window.
+    async fn acquire_batch(
+        &self,
+        selector: &KeySelector<S::Key, S::Domain>,
+        pending: usize,
+    ) -> Result<Vec<S::Key>, S::Error>
+    where
+        S: Sync,
+    {
+        // like the storages, only count the fallback domains if the selected keys are used up
+        let mut counted = selector.clone();
+        let capacity = loop {
+            match self.storage.capacity(counted.clone()).await? {
+                0 => match counted.fallback() {
+                    Some(fallback) => counted = fallback,
+                    None => break 0,
+                },
+                capacity => break capacity,
+            }
+        };
+
+        let number = i64::try_from(pending).unwrap_or(i64::MAX).min(capacity);
+        if number <= 0 {
+            return Ok(Vec::new());
+        }
+        self.storage
+            .acquire_many_keys(selector.clone(), number)
+            .await
+    }
+}
+
+#[cfg(feature = "tokio-runtime")]
+async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(all(not(feature = "tokio-runtime"), feature = "actix-runtime"))]
+async fn sleep(duration: Duration) {
+    actix_rt::time::sleep(duration).await;
+}
+
+// pacing can't be enabled without a runtime
+#[cfg(not(any(feature = "tokio-runtime", feature = "actix-runtime")))]
+async fn sleep(_duration: Duration) {
+    unreachable!()
 }
 
 #[cfg(all(test, feature = "postgres"))]
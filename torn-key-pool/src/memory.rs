@@ -0,0 +1,709 @@
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use thiserror::Error;
+use torn_api::ApiErrorCode;
+
+use crate::{ApiKey, IntoSelector, KeyDomain, KeyPoolStorage, KeySelector};
+
+pub trait MemoryKeyDomain: KeyDomain + PartialEq {}
+
+impl<T> MemoryKeyDomain for T where T: KeyDomain + PartialEq {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryKey<D>
+where
+    D: MemoryKeyDomain,
+{
+    pub id: i32,
+    pub user_id: i32,
+    pub key: String,
+    pub uses: i16,
+    pub domains: Vec<D>,
+}
+
+impl<D> ApiKey for MemoryKey<D>
+where
+    D: MemoryKeyDomain,
+{
+    type IdType = i32;
+
+    #[inline(always)]
+    fn value(&self) -> &str {
+        &self.key
+    }
+
+    #[inline(always)]
+    fn id(&self) -> Self::IdType {
+        self.id
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MemoryStorageError<D>
+where
+    D: MemoryKeyDomain,
+{
+    #[error("No key avalaible for domain {0:?}")]
+    Unavailable(KeySelector<MemoryKey<D>, D>),
+
+    #[error("Key not found: '{0:?}'")]
+    KeyNotFound(KeySelector<MemoryKey<D>, D>),
+
+    #[error("Injected storage failure")]
+    Injected,
+}
+
+/// A scripted misbehaviour of the next key acquisition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Fail as if no key matched the selector.
+    Unavailable,
+    /// Lose the race against other acquisitions this many times before succeeding, like a
+    /// serialisation failure in the postgres storage would.
+    Contention(usize),
+    /// Fail with [`MemoryStorageError::Injected`], like a lost database connection would.
+    Storage,
+}
+
+#[derive(Debug)]
+struct State<D>
+where
+    D: MemoryKeyDomain,
+{
+    keys: Vec<MemoryKey<D>>,
+    flags: Vec<(i32, ApiErrorCode)>,
+    faults: VecDeque<Fault>,
+    contention_retries: usize,
+    next_id: i32,
+}
+
+/// In-memory key storage for testing how applications degrade when keys run out or get flagged.
+/// Every key can be used `limit` times before [`MemoryKeyPoolStorage::reset_uses`] is called.
+#[derive(Debug)]
+pub struct MemoryKeyPoolStorage<D>
+where
+    D: MemoryKeyDomain,
+{
+    state: Mutex<State<D>>,
+    limit: i16,
+}
+
+fn matches<D>(key: &MemoryKey<D>, selector: &KeySelector<MemoryKey<D>, D>) -> bool
+where
+    D: MemoryKeyDomain,
+{
+    match selector {
+        KeySelector::Key(value) => key.key == *value,
+        KeySelector::Id(id) => key.id == *id,
+        KeySelector::UserId(user_id) => key.user_id == *user_id,
+        KeySelector::Has(domain) => key.domains.contains(domain),
+        KeySelector::OneOf(domains) => domains.iter().any(|d| key.domains.contains(d)),
+    }
+}
+
+impl<D> MemoryKeyPoolStorage<D>
+where
+    D: MemoryKeyDomain,
+{
+    pub fn new(limit: i16) -> Self {
+        Self {
+            state: Mutex::new(State {
+                keys: Vec::new(),
+                flags: Vec::new(),
+                faults: VecDeque::new(),
+                contention_retries: 0,
+                next_id: 1,
+            }),
+            limit,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State<D>> {
+        self.state.lock().unwrap()
+    }
+
+    /// Queue a fault for the next key acquisition. Faults are consumed in order, one per call of
+    /// `acquire_key` or `acquire_many_keys`.
+    pub fn inject(&self, fault: Fault) {
+        self.state().faults.push_back(fault);
+    }
+
+    /// Flag a key as the key pool would after receiving `code` for it.
+    pub fn flag(&self, id: i32, code: ApiErrorCode) {
+        self.state().flags.push((id, code));
+    }
+
+    /// All flags received so far, in order.
+    pub fn flags(&self) -> Vec<(i32, ApiErrorCode)> {
+        self.state().flags.clone()
+    }
+
+    /// Number of simulated contention retries so far.
+    pub fn contention_retries(&self) -> usize {
+        self.state().contention_retries
+    }
+
+    /// Start a new rate limit window. Keys flagged for a temporary reason become available again.
+    pub fn reset_uses(&self) {
+        let mut state = self.state();
+        state.keys.iter_mut().for_each(|k| k.uses = 0);
+        state.flags.retain(|(_, code)| code.is_key_invalid());
+    }
+
+    fn take_fault(
+        state: &mut State<D>,
+        selector: &KeySelector<MemoryKey<D>, D>,
+    ) -> Result<(), MemoryStorageError<D>> {
+        match state.faults.pop_front() {
+            Some(Fault::Unavailable) => Err(MemoryStorageError::Unavailable(selector.clone())),
+            Some(Fault::Storage) => Err(MemoryStorageError::Injected),
+            Some(Fault::Contention(retries)) => {
+                state.contention_retries += retries;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn available<'a>(
+        state: &'a mut State<D>,
+        selector: &KeySelector<MemoryKey<D>, D>,
+        limit: i16,
+    ) -> Vec<&'a mut MemoryKey<D>> {
+        let flags = &state.flags;
+        let mut keys: Vec<_> = state
+            .keys
+            .iter_mut()
+            .filter(|k| k.uses < limit && matches(k, selector))
+            .filter(|k| flags.iter().all(|(id, _)| *id != k.id))
+            .collect();
+        keys.sort_by_key(|k| k.uses);
+        keys
+    }
+
+    fn modify<S, F>(&self, selector: S, modify: F) -> Result<MemoryKey<D>, MemoryStorageError<D>>
+    where
+        S: IntoSelector<MemoryKey<D>, D>,
+        F: FnOnce(&mut MemoryKey<D>),
+    {
+        let selector = selector.into_selector();
+        let mut state = self.state();
+        let key = state
+            .keys
+            .iter_mut()
+            .find(|k| matches(k, &selector))
+            .ok_or(MemoryStorageError::KeyNotFound(selector))?;
+        modify(key);
+        Ok(key.clone())
+    }
+}
+
+#[async_trait]
+impl<D> KeyPoolStorage for MemoryKeyPoolStorage<D>
+where
+    D: MemoryKeyDomain,
+{
+    type Key = MemoryKey<D>;
+    type Domain = D;
+
+    type Error = MemoryStorageError<D>;
+
+    async fn acquire_key<S>(&self, selector: S) -> Result<Self::Key, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let mut selector = selector.into_selector();
+        let mut state = self.state();
+        Self::take_fault(&mut state, &selector)?;
+
+        loop {
+            if let Some(key) = Self::available(&mut state, &selector, self.limit)
+                .into_iter()
+                .next()
+            {
+                key.uses += 1;
+                return Ok(key.clone());
+            }
+
+            selector = selector
+                .fallback()
+                .ok_or(MemoryStorageError::Unavailable(selector))?;
+        }
+    }
+
+    async fn acquire_many_keys<S>(
+        &self,
+        selector: S,
+        number: i64,
+    ) -> Result<Vec<Self::Key>, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let mut selector = selector.into_selector();
+        let mut state = self.state();
+        Self::take_fault(&mut state, &selector)?;
+
+        loop {
+            let mut keys = Self::available(&mut state, &selector, self.limit);
+            if !keys.is_empty() {
+                let mut result = Vec::with_capacity(number as usize);
+                while result.len() < number as usize {
+                    keys.retain(|k| k.uses < self.limit);
+                    keys.sort_by_key(|k| k.uses);
+                    let Some(key) = keys.first_mut() else {
+                        break;
+                    };
+                    key.uses += 1;
+                    result.push(key.clone());
+                }
+                return Ok(result);
+            }
+
+            selector = selector
+                .fallback()
+                .ok_or(MemoryStorageError::Unavailable(selector))?;
+        }
+    }
+
+    async fn capacity<S>(&self, selector: S) -> Result<i64, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let selector = selector.into_selector();
+        let mut state = self.state();
+        Ok(Self::available(&mut state, &selector, self.limit)
+            .into_iter()
+            .map(|k| i64::from(self.limit - k.uses))
+            .sum())
+    }
+
+    async fn flag_key(&self, key: Self::Key, code: ApiErrorCode) -> Result<bool, Self::Error> {
+        self.flag(key.id, code);
+        Ok(code.is_key_invalid() || code == ApiErrorCode::TooManyRequests)
+    }
+
+    async fn store_key(
+        &self,
+        user_id: i32,
+        key: String,
+        domains: Vec<D>,
+    ) -> Result<Self::Key, Self::Error> {
+        let mut state = self.state();
+        let key = MemoryKey {
+            id: state.next_id,
+            user_id,
+            key,
+            uses: 0,
+            domains,
+        };
+        state.next_id += 1;
+        state.keys.push(key.clone());
+        Ok(key)
+    }
+
+    async fn read_key<S>(&self, selector: S) -> Result<Option<Self::Key>, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let selector = selector.into_selector();
+        Ok(self
+            .state()
+            .keys
+            .iter()
+            .find(|k| matches(k, &selector))
+            .cloned())
+    }
+
+    async fn read_keys<S>(&self, selector: S) -> Result<Vec<Self::Key>, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let selector = selector.into_selector();
+        Ok(self
+            .state()
+            .keys
+            .iter()
+            .filter(|k| matches(k, &selector))
+            .cloned()
+            .collect())
+    }
+
+    async fn remove_key<S>(&self, selector: S) -> Result<Self::Key, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let selector = selector.into_selector();
+        let mut state = self.state();
+        let idx = state
+            .keys
+            .iter()
+            .position(|k| matches(k, &selector))
+            .ok_or(MemoryStorageError::KeyNotFound(selector))?;
+        Ok(state.keys.remove(idx))
+    }
+
+    async fn add_domain_to_key<S>(&self, selector: S, domain: D) -> Result<Self::Key, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        self.modify(selector, |key| {
+            if !key.domains.contains(&domain) {
+                key.domains.push(domain);
+            }
+        })
+    }
+
+    async fn remove_domain_from_key<S>(
+        &self,
+        selector: S,
+        domain: D,
+    ) -> Result<Self::Key, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        self.modify(selector, |key| key.domains.retain(|d| *d != domain))
+    }
+
+    async fn set_domains_for_key<S>(
+        &self,
+        selector: S,
+        domains: Vec<D>,
+    ) -> Result<Self::Key, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        self.modify(selector, |key| key.domains = domains)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Domain {
+        All,
+        Guild,
+        Faction,
+    }
+
+    impl KeyDomain for Domain {
+        fn fallback(&self) -> Option<Self> {
+            match self {
+                Self::Guild | Self::Faction => Some(Self::All),
+                Self::All => None,
+            }
+        }
+
+        fn for_request(&self, request: &crate::RequestHint<'_>) -> Option<Self> {
+            (request.category == "faction" && request.id.is_none()).then_some(Self::Faction)
+        }
+    }
+
+    #[tokio::test]
+    async fn scripted_faults() {
+        let storage = MemoryKeyPoolStorage::new(2);
+        let key = storage
+            .store_key(1, "key".to_owned(), vec![Domain::All])
+            .await
+            .unwrap();
+
+        // falls back from guild to all
+        assert_eq!(storage.acquire_key(Domain::Guild).await.unwrap().id, key.id);
+
+        storage.inject(Fault::Unavailable);
+        assert!(matches!(
+            storage.acquire_key(Domain::All).await,
+            Err(MemoryStorageError::Unavailable(_))
+        ));
+
+        storage.inject(Fault::Contention(3));
+        assert!(storage.acquire_key(Domain::All).await.is_ok());
+        assert_eq!(storage.contention_retries(), 3);
+
+        // the limit of 2 uses is exhausted
+        assert!(storage.acquire_key(Domain::All).await.is_err());
+        storage.reset_uses();
+
+        assert!(storage
+            .flag_key(key.clone(), ApiErrorCode::IncorrectKey)
+            .await
+            .unwrap());
+        storage.reset_uses();
+        assert!(storage.acquire_many_keys(Domain::All, 2).await.is_err());
+        assert_eq!(storage.flags(), [(key.id, ApiErrorCode::IncorrectKey)]);
+    }
+
+    #[tokio::test]
+    async fn capacity() {
+        let storage = MemoryKeyPoolStorage::new(5);
+        for user_id in [1, 2] {
+            storage
+                .store_key(user_id, format!("key{user_id}"), vec![Domain::Guild])
+                .await
+                .unwrap();
+        }
+        assert_eq!(storage.capacity(Domain::Guild).await.unwrap(), 10);
+
+        // flagged keys have no capacity left in this window
+        storage.flag(1, ApiErrorCode::TooManyRequests);
+        assert_eq!(storage.capacity(Domain::Guild).await.unwrap(), 5);
+
+        storage.acquire_many_keys(Domain::Guild, 3).await.unwrap();
+        assert_eq!(storage.capacity(Domain::Guild).await.unwrap(), 2);
+
+        assert_eq!(storage.capacity(Domain::All).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn deserialize_hook() {
+        use std::sync::Arc;
+
+        use torn_api::{mock::MockClient, user, Decode, DeserializeHook, SelectionError};
+
+        use crate::send::KeyPool;
+
+        struct Tracking;
+
+        impl DeserializeHook for Tracking {
+            fn deserialize(
+                &self,
+                _selection: &'static str,
+                decode: Decode<'_>,
+            ) -> serde_json::Result<()> {
+                decode.run_tracked()
+            }
+        }
+
+        let client = MockClient::new().respond(
+            Some(1),
+            [user::Selection::Discord],
+            serde_json::json!({ "discord": { "userID": true, "discordID": "" } }),
+        );
+        let storage = MemoryKeyPoolStorage::new(10);
+        storage
+            .store_key(1, "key".to_owned(), vec![Domain::All])
+            .await
+            .unwrap();
+
+        let pool = KeyPool::new(client, storage, None).deserialize_hook(Arc::new(Tracking));
+        let response = pool
+            .torn_api(Domain::All)
+            .user(|b| b.id(1).selections([user::Selection::Discord]))
+            .await
+            .unwrap();
+        let Err(SelectionError::Malformed(err)) = response.discord() else {
+            panic!("expected a malformed selection");
+        };
+        assert!(err.path.is_some());
+    }
+
+    #[tokio::test]
+    async fn strictness() {
+        use torn_api::{mock::MockClient, user, Strictness};
+
+        use crate::send::KeyPool;
+
+        let client = MockClient::new().respond(
+            Some(1),
+            [user::Selection::Basic],
+            serde_json::json!({
+                "player_id": 1,
+                "name": "Duke",
+                "level": 100,
+                "gender": "Agender",
+                "status": {
+                    "description": "Okay",
+                    "details": "",
+                    "state": "Okay",
+                    "color": "green",
+                    "until": 0
+                }
+            }),
+        );
+        let storage = MemoryKeyPoolStorage::new(10);
+        storage
+            .store_key(1, "key".to_owned(), vec![Domain::All])
+            .await
+            .unwrap();
+
+        let mut pool = KeyPool::new(client, storage, None);
+        for strictness in [Strictness::Lenient, Strictness::Strict] {
+            pool = pool.strictness(strictness);
+            let response = pool
+                .torn_api(Domain::All)
+                .user(|b| b.id(1).selections([user::Selection::Basic]))
+                .await
+                .unwrap();
+            match strictness {
+                Strictness::Lenient => assert_eq!(
+                    response.basic().unwrap().gender,
+                    user::Gender::Other("Agender".to_owned())
+                ),
+                Strictness::Strict => assert!(response.basic().is_err()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn request_hint() {
+        use torn_api::{faction, mock::MockClient, user};
+
+        use crate::send::KeyPool;
+
+        let client = MockClient::new()
+            .respond(
+                None::<i32>,
+                [faction::Selection::Basic],
+                serde_json::json!({ "ID": 1 }),
+            )
+            .respond(
+                Some(1),
+                [user::Selection::Discord],
+                serde_json::json!({ "discord": { "userID": 1, "discordID": "" } }),
+            );
+        let storage = MemoryKeyPoolStorage::new(10);
+        storage
+            .store_key(1, "member".to_owned(), vec![Domain::All])
+            .await
+            .unwrap();
+        storage
+            .store_key(2, "officer".to_owned(), vec![Domain::All, Domain::Faction])
+            .await
+            .unwrap();
+
+        let pool = KeyPool::new(client, storage, None);
+        let provider = pool.torn_api(Domain::All);
+        for _ in 0..2 {
+            _ = provider
+                .faction(|b| b.selections([faction::Selection::Basic]))
+                .await;
+        }
+        _ = provider
+            .user(|b| b.id(1).selections([user::Selection::Discord]))
+            .await;
+
+        // the faction requests need the officer's key, other requests can use the less used one
+        let uses: Vec<_> = pool.storage.state().keys.iter().map(|k| k.uses).collect();
+        assert_eq!(uses, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn buffer_reuse() {
+        use torn_api::{mock::MockClient, send::ApiProvider, user};
+
+        use crate::{IntoSelector, KeyPoolExecutor};
+
+        let client = MockClient::new().respond(
+            Some(1),
+            [user::Selection::Discord],
+            serde_json::json!({ "discord": { "userID": 1, "discordID": "" } }),
+        );
+        let storage = MemoryKeyPoolStorage::new(10);
+        storage
+            .store_key(1, "key".to_owned(), vec![Domain::All])
+            .await
+            .unwrap();
+
+        let executor = KeyPoolExecutor::new(&storage, Domain::All.into_selector(), None);
+        let buffers = executor.buffers.clone();
+        let response = ApiProvider::new(&client, executor)
+            .user(|b| b.id(1).selections([user::Selection::Discord]))
+            .await
+            .unwrap();
+        assert_eq!(buffers.idle(), 0);
+
+        // the body is handed back once the response is gone
+        drop(response);
+        assert_eq!(buffers.idle(), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_key() {
+        use torn_api::{mock::MockClient, user};
+
+        use crate::send::KeyPool;
+
+        let client = MockClient::new().respond_error(
+            Some(1),
+            [user::Selection::Discord],
+            ApiErrorCode::TooManyRequests,
+        );
+        let storage = MemoryKeyPoolStorage::new(10);
+        for user_id in [1, 2, 3] {
+            storage
+                .store_key(user_id, format!("key{user_id}"), vec![Domain::All])
+                .await
+                .unwrap();
+        }
+
+        let pool = KeyPool::new(client, storage, None);
+        let err = pool
+            .torn_api(Domain::All)
+            .user(|b| b.id(1).selections([user::Selection::Discord]))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.api_code(), Some(ApiErrorCode::TooManyRequests));
+
+        // both keys are cooled down, the third one wasn't tried
+        let flags = pool.storage.flags();
+        assert_eq!(flags.len(), 2);
+        assert!(flags
+            .iter()
+            .all(|(_, code)| *code == ApiErrorCode::TooManyRequests));
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn paced_bulk() {
+        use std::time::Duration;
+
+        use torn_api::{mock::MockClient, user};
+
+        use crate::send::KeyPool;
+
+        let mut client = MockClient::new();
+        for id in 1..=6 {
+            client = client.respond(
+                Some(id),
+                [user::Selection::Discord],
+                serde_json::json!({ "discord": { "userID": id, "discordID": "" } }),
+            );
+        }
+
+        let storage = MemoryKeyPoolStorage::new(2);
+        for user_id in [1, 2] {
+            storage
+                .store_key(user_id, format!("key{user_id}"), vec![Domain::All])
+                .await
+                .unwrap();
+        }
+
+        // without pacing the ids beyond the 4 available calls fail
+        let pool = KeyPool::new(client, storage, None);
+        let response = pool
+            .torn_api(Domain::All)
+            .users(1..=6, |b| b.selections([user::Selection::Discord]))
+            .await;
+        assert_eq!(response.values().filter(|r| r.is_ok()).count(), 4);
+        assert_eq!(response.len(), 6);
+
+        pool.storage.reset_uses();
+        let pool = pool.pacing(Duration::from_secs(60));
+        let provider = pool.torn_api(Domain::All);
+        let (response, _) = tokio::join!(
+            provider.users(1..=6, |b| b.selections([user::Selection::Discord])),
+            async {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                pool.storage.reset_uses();
+            }
+        );
+        assert_eq!(response.len(), 6);
+        assert!(response.values().all(Result::is_ok));
+    }
+}
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use indoc::indoc;
 use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
 use thiserror::Error;
+use torn_api::ApiErrorCode;
 
 use crate::{ApiKey, IntoSelector, KeyDomain, KeyPoolStorage, KeySelector};
 
@@ -83,6 +84,7 @@ where
     D: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
 {
     pool: PgPool,
+    read_pool: Option<PgPool>,
     limit: i16,
     _phantom: std::marker::PhantomData<D>,
 }
@@ -111,11 +113,30 @@ where
     pub fn new(pool: PgPool, limit: i16) -> Self {
         Self {
             pool,
+            read_pool: None,
             limit,
             _phantom: Default::default(),
         }
     }
 
+    /// Run lookups which don't modify keys, like [`KeyPoolStorage::read_keys`], on a separate
+    /// pool, e.g. one connected to a replica. Reads may then lag behind recent writes.
+    #[must_use]
+    pub fn with_read_pool(mut self, pool: PgPool) -> Self {
+        self.read_pool = Some(pool);
+        self
+    }
+
+    /// Like [`PgKeyPoolStorage::with_read_pool`], connecting lazily to `url`.
+    pub fn with_replica_url(self, url: &str) -> Result<Self, PgStorageError<D>> {
+        let pool = PgPool::connect_lazy(url)?;
+        Ok(self.with_read_pool(pool))
+    }
+
+    fn read_pool(&self) -> &PgPool {
+        self.read_pool.as_ref().unwrap_or(&self.pool)
+    }
+
     pub async fn initialise(&self) -> Result<(), PgStorageError<D>> {
         sqlx::query(indoc! {r#"
             CREATE TABLE IF NOT EXISTS api_keys (
@@ -346,15 +367,17 @@ where
                     return Ok(None);
                 }
 
-                keys.sort_unstable_by(|k1, k2| k1.uses.cmp(&k2.uses));
+                keys.sort_unstable_by_key(|k| k.uses);
 
                 let mut result = Vec::with_capacity(number as usize);
                 let (max, rest) = keys.split_last_mut().unwrap();
                 for key in rest {
                     let available = max.uses - key.uses;
-                    let using = std::cmp::min(available, (number as i16) - (result.len() as i16));
+                    let remaining =
+                        i16::try_from(number as usize - result.len()).unwrap_or(i16::MAX);
+                    let using = std::cmp::min(available, remaining);
                     key.uses += using;
-                    result.extend(std::iter::repeat(key.clone()).take(using as usize));
+                    result.extend(std::iter::repeat_n(key.clone(), using as usize));
 
                     if result.len() == number as usize {
                         break;
@@ -420,20 +443,40 @@ where
         }
     }
 
-    async fn flag_key(&self, key: Self::Key, code: u8) -> Result<bool, Self::Error> {
+    async fn capacity<S>(&self, selector: S) -> Result<i64, Self::Error>
+    where
+        S: IntoSelector<Self::Key, Self::Domain>,
+    {
+        let selector = selector.into_selector();
+
+        let mut qb = QueryBuilder::new("select coalesce(sum(greatest(");
+        qb.push_bind(self.limit);
+        qb.push(
+            " - case when last_used >= date_trunc('minute', now()) then uses else 0 end, 0)), \
+             0)::int8 from api_keys where (cooldown is null or now() >= cooldown) and ",
+        );
+        build_predicate(&mut qb, &selector);
+
+        qb.build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn flag_key(&self, key: Self::Key, code: ApiErrorCode) -> Result<bool, Self::Error> {
         match code {
-            2 | 10 | 13 => {
-                // invalid key, owner fedded or owner inactive
+            code if code.is_key_invalid() => {
+                // invalid key, owner fedded, owner inactive or key paused
                 sqlx::query(
                     "update api_keys set cooldown='infinity'::timestamptz, flag=$1 where id=$2",
                 )
-                .bind(code as i16)
+                .bind(code.code() as i16)
                 .bind(key.id)
                 .execute(&self.pool)
                 .await?;
                 Ok(true)
             }
-            5 => {
+            ApiErrorCode::TooManyRequests => {
                 // too many requests
                 sqlx::query(
                     "update api_keys set cooldown=date_trunc('min', now()) + interval '1 min', \
@@ -444,21 +487,21 @@ where
                 .await?;
                 Ok(true)
             }
-            8 => {
+            ApiErrorCode::IpBlock => {
                 // IP block
                 sqlx::query("update api_keys set cooldown=now() + interval '5 min', flag=8")
                     .execute(&self.pool)
                     .await?;
                 Ok(false)
             }
-            9 => {
+            ApiErrorCode::ApiDisabled => {
                 // API disabled
                 sqlx::query("update api_keys set cooldown=now() + interval '1 min', flag=9")
                     .execute(&self.pool)
                     .await?;
                 Ok(false)
             }
-            14 => {
+            ApiErrorCode::DailyReadLimit => {
                 // daily read limit reached
                 sqlx::query(
                     "update api_keys set cooldown=date_trunc('day', now()) + interval '1 day', \
@@ -502,7 +545,7 @@ where
         build_predicate(&mut qb, &selector);
 
         qb.build_query_as()
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.read_pool())
             .await
             .map_err(Into::into)
     }
@@ -517,7 +560,7 @@ where
         build_predicate(&mut qb, &selector);
 
         qb.build_query_as()
-            .fetch_all(&self.pool)
+            .fetch_all(self.read_pool())
             .await
             .map_err(Into::into)
     }
@@ -788,6 +831,17 @@ pub(crate) mod test {
         assert_eq!(keys.len(), 1);
     }
 
+    #[test]
+    async fn test_read_pool() {
+        let (storage, key) = setup().await;
+        let storage = storage
+            .with_replica_url(&std::env::var("DATABASE_URL").unwrap())
+            .unwrap();
+
+        let read = storage.read_key(KeySelector::Id(key.id)).await.unwrap();
+        assert_eq!(read.map(|k| k.id), Some(key.id));
+    }
+
     #[test]
     async fn acquire_one() {
         let (storage, _) = setup().await;
@@ -797,6 +851,15 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    async fn capacity() {
+        let (storage, _) = setup().await;
+        assert_eq!(storage.capacity(Domain::All).await.unwrap(), 1000);
+
+        storage.acquire_many_keys(Domain::All, 10).await.unwrap();
+        assert_eq!(storage.capacity(Domain::All).await.unwrap(), 990);
+    }
+
     #[test]
     async fn uses_spread() {
         let (storage, _) = setup().await;
@@ -820,7 +883,10 @@ pub(crate) mod test {
     async fn test_flag_key_one() {
         let (storage, key) = setup().await;
 
-        assert!(storage.flag_key(key, 2).await.unwrap());
+        assert!(storage
+            .flag_key(key, ApiErrorCode::IncorrectKey)
+            .await
+            .unwrap());
 
         match storage.acquire_key(Domain::All).await.unwrap_err() {
             PgStorageError::Unavailable(d) => assert!(matches!(d, KeySelector::Has(Domain::All))),
@@ -832,7 +898,10 @@ pub(crate) mod test {
     async fn test_flag_key_many() {
         let (storage, key) = setup().await;
 
-        assert!(storage.flag_key(key, 2).await.unwrap());
+        assert!(storage
+            .flag_key(key, ApiErrorCode::IncorrectKey)
+            .await
+            .unwrap());
 
         match storage.acquire_many_keys(Domain::All, 5).await.unwrap_err() {
             PgStorageError::Unavailable(d) => assert!(matches!(d, KeySelector::Has(Domain::All))),
@@ -1,13 +1,15 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 
 use torn_api::{
+    bulk::BufferPool,
     send::{ApiClient, ApiProvider, RequestExecutor},
-    ApiRequest, ApiResponse, ApiSelection, ResponseError,
+    ApiErrorCode, ApiRequest, ApiResponse, ApiSelection, DeserializeHook, ResponseError,
+    Strictness,
 };
 
-use crate::{ApiKey, IntoSelector, KeyPoolError, KeyPoolExecutor, KeyPoolStorage};
+use crate::{ApiKey, IntoSelector, KeyPoolError, KeyPoolExecutor, KeyPoolStorage, RequestHint};
 
 #[async_trait]
 impl<'client, C, S> RequestExecutor<C> for KeyPoolExecutor<'client, C, S>
@@ -26,18 +28,28 @@ where
     where
         A: ApiSelection,
     {
+        request
+            .validate(id.is_some())
+            .map_err(KeyPoolError::Validation)?;
+
         request.comment = self.comment.map(ToOwned::to_owned);
+        let selector = self
+            .selector
+            .for_request(&RequestHint::new(&request, id.as_deref()));
+        let mut rate_limited = false;
         loop {
             let key = self
                 .storage
-                .acquire_key(self.selector.clone())
+                .acquire_key(selector.clone())
                 .await
                 .map_err(|e| KeyPoolError::Storage(Arc::new(e)))?;
             let url = request.url(key.value(), id.as_deref());
-            let value = client.request(url).await?;
+            let raw = client.request_buffered(url, &self.buffers).await?;
 
-            match ApiResponse::from_value(value) {
-                Err(ResponseError::Api { code, reason }) => {
+            match self.parse(raw) {
+                Err(
+                    why @ (ResponseError::Key { code, .. } | ResponseError::Service { code, .. }),
+                ) => {
                     if !self
                         .storage
                         .flag_key(key, code)
@@ -45,10 +57,17 @@ where
                         .map_err(Arc::new)
                         .map_err(KeyPoolError::Storage)?
                     {
-                        return Err(KeyPoolError::Response(ResponseError::Api { code, reason }));
+                        return Err(KeyPoolError::Response(why));
+                    }
+                    // the key is cooled down, but only one other key is tried
+                    if code == ApiErrorCode::TooManyRequests {
+                        if rate_limited {
+                            return Err(KeyPoolError::Response(why));
+                        }
+                        rate_limited = true;
                     }
                 }
-                Err(parsing_error) => return Err(KeyPoolError::Response(parsing_error)),
+                Err(why) => return Err(KeyPoolError::Response(why)),
                 Ok(res) => return Ok(res),
             };
         }
@@ -64,65 +83,59 @@ where
         A: ApiSelection,
         I: ToString + std::hash::Hash + std::cmp::Eq + Send + Sync,
     {
-        let keys = match self
-            .storage
-            .acquire_many_keys(self.selector.clone(), ids.len() as i64)
-            .await
-        {
-            Ok(keys) => keys,
-            Err(why) => {
-                let shared = Arc::new(why);
-                return ids
-                    .into_iter()
-                    .map(|i| (i, Err(Self::Error::Storage(shared.clone()))))
-                    .collect();
-            }
-        };
+        if let Err(why) = request.validate(true) {
+            return ids
+                .into_iter()
+                .map(|i| (i, Err(KeyPoolError::Validation(why.clone()))))
+                .collect();
+        }
 
         request.comment = self.comment.map(ToOwned::to_owned);
+        let selector = self.selector.for_request(&RequestHint::new(&request, None));
         let request_ref = &request;
+        let selector_ref = &selector;
 
-        let tuples =
-            futures::future::join_all(std::iter::zip(ids, keys).map(|(id, mut key)| async move {
-                let id_string = id.to_string();
-                loop {
-                    let url = request_ref.url(key.value(), Some(&id_string));
-                    let value = match client.request(url).await {
-                        Ok(v) => v,
-                        Err(why) => return (id, Err(Self::Error::Client(why))),
-                    };
-
-                    match ApiResponse::from_value(value) {
-                        Err(ResponseError::Api { code, reason }) => {
-                            match self.storage.flag_key(key, code).await {
-                                Ok(false) => {
-                                    return (
-                                        id,
-                                        Err(KeyPoolError::Response(ResponseError::Api {
-                                            code,
-                                            reason,
-                                        })),
-                                    )
-                                }
-                                Ok(true) => (),
-                                Err(why) => return (id, Err(KeyPoolError::Storage(Arc::new(why)))),
-                            }
-                        }
-                        Err(parsing_error) => {
-                            return (id, Err(KeyPoolError::Response(parsing_error)))
+        let run = |id: I, key: Option<S::Key>| async move {
+            let id_string = id.to_string();
+            let mut key = match key {
+                Some(key) => key,
+                None => match self.storage.acquire_key(selector_ref.clone()).await {
+                    Ok(k) => k,
+                    Err(why) => return (id, Err(Self::Error::Storage(Arc::new(why)))),
+                },
+            };
+            let mut rate_limited = false;
+            loop {
+                let url = request_ref.url(key.value(), Some(&id_string));
+                let raw = match client.request_buffered(url, &self.buffers).await {
+                    Ok(r) => r,
+                    Err(why) => return (id, Err(Self::Error::Client(why))),
+                };
+
+                match self.parse(raw) {
+                    Err(
+                        why @ (ResponseError::Key { code, .. }
+                        | ResponseError::Service { code, .. }),
+                    ) => match self.storage.flag_key(key, code).await {
+                        Ok(false) => return (id, Err(KeyPoolError::Response(why))),
+                        Ok(true) if code == ApiErrorCode::TooManyRequests && rate_limited => {
+                            return (id, Err(KeyPoolError::Response(why)))
                         }
-                        Ok(res) => return (id, Ok(res)),
-                    };
+                        Ok(true) => rate_limited |= code == ApiErrorCode::TooManyRequests,
+                        Err(why) => return (id, Err(KeyPoolError::Storage(Arc::new(why)))),
+                    },
+                    Err(why) => return (id, Err(KeyPoolError::Response(why))),
+                    Ok(res) => return (id, Ok(res)),
+                };
 
-                    key = match self.storage.acquire_key(self.selector.clone()).await {
-                        Ok(k) => k,
-                        Err(why) => return (id, Err(Self::Error::Storage(Arc::new(why)))),
-                    };
-                }
-            }))
-            .await;
+                key = match self.storage.acquire_key(selector_ref.clone()).await {
+                    Ok(k) => k,
+                    Err(why) => return (id, Err(Self::Error::Storage(Arc::new(why)))),
+                };
+            }
+        };
 
-        HashMap::from_iter(tuples)
+        self.run_batched(&selector, ids, run).await
     }
 }
 
@@ -135,6 +148,10 @@ where
     client: C,
     pub storage: S,
     comment: Option<String>,
+    pacing: Option<Duration>,
+    hook: Option<Arc<dyn DeserializeHook>>,
+    strictness: Strictness,
+    buffers: Arc<BufferPool>,
 }
 
 impl<C, S> KeyPool<C, S>
@@ -147,21 +164,49 @@ where
             client,
             storage,
             comment,
+            pacing: None,
+            hook: None,
+            strictness: Strictness::default(),
+            buffers: Default::default(),
         }
     }
 
-    pub fn torn_api<I>(&self, selector: I) -> ApiProvider<C, KeyPoolExecutor<C, S>>
+    /// See [`KeyPoolExecutor::pacing`].
+    #[cfg(any(feature = "tokio-runtime", feature = "actix-runtime"))]
+    #[must_use]
+    pub fn pacing(mut self, window: Duration) -> Self {
+        self.pacing = Some(window);
+        self
+    }
+
+    /// See [`KeyPoolExecutor::deserialize_hook`].
+    #[must_use]
+    pub fn deserialize_hook(mut self, hook: Arc<dyn DeserializeHook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// See [`KeyPoolExecutor::strictness`].
+    #[must_use]
+    pub fn strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    pub fn torn_api<I>(&self, selector: I) -> ApiProvider<'_, C, KeyPoolExecutor<'_, C, S>>
     where
         I: IntoSelector<S::Key, S::Domain>,
     {
-        ApiProvider::new(
-            &self.client,
-            KeyPoolExecutor::new(
-                &self.storage,
-                selector.into_selector(),
-                self.comment.as_deref(),
-            ),
-        )
+        let mut executor = KeyPoolExecutor::new(
+            &self.storage,
+            selector.into_selector(),
+            self.comment.as_deref(),
+        );
+        executor.pacing = self.pacing;
+        executor.hook = self.hook.clone();
+        executor.strictness = self.strictness;
+        executor.buffers = self.buffers.clone();
+        ApiProvider::new(&self.client, executor)
     }
 }
 
@@ -170,7 +215,7 @@ pub trait WithStorage {
         &'a self,
         storage: &'a S,
         selector: I,
-    ) -> ApiProvider<Self, KeyPoolExecutor<Self, S>>
+    ) -> ApiProvider<'a, Self, KeyPoolExecutor<'a, Self, S>>
     where
         Self: ApiClient + Sized,
         S: KeyPoolStorage + Send + Sync + 'static,